@@ -12,7 +12,8 @@ use scale_info::Variant;
 use scale_info::form::PortableForm;
 
 use subxt_metadata::{
-    ConstantMetadata, Metadata, PalletMetadata, RuntimeApiMetadata, StorageEntryMetadata,
+    ConstantMetadata, Metadata, PalletMetadata, RuntimeApiMetadata, SemanticDiff,
+    StorageEntryMetadata,
 };
 
 /// Explore the differences between two nodes
@@ -32,6 +33,10 @@ pub struct Opts {
     /// Allow insecure URLs e.g. URLs starting with ws:// or http:// without SSL encryption
     #[clap(long, short)]
     allow_insecure: bool,
+    /// Output each change as JSON, classified as `breaking`, `additive` or `cosmetic`,
+    /// along with the most severe change overall (or `null` if nothing changed).
+    #[clap(long)]
+    json: bool,
 }
 
 pub async fn run(opts: Opts, output: &mut impl std::io::Write) -> color_eyre::Result<()> {
@@ -40,6 +45,13 @@ pub async fn run(opts: Opts, output: &mut impl std::io::Write) -> color_eyre::Re
 
     let (entry_1_metadata, entry_2_metadata) = get_metadata(&opts).await?;
 
+    if opts.json {
+        let semantic_diff = entry_1_metadata.semantic_diff(&entry_2_metadata);
+        let json = semantic_diff_to_json(&semantic_diff);
+        writeln!(output, "{}", serde_json::to_string_pretty(&json)?)?;
+        return Ok(());
+    }
+
     let metadata_diff = MetadataDiff::construct(&entry_1_metadata, &entry_2_metadata);
 
     if metadata_diff.is_empty() {
@@ -268,6 +280,27 @@ impl StorageEntryDiff {
     }
 }
 
+fn semantic_diff_to_json(diff: &SemanticDiff) -> serde_json::Value {
+    let changes: Vec<_> = diff
+        .changes()
+        .iter()
+        .map(|change| {
+            serde_json::json!({
+                "item": change.item().to_string(),
+                "kind": change.item().kind(),
+                "severity": change.severity().as_str(),
+                "description": change.description(),
+                "type_path": change.type_path(),
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "severity": diff.severity().map(|s| s.as_str()),
+        "changes": changes,
+    })
+}
+
 async fn get_metadata(opts: &Opts) -> color_eyre::Result<(Metadata, Metadata)> {
     let bytes = opts.metadata_or_url_1.fetch().await?;
     let entry_1_metadata: Metadata =
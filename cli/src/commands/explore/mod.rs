@@ -449,6 +449,23 @@ pub mod tests {
         assert_eq_start!(output, start);
     }
 
+    #[tokio::test]
+    async fn type_shapes_are_described() {
+        // Storage values are expanded into their fields:
+        let output = run_against_file("pallet System storage Account")
+            .await
+            .unwrap()
+            .strip_ansi();
+        assert!(output.contains("struct AccountInfo"));
+        assert!(output.contains("nonce: u32"));
+
+        let output = run_against_file("pallet System constants BlockHashCount")
+            .await
+            .unwrap()
+            .strip_ansi();
+        assert!(output.contains("following shape:\n    u32\n"));
+    }
+
     #[tokio::test]
     async fn insecure_urls_get_denied() {
         // Connection should work fine:
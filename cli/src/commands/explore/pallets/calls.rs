@@ -75,7 +75,7 @@ pub fn explore_calls(
             .iter()
             .map(|f| (f.name.as_deref(), f.ty.id))
             .collect();
-        let type_description = fields_description(&fields, &call.name, metadata.types()).indent(4);
+        let type_description = fields_description(&fields, &call.name, &metadata).indent(4);
         let fields_example =
             fields_composite_example(call.fields.iter().map(|e| e.ty.id), metadata.types())
                 .indent(4)
@@ -1,10 +1,11 @@
 use clap::Args;
 use color_eyre::eyre::eyre;
 use indoc::{formatdoc, writedoc};
-use scale_typegen_description::type_description;
 use subxt::metadata::{ArcMetadata, PalletMetadata};
 
-use crate::utils::{Indent, SyntaxHighlight, first_paragraph_of_docs, format_scale_value};
+use crate::utils::{
+    Indent, SyntaxHighlight, first_paragraph_of_docs, format_scale_value, type_description,
+};
 
 #[derive(Debug, Clone, Args)]
 pub struct ConstantsSubcommand {
@@ -58,8 +59,7 @@ pub fn explore_constants(
     }
 
     // shape
-    let type_description = type_description(constant.ty(), metadata.types(), true)
-        .expect("No Type Description")
+    let type_description = type_description(constant.ty(), &metadata)
         .indent(4)
         .highlight();
 
@@ -62,7 +62,7 @@ pub fn explore_events(
         .iter()
         .map(|f| (f.name.as_deref(), f.ty.id))
         .collect();
-    let type_description = fields_description(&fields, &event.name, metadata.types()).indent(4);
+    let type_description = fields_description(&fields, &event.name, &metadata).indent(4);
     writedoc!(
         output,
         "
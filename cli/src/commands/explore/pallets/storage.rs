@@ -1,7 +1,6 @@
 use clap::Args;
 use color_eyre::{eyre::bail, owo_colors::OwoColorize};
 use indoc::{formatdoc, writedoc};
-use scale_typegen_description::type_description;
 use scale_value::Value;
 use std::fmt::Write;
 use std::write;
@@ -9,7 +8,7 @@ use subxt::metadata::{ArcMetadata, PalletMetadata, StorageMetadata};
 
 use crate::utils::{
     FileOrUrl, Indent, SyntaxHighlight, create_client, first_paragraph_of_docs,
-    parse_string_into_scale_value, type_example,
+    parse_string_into_scale_value, type_description, type_example,
 };
 
 #[derive(Debug, Clone, Args)]
@@ -92,8 +91,7 @@ pub async fn explore_storage(
         "}?;
     }
 
-    let return_ty_description = type_description(return_ty_id, metadata.types(), true)
-        .expect("No type Description")
+    let return_ty_description = type_description(return_ty_id, &metadata)
         .indent(4)
         .highlight();
 
@@ -109,8 +107,7 @@ pub async fn explore_storage(
             "({})",
             storage_keys
                 .iter()
-                .map(|key| type_description(key.key_id, metadata.types(), true)
-                    .expect("No type Description"))
+                .map(|key| metadata.type_signature(key.key_id).to_string())
                 .collect::<Vec<_>>()
                 .join(", ")
         )
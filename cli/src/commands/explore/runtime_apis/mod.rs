@@ -1,6 +1,6 @@
 use crate::utils::{
     FileOrUrl, Indent, SyntaxHighlight, create_client, fields_composite_example,
-    fields_description, first_paragraph_of_docs, parse_string_into_scale_value,
+    fields_description, first_paragraph_of_docs, parse_string_into_scale_value, type_description,
 };
 
 use color_eyre::{
@@ -9,7 +9,6 @@ use color_eyre::{
 };
 
 use indoc::{formatdoc, writedoc};
-use scale_typegen_description::type_description;
 use scale_value::Value;
 use subxt::{
     Metadata,
@@ -103,8 +102,7 @@ pub async fn run<'a>(
 
         let fields: Vec<(Option<&str>, u32)> =
             method.inputs().map(|f| (Some(&*f.name), f.id)).collect();
-        let fields_description =
-            fields_description(&fields, method.name(), metadata.types()).indent(4);
+        let fields_description = fields_description(&fields, method.name(), metadata).indent(4);
 
         let fields_example =
             fields_composite_example(method.inputs().map(|e| e.id), metadata.types())
@@ -120,8 +118,7 @@ pub async fn run<'a>(
     };
 
     let execute_usage = || {
-        let output = type_description(method.output_ty(), metadata.types(), true)
-            .expect("No Type Description")
+        let output = type_description(method.output_ty(), metadata)
             .indent(4)
             .highlight();
         let input = input_values();
@@ -0,0 +1,93 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+use crate::utils::{FileOrUrl, validate_url_security};
+use clap::{Parser as ClapParser, Subcommand};
+use codec::Decode;
+use color_eyre::eyre::eyre;
+use subxt_metadata::Metadata;
+
+/// Generate a JSON Schema describing the JSON form of some type in the metadata.
+///
+/// The JSON described is that which `scale_value::Value`s serialize to, and so the
+/// schemas can be used to validate call arguments, storage values and so on before
+/// they are encoded.
+///
+/// # Example
+///
+/// ```text
+/// subxt json-schema --file=polkadot_metadata.scale call Balances transfer_keep_alive
+/// subxt json-schema --file=polkadot_metadata.scale runtime-api-output Core version
+/// ```
+#[derive(Debug, ClapParser)]
+pub struct Opts {
+    #[command(flatten)]
+    file_or_url: FileOrUrl,
+    #[command(subcommand)]
+    item: Item,
+    /// Allow insecure URLs e.g. URLs starting with ws:// or http:// without SSL encryption
+    #[clap(long, short)]
+    allow_insecure: bool,
+}
+
+/// The item to generate a JSON Schema for.
+#[derive(Debug, Subcommand)]
+enum Item {
+    /// The type with the given ID.
+    Type { id: u32 },
+    /// The arguments of a call.
+    Call { pallet: String, call: String },
+    /// The fields of an event.
+    Event { pallet: String, event: String },
+    /// The value of a storage entry.
+    Storage { pallet: String, entry: String },
+    /// The inputs to a Runtime API method.
+    RuntimeApiInputs { runtime_api: String, method: String },
+    /// The output of a Runtime API method.
+    RuntimeApiOutput { runtime_api: String, method: String },
+}
+
+pub async fn run(opts: Opts, output: &mut impl std::io::Write) -> color_eyre::Result<()> {
+    validate_url_security(opts.file_or_url.url.as_ref(), opts.allow_insecure)?;
+    let bytes = opts.file_or_url.fetch().await?;
+    let metadata = Metadata::decode(&mut &bytes[..])?;
+
+    let schema = match &opts.item {
+        Item::Type { id } => Some(metadata.type_json_schema(*id)),
+        Item::Call { pallet, call } => metadata.call_json_schema(pallet, call),
+        Item::Event { pallet, event } => metadata.event_json_schema(pallet, event),
+        Item::Storage { pallet, entry } => metadata.storage_value_json_schema(pallet, entry),
+        Item::RuntimeApiInputs {
+            runtime_api,
+            method,
+        } => metadata.runtime_api_inputs_json_schema(runtime_api, method),
+        Item::RuntimeApiOutput {
+            runtime_api,
+            method,
+        } => metadata.runtime_api_output_json_schema(runtime_api, method),
+    };
+
+    let schema = schema.ok_or_else(|| eyre!("{} not found in the metadata", opts.item))?;
+    writeln!(output, "{}", serde_json::to_string_pretty(&schema)?)?;
+    Ok(())
+}
+
+impl std::fmt::Display for Item {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Item::Type { id } => write!(f, "Type {id}"),
+            Item::Call { pallet, call } => write!(f, "Call {pallet}.{call}"),
+            Item::Event { pallet, event } => write!(f, "Event {pallet}.{event}"),
+            Item::Storage { pallet, entry } => write!(f, "Storage entry {pallet}.{entry}"),
+            Item::RuntimeApiInputs {
+                runtime_api,
+                method,
+            }
+            | Item::RuntimeApiOutput {
+                runtime_api,
+                method,
+            } => write!(f, "Runtime API method {runtime_api}_{method}"),
+        }
+    }
+}
@@ -7,5 +7,8 @@ pub mod codegen;
 pub mod compatibility;
 pub mod diff;
 pub mod explore;
+pub mod json_schema;
 pub mod metadata;
+pub mod spec_versions;
+pub mod storage_stats;
 pub mod version;
@@ -0,0 +1,81 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+use crate::utils::validate_url_security;
+use clap::Parser as ClapParser;
+use color_eyre::eyre::bail;
+use subxt::{OnlineClient, PolkadotConfig};
+use subxt_utils_fetchmetadata::Url;
+
+/// Discover the spec and transaction versions used by each range of blocks on a chain.
+///
+/// Rather than looking at every block, this binary searches for the blocks at which the
+/// versions change. The node must be an archive node in order to look up old blocks.
+///
+/// The JSON output (see `--format`) can be deserialized into a list of `SpecVersionForRange`s
+/// and handed to `SubstrateConfigBuilder::set_spec_version_for_block_ranges()`.
+///
+/// # Example
+///
+/// ```text
+/// subxt spec-versions --url wss://rpc.polkadot.io:443 --from 0 --to 1000000
+/// ```
+#[derive(Debug, ClapParser)]
+pub struct Opts {
+    /// The url of the substrate node to query. Defaults to `ws://127.0.0.1:9944`.
+    #[clap(long, value_parser)]
+    url: Option<Url>,
+    /// The first block to look at. Defaults to the genesis block.
+    #[clap(long, default_value = "0")]
+    from: u64,
+    /// The block to stop before. Defaults to the block after the latest finalized block.
+    #[clap(long)]
+    to: Option<u64>,
+    /// The format to display the ranges in: `text` or `json`.
+    #[clap(long, default_value = "text")]
+    format: String,
+    /// Allow insecure URLs e.g. URLs starting with ws:// or http:// without SSL encryption
+    #[clap(long, short)]
+    allow_insecure: bool,
+}
+
+pub async fn run(opts: Opts, output: &mut impl std::io::Write) -> color_eyre::Result<()> {
+    validate_url_security(opts.url.as_ref(), opts.allow_insecure)?;
+
+    if opts.format != "text" && opts.format != "json" {
+        bail!(
+            "Unsupported format `{}`, expected `text` or `json`",
+            opts.format
+        );
+    }
+
+    let client = match &opts.url {
+        Some(url) => OnlineClient::<PolkadotConfig>::from_url(url).await?,
+        None => OnlineClient::<PolkadotConfig>::new().await?,
+    };
+    let to = match opts.to {
+        Some(to) => to,
+        None => client.at_current_block().await?.block_number() + 1,
+    };
+
+    let ranges = client.discover_spec_versions(opts.from..to).await?;
+
+    if opts.format == "json" {
+        let json = serde_json::to_string_pretty(&ranges)?;
+        writeln!(output, "{json}")?;
+    } else {
+        for range in ranges {
+            writeln!(
+                output,
+                "blocks {}..{}: spec version {}, transaction version {}",
+                range.block_range.start,
+                range.block_range.end,
+                range.spec_version,
+                range.transaction_version
+            )?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,161 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+use crate::utils::validate_url_security;
+use clap::Parser as ClapParser;
+use color_eyre::owo_colors::OwoColorize;
+use std::collections::BTreeMap;
+use subxt::ext::futures::StreamExt;
+use subxt::storage::{StorageEntryStats, StorageSizeHistogram, StorageStatsOptions};
+use subxt::{OnlineClient, PolkadotConfig};
+use subxt_utils_fetchmetadata::Url;
+
+/// Show how many keys and bytes each storage entry and pallet occupies at some block.
+///
+/// Values are streamed from the node rather than all being loaded into memory,
+/// but gathering statistics for every pallet can take a long time on large chains.
+///
+/// # Example
+///
+/// ```text
+/// subxt storage-stats --url wss://rpc.polkadot.io:443 --pallet System --pallet Balances
+/// ```
+#[derive(Debug, ClapParser)]
+pub struct Opts {
+    /// The url of the substrate node to query. Defaults to `ws://127.0.0.1:9944`.
+    #[clap(long, value_parser)]
+    url: Option<Url>,
+    /// The block number to gather statistics at. Defaults to the latest finalized block.
+    #[clap(long)]
+    at_block: Option<u64>,
+    /// Only gather statistics for these pallets.
+    #[clap(long, use_value_delimiter = true, value_parser)]
+    pallet: Vec<String>,
+    /// Only gather statistics for these storage entries.
+    #[clap(long, use_value_delimiter = true, value_parser)]
+    entry: Vec<String>,
+    /// The number of storage values to ask the node for in each request.
+    #[clap(long)]
+    page_size: Option<u32>,
+    /// The number of largest items to show for each storage entry.
+    #[clap(long, default_value = "5")]
+    largest: usize,
+    /// Allow insecure URLs e.g. URLs starting with ws:// or http:// without SSL encryption
+    #[clap(long, short)]
+    allow_insecure: bool,
+}
+
+pub async fn run(opts: Opts, output: &mut impl std::io::Write) -> color_eyre::Result<()> {
+    validate_url_security(opts.url.as_ref(), opts.allow_insecure)?;
+
+    let client = match &opts.url {
+        Some(url) => OnlineClient::<PolkadotConfig>::from_url(url).await?,
+        None => OnlineClient::<PolkadotConfig>::new().await?,
+    };
+    let at_block = match opts.at_block {
+        Some(number) => client.at_block(number).await?,
+        None => client.at_current_block().await?,
+    };
+
+    writeln!(
+        output,
+        "Storage statistics at block {} ({:?}):\n",
+        at_block.block_number(),
+        at_block.block_hash()
+    )?;
+
+    let mut stats_options = StorageStatsOptions::new().largest_items(opts.largest);
+    if let Some(page_size) = opts.page_size {
+        stats_options = stats_options.page_size(page_size);
+    }
+
+    // Per pallet totals of (key count, key bytes, value bytes):
+    let mut pallet_totals: BTreeMap<String, (u64, u64, u64)> = BTreeMap::new();
+
+    let storage = at_block.storage();
+    let stats = storage.stats_matching(stats_options, |entry| {
+        let pallet_matches = opts.pallet.is_empty()
+            || opts
+                .pallet
+                .iter()
+                .any(|p| p.eq_ignore_ascii_case(entry.pallet_name()));
+        let entry_matches = opts.entry.is_empty()
+            || opts
+                .entry
+                .iter()
+                .any(|e| e.eq_ignore_ascii_case(entry.entry_name()));
+        pallet_matches && entry_matches
+    });
+    let mut stats = std::pin::pin!(stats);
+
+    while let Some(entry_stats) = stats.next().await {
+        let entry_stats = entry_stats?;
+        write_entry_stats(&entry_stats, output)?;
+
+        let totals = pallet_totals
+            .entry(entry_stats.pallet_name().to_owned())
+            .or_default();
+        totals.0 += entry_stats.key_count();
+        totals.1 += entry_stats.total_key_bytes();
+        totals.2 += entry_stats.total_value_bytes();
+    }
+
+    writeln!(output, "{}", "Pallet totals:".bold())?;
+    for (pallet_name, (key_count, key_bytes, value_bytes)) in pallet_totals {
+        writeln!(
+            output,
+            "    {pallet_name}: {key_count} keys, {key_bytes} key bytes, {value_bytes} value bytes"
+        )?;
+    }
+
+    Ok(())
+}
+
+fn write_entry_stats(
+    stats: &StorageEntryStats,
+    output: &mut impl std::io::Write,
+) -> color_eyre::Result<()> {
+    let name = format!("{}.{}", stats.pallet_name(), stats.entry_name());
+    writeln!(
+        output,
+        "{}: {} keys, {} key bytes, {} value bytes",
+        name.bold(),
+        stats.key_count(),
+        stats.total_key_bytes(),
+        stats.total_value_bytes()
+    )?;
+
+    if stats.key_count() == 0 {
+        writeln!(output)?;
+        return Ok(());
+    }
+
+    writeln!(output, "    value sizes:")?;
+    for (bucket, count) in stats.histogram().buckets().iter().enumerate() {
+        if *count == 0 {
+            continue;
+        }
+        let range = StorageSizeHistogram::bucket_range(bucket);
+        writeln!(
+            output,
+            "        {}..{} bytes: {count}",
+            range.start, range.end
+        )?;
+    }
+
+    if !stats.largest_items().is_empty() {
+        writeln!(output, "    largest items:")?;
+        for item in stats.largest_items() {
+            writeln!(
+                output,
+                "        0x{}: {} bytes",
+                hex::encode(item.key()),
+                item.value_size()
+            )?;
+        }
+    }
+
+    writeln!(output)?;
+    Ok(())
+}
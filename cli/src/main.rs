@@ -19,6 +19,9 @@ enum Command {
     Version(commands::version::Opts),
     Explore(commands::explore::Opts),
     ChainSpec(commands::chain_spec::Opts),
+    StorageStats(commands::storage_stats::Opts),
+    SpecVersions(commands::spec_versions::Opts),
+    JsonSchema(commands::json_schema::Opts),
 }
 
 #[tokio::main]
@@ -34,5 +37,8 @@ async fn main() -> color_eyre::Result<()> {
         Command::Version(opts) => commands::version::run(opts, &mut output),
         Command::Explore(opts) => commands::explore::run(opts, &mut output).await,
         Command::ChainSpec(opts) => commands::chain_spec::run(opts, &mut output).await,
+        Command::StorageStats(opts) => commands::storage_stats::run(opts, &mut output).await,
+        Command::SpecVersions(opts) => commands::spec_versions::run(opts, &mut output).await,
+        Command::JsonSchema(opts) => commands::json_schema::run(opts, &mut output).await,
     }
 }
@@ -7,11 +7,11 @@ use color_eyre::eyre::{bail, eyre};
 use color_eyre::owo_colors::OwoColorize;
 use heck::ToUpperCamelCase;
 use scale_info::PortableRegistry;
-use scale_typegen_description::{format_type_description, type_description};
+use scale_typegen_description::format_type_description;
 use std::fmt::Display;
 use std::str::FromStr;
 use std::{fs, io::Read, path::PathBuf};
-use subxt::{OnlineClient, PolkadotConfig};
+use subxt::{Metadata, OnlineClient, PolkadotConfig};
 
 use scale_value::Value;
 use subxt_utils_fetchmetadata::{self as fetch_metadata, MetadataVersion, Url};
@@ -156,11 +156,24 @@ pub fn fields_composite_example(
     Value::unnamed_composite(examples)
 }
 
+/// Returns a formatted description of the shape of some type, expanding any structs
+/// and enums that it contains.
+pub fn type_description(type_id: u32, metadata: &Metadata) -> String {
+    format_type_description(&expanded_type_signature(type_id, metadata))
+}
+
+fn expanded_type_signature(type_id: u32, metadata: &Metadata) -> String {
+    metadata
+        .type_signature(type_id)
+        .max_depth(usize::MAX)
+        .to_string()
+}
+
 /// Returns a field description that is already formatted.
 pub fn fields_description(
     fields: &[(Option<&str>, u32)],
     name: &str,
-    types: &PortableRegistry,
+    metadata: &Metadata,
 ) -> String {
     if fields.is_empty() {
         return "Zero Sized Type, no fields.".to_string();
@@ -170,8 +183,7 @@ pub fn fields_description(
     let fields = fields
         .iter()
         .map(|field| {
-            let field_description =
-                type_description(field.1, types, false).expect("No Description.");
+            let field_description = expanded_type_signature(field.1, metadata);
             if all_named {
                 let field_name = field.0.unwrap();
                 format!("{field_name}: {field_description}")
@@ -244,6 +244,8 @@ impl RuntimeGenerator {
         let metadata_hash = self.metadata.hasher().hash();
 
         let custom_values = generate_custom_values(&self.metadata, &type_gen, &crate_path);
+        let codegen_items = generate_codegen_items(&self.metadata, &crate_path);
+        let codegen_items_len = codegen_items.len();
 
         Ok(quote! {
             #( #item_mod_attrs )*
@@ -358,6 +360,20 @@ impl RuntimeGenerator {
                     runtime_metadata_hash == [ #(#metadata_hash,)* ]
                 }
 
+                // Each item that the static code makes use of, along with its hash.
+                pub static CODEGEN_ITEMS: [#crate_path::metadata::CodegenItem<'static>; #codegen_items_len] = [ #(#codegen_items,)* ];
+
+                /// Check each item that this statically generated code makes use of against the metadata provided,
+                /// reporting exactly which calls, storage entries, constants, events, runtime API methods and view
+                /// functions are missing or have changed.
+                ///
+                /// **Note:** Only these individual items are checked. Unlike [`is_codegen_valid_for`], changes to the
+                /// outer enums, the extrinsic format, custom values or the rest of a pallet (such as its errors) are not
+                /// noticed, so the report can be compatible even though [`is_codegen_valid_for`] returns false.
+                pub fn codegen_compatibility_report(metadata: &#crate_path::Metadata) -> #crate_path::metadata::CodegenCompatibilityReport {
+                    metadata.codegen_compatibility_report(&CODEGEN_ITEMS)
+                }
+
                 #( #modules )*
                 #types_mod
             }
@@ -365,6 +381,66 @@ impl RuntimeGenerator {
     }
 }
 
+/// Return a `CodegenItem` for each call, storage entry, constant, event, view function
+/// and runtime API method in the metadata, used to check the generated code item by item.
+fn generate_codegen_items(metadata: &Metadata, crate_path: &syn::Path) -> Vec<TokenStream2> {
+    let item = |kind: &str, parent: &str, name: &str, hash: [u8; 32]| {
+        let kind = format_ident!("{kind}");
+        quote! {
+            #crate_path::metadata::CodegenItem::new(
+                #crate_path::metadata::CodegenItemKind::#kind,
+                #parent,
+                #name,
+                [#(#hash,)*],
+            )
+        }
+    };
+
+    let mut items = Vec::new();
+    for pallet in metadata.pallets() {
+        let pallet_name = pallet.name();
+        for call in pallet.call_variants().unwrap_or_default() {
+            if let Some(hash) = pallet.call_hash(&call.name) {
+                items.push(item("Call", pallet_name, &call.name, hash));
+            }
+        }
+        for entry in pallet.storage().map(|s| s.entries()).unwrap_or_default() {
+            if let Some(hash) = pallet.storage_hash(entry.name()) {
+                items.push(item("StorageEntry", pallet_name, entry.name(), hash));
+            }
+        }
+        for constant in pallet.constants() {
+            if let Some(hash) = pallet.constant_hash(constant.name()) {
+                items.push(item("Constant", pallet_name, constant.name(), hash));
+            }
+        }
+        for event in pallet.event_variants().unwrap_or_default() {
+            if let Some(hash) = pallet.event_hash(&event.name) {
+                items.push(item("Event", pallet_name, &event.name, hash));
+            }
+        }
+        for view_function in pallet.view_functions() {
+            items.push(item(
+                "ViewFunction",
+                pallet_name,
+                view_function.name(),
+                view_function.hash(),
+            ));
+        }
+    }
+    for api in metadata.runtime_api_traits() {
+        for method in api.methods() {
+            items.push(item(
+                "RuntimeApiMethod",
+                api.name(),
+                method.name(),
+                method.hash(),
+            ));
+        }
+    }
+    items
+}
+
 /// Return a vector of tuples of variant names and corresponding struct definitions.
 pub fn generate_structs_from_variants<F>(
     type_gen: &TypeGenerator,
@@ -0,0 +1,310 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+//! Check, item by item, whether some statically generated code is compatible with some metadata.
+
+use crate::Metadata;
+use crate::utils::validation::HASH_LEN;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// The kind of item that some statically generated code makes use of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CodegenItemKind {
+    /// A pallet call.
+    Call,
+    /// A pallet storage entry.
+    StorageEntry,
+    /// A pallet constant.
+    Constant,
+    /// A pallet event.
+    Event,
+    /// A pallet view function.
+    ViewFunction,
+    /// A runtime API method.
+    RuntimeApiMethod,
+}
+
+/// An item that some statically generated code makes use of, along with
+/// the hash of the item in the metadata that the code was generated from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodegenItem<'a> {
+    /// The kind of item.
+    pub kind: CodegenItemKind,
+    /// The name of the pallet that the item is in, or the name of
+    /// the runtime API trait for [`CodegenItemKind::RuntimeApiMethod`]s.
+    pub parent: &'a str,
+    /// The name of the item.
+    pub name: &'a str,
+    /// The hash of the item at the time that the code was generated.
+    pub hash: [u8; HASH_LEN],
+}
+
+impl<'a> CodegenItem<'a> {
+    /// Construct a new [`CodegenItem`].
+    pub const fn new(
+        kind: CodegenItemKind,
+        parent: &'a str,
+        name: &'a str,
+        hash: [u8; HASH_LEN],
+    ) -> Self {
+        CodegenItem {
+            kind,
+            parent,
+            name,
+            hash,
+        }
+    }
+}
+
+/// Why some item used by statically generated code is not compatible with the metadata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodegenIncompatibilityReason {
+    /// The item (or the pallet or runtime API trait that it lives in) no longer exists.
+    Missing,
+    /// The item exists, but its shape differs from the one that the code was generated for.
+    HashMismatch,
+}
+
+/// An item used by statically generated code which is not compatible with the metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodegenIncompatibility {
+    kind: CodegenItemKind,
+    parent: String,
+    name: String,
+    reason: CodegenIncompatibilityReason,
+}
+
+impl CodegenIncompatibility {
+    /// The kind of item.
+    pub fn kind(&self) -> CodegenItemKind {
+        self.kind
+    }
+
+    /// The name of the pallet (or runtime API trait) that the item is in.
+    pub fn parent(&self) -> &str {
+        &self.parent
+    }
+
+    /// The name of the item.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Why the item is not compatible.
+    pub fn reason(&self) -> CodegenIncompatibilityReason {
+        self.reason
+    }
+}
+
+/// A report detailing which items used by some statically generated code are
+/// missing from, or differ in, some metadata. See [`Metadata::codegen_compatibility_report()`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CodegenCompatibilityReport {
+    checked: usize,
+    issues: Vec<CodegenIncompatibility>,
+}
+
+impl CodegenCompatibilityReport {
+    /// Is every item compatible with the metadata?
+    pub fn is_compatible(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// The number of items that were checked.
+    pub fn checked_items(&self) -> usize {
+        self.checked
+    }
+
+    /// All of the items which are not compatible with the metadata.
+    pub fn issues(&self) -> &[CodegenIncompatibility] {
+        &self.issues
+    }
+
+    /// The items which are not compatible with the metadata, grouped by the
+    /// pallet (or runtime API trait) that they are in.
+    pub fn issues_by_parent(&self) -> BTreeMap<&str, Vec<&CodegenIncompatibility>> {
+        let mut by_parent: BTreeMap<&str, Vec<&CodegenIncompatibility>> = BTreeMap::new();
+        for issue in &self.issues {
+            by_parent.entry(&issue.parent).or_default().push(issue);
+        }
+        by_parent
+    }
+
+    /// Is the given item compatible? Items that were not checked are assumed to be compatible.
+    pub fn is_item_compatible(&self, kind: CodegenItemKind, parent: &str, name: &str) -> bool {
+        !self
+            .issues
+            .iter()
+            .any(|issue| issue.kind == kind && issue.parent == parent && issue.name == name)
+    }
+}
+
+impl Metadata {
+    /// Check each of the given items against this metadata, reporting those which are
+    /// missing or whose hashes differ. Unlike comparing the hash of the whole metadata,
+    /// this tells us exactly which parts of some statically generated code can still be used.
+    ///
+    /// **Note:** Only the given items are checked. Things which are hashed when validating
+    /// the whole metadata but which aren't items, such as the outer enums, the extrinsic
+    /// format, custom values and pallet errors, are not, so a compatible report doesn't
+    /// guarantee that the hash of the whole metadata matches.
+    pub fn codegen_compatibility_report(
+        &self,
+        items: &[CodegenItem<'_>],
+    ) -> CodegenCompatibilityReport {
+        let issues = items
+            .iter()
+            .filter_map(|item| {
+                let reason = match self.item_hash(item) {
+                    None => CodegenIncompatibilityReason::Missing,
+                    Some(hash) if hash != item.hash => CodegenIncompatibilityReason::HashMismatch,
+                    Some(_) => return None,
+                };
+                Some(CodegenIncompatibility {
+                    kind: item.kind,
+                    parent: item.parent.to_string(),
+                    name: item.name.to_string(),
+                    reason,
+                })
+            })
+            .collect();
+
+        CodegenCompatibilityReport {
+            checked: items.len(),
+            issues,
+        }
+    }
+
+    fn item_hash(&self, item: &CodegenItem<'_>) -> Option<[u8; HASH_LEN]> {
+        let pallet = || self.pallet_by_name(item.parent);
+        match item.kind {
+            CodegenItemKind::Call => pallet()?.call_hash(item.name),
+            CodegenItemKind::StorageEntry => pallet()?.storage_hash(item.name),
+            CodegenItemKind::Constant => pallet()?.constant_hash(item.name),
+            CodegenItemKind::Event => pallet()?.event_hash(item.name),
+            CodegenItemKind::ViewFunction => pallet()?
+                .view_function_by_name(item.name)
+                .map(|view_function| view_function.hash()),
+            CodegenItemKind::RuntimeApiMethod => self
+                .runtime_api_trait_by_name(item.parent)?
+                .method_by_name(item.name)
+                .map(|method| method.hash()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::test_utils::polkadot_metadata_small;
+
+    #[test]
+    fn reports_missing_and_mismatched_items() {
+        let metadata = polkadot_metadata_small();
+        let system = metadata.pallet_by_name("System").unwrap();
+        let remark_hash = system.call_hash("remark").unwrap();
+        let account_hash = system.storage_hash("Account").unwrap();
+
+        let items = [
+            CodegenItem::new(CodegenItemKind::Call, "System", "remark", remark_hash),
+            CodegenItem::new(CodegenItemKind::StorageEntry, "System", "Account", [0; 32]),
+            CodegenItem::new(CodegenItemKind::Constant, "System", "NotAConstant", [0; 32]),
+            CodegenItem::new(CodegenItemKind::Event, "NotAPallet", "Foo", [0; 32]),
+            CodegenItem::new(
+                CodegenItemKind::RuntimeApiMethod,
+                "NotAnApi",
+                "foo",
+                [0; 32],
+            ),
+        ];
+        assert_ne!(account_hash, [0; 32]);
+
+        let report = metadata.codegen_compatibility_report(&items);
+        assert!(!report.is_compatible());
+        assert_eq!(report.checked_items(), 5);
+
+        let reasons: Vec<_> = report
+            .issues()
+            .iter()
+            .map(|issue| (issue.parent(), issue.name(), issue.reason()))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (
+                    "System",
+                    "Account",
+                    CodegenIncompatibilityReason::HashMismatch
+                ),
+                (
+                    "System",
+                    "NotAConstant",
+                    CodegenIncompatibilityReason::Missing
+                ),
+                ("NotAPallet", "Foo", CodegenIncompatibilityReason::Missing),
+                ("NotAnApi", "foo", CodegenIncompatibilityReason::Missing),
+            ]
+        );
+
+        assert!(report.is_item_compatible(CodegenItemKind::Call, "System", "remark"));
+        assert!(!report.is_item_compatible(CodegenItemKind::StorageEntry, "System", "Account"));
+        assert_eq!(report.issues_by_parent()["System"].len(), 2);
+    }
+
+    #[test]
+    fn every_item_in_metadata_is_compatible_with_itself() {
+        let metadata = polkadot_metadata_small();
+        let mut items = Vec::new();
+        for pallet in metadata.pallets() {
+            for call in pallet.call_variants().unwrap_or_default() {
+                let hash = pallet.call_hash(&call.name).unwrap();
+                items.push((
+                    CodegenItemKind::Call,
+                    pallet.name(),
+                    call.name.as_str(),
+                    hash,
+                ));
+            }
+            for event in pallet.event_variants().unwrap_or_default() {
+                let hash = pallet.event_hash(&event.name).unwrap();
+                items.push((
+                    CodegenItemKind::Event,
+                    pallet.name(),
+                    event.name.as_str(),
+                    hash,
+                ));
+            }
+            for constant in pallet.constants() {
+                let hash = pallet.constant_hash(constant.name()).unwrap();
+                items.push((
+                    CodegenItemKind::Constant,
+                    pallet.name(),
+                    constant.name(),
+                    hash,
+                ));
+            }
+        }
+        for api in metadata.runtime_api_traits() {
+            for method in api.methods() {
+                let hash = method.hash();
+                items.push((
+                    CodegenItemKind::RuntimeApiMethod,
+                    api.name(),
+                    method.name(),
+                    hash,
+                ));
+            }
+        }
+
+        let items: Vec<_> = items
+            .into_iter()
+            .map(|(kind, parent, name, hash)| CodegenItem::new(kind, parent, name, hash))
+            .collect();
+        let report = metadata.codegen_compatibility_report(&items);
+        assert!(report.is_compatible());
+        assert_eq!(report.checked_items(), items.len());
+    }
+}
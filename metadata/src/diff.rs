@@ -0,0 +1,348 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+//! Work out which pallets, calls, storage entries, constants, events and runtime APIs
+//! were added, removed or changed between two sets of metadata.
+
+use crate::utils::validation::HASH_LEN;
+use crate::{Metadata, PalletMetadata, RuntimeApiMetadata};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use hashbrown::{HashMap, HashSet};
+
+/// How some item differs between two sets of metadata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiffKind {
+    /// The item exists only in the new metadata.
+    Added,
+    /// The item exists only in the old metadata.
+    Removed,
+    /// The item exists in both, but its hash differs.
+    Changed,
+}
+
+/// A single item (for instance a call or storage entry) which differs between two sets of metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemDiff {
+    name: String,
+    kind: DiffKind,
+}
+
+impl ItemDiff {
+    /// The name of the item.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// How the item differs.
+    pub fn kind(&self) -> DiffKind {
+        self.kind
+    }
+}
+
+/// A pallet which differs between two sets of metadata. Only pallets which exist in both
+/// sets of metadata (ie whose kind is [`DiffKind::Changed`]) list the items which differ.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PalletDiff {
+    name: String,
+    kind: DiffKind,
+    calls: Vec<ItemDiff>,
+    storage_entries: Vec<ItemDiff>,
+    constants: Vec<ItemDiff>,
+    events: Vec<ItemDiff>,
+}
+
+impl PalletDiff {
+    /// The name of the pallet.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// How the pallet differs.
+    pub fn kind(&self) -> DiffKind {
+        self.kind
+    }
+
+    /// The calls which differ.
+    pub fn calls(&self) -> &[ItemDiff] {
+        &self.calls
+    }
+
+    /// The storage entries which differ.
+    pub fn storage_entries(&self) -> &[ItemDiff] {
+        &self.storage_entries
+    }
+
+    /// The constants which differ.
+    pub fn constants(&self) -> &[ItemDiff] {
+        &self.constants
+    }
+
+    /// The events which differ.
+    pub fn events(&self) -> &[ItemDiff] {
+        &self.events
+    }
+}
+
+/// A runtime API trait which differs between two sets of metadata. Only traits which exist
+/// in both sets of metadata (ie whose kind is [`DiffKind::Changed`]) list the methods which differ.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeApiDiff {
+    name: String,
+    kind: DiffKind,
+    methods: Vec<ItemDiff>,
+}
+
+impl RuntimeApiDiff {
+    /// The name of the runtime API trait.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// How the runtime API trait differs.
+    pub fn kind(&self) -> DiffKind {
+        self.kind
+    }
+
+    /// The methods which differ.
+    pub fn methods(&self) -> &[ItemDiff] {
+        &self.methods
+    }
+}
+
+/// The differences between two sets of metadata. See [`Metadata::diff()`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetadataDiff {
+    pallets: Vec<PalletDiff>,
+    runtime_apis: Vec<RuntimeApiDiff>,
+}
+
+impl MetadataDiff {
+    /// Are the two sets of metadata the same?
+    pub fn is_empty(&self) -> bool {
+        self.pallets.is_empty() && self.runtime_apis.is_empty()
+    }
+
+    /// The pallets which differ.
+    pub fn pallets(&self) -> &[PalletDiff] {
+        &self.pallets
+    }
+
+    /// The runtime API traits which differ.
+    pub fn runtime_apis(&self) -> &[RuntimeApiDiff] {
+        &self.runtime_apis
+    }
+}
+
+impl Metadata {
+    /// Compare this (old) metadata with some new metadata, returning the pallets, calls,
+    /// storage entries, constants, events and runtime APIs which were added, removed or
+    /// changed. Items are compared using the same hashes that are used to validate
+    /// statically generated code, so a change here means that such code may no longer work.
+    pub fn diff(&self, new: &Metadata) -> MetadataDiff {
+        let pallets = diff_items(
+            self.pallets().map(|p| (p.name(), p.hash())),
+            new.pallets().map(|p| (p.name(), p.hash())),
+        )
+        .into_iter()
+        .map(|(name, kind)| match kind {
+            DiffKind::Changed => {
+                let old = self
+                    .pallet_by_name(name)
+                    .expect("pallet exists in old metadata");
+                let new = new
+                    .pallet_by_name(name)
+                    .expect("pallet exists in new metadata");
+                pallet_diff(old, new)
+            }
+            _ => PalletDiff {
+                name: name.to_string(),
+                kind,
+                calls: Vec::new(),
+                storage_entries: Vec::new(),
+                constants: Vec::new(),
+                events: Vec::new(),
+            },
+        })
+        .collect();
+
+        let runtime_apis = diff_items(
+            self.runtime_api_traits()
+                .map(|api| (api.name(), api.hash())),
+            new.runtime_api_traits().map(|api| (api.name(), api.hash())),
+        )
+        .into_iter()
+        .map(|(name, kind)| {
+            let methods = match kind {
+                DiffKind::Changed => {
+                    let old = self
+                        .runtime_api_trait_by_name(name)
+                        .expect("runtime API exists in old metadata");
+                    let new = new
+                        .runtime_api_trait_by_name(name)
+                        .expect("runtime API exists in new metadata");
+                    runtime_api_method_diffs(old, new)
+                }
+                _ => Vec::new(),
+            };
+            RuntimeApiDiff {
+                name: name.to_string(),
+                kind,
+                methods,
+            }
+        })
+        .collect();
+
+        MetadataDiff {
+            pallets,
+            runtime_apis,
+        }
+    }
+}
+
+fn pallet_diff(old: PalletMetadata<'_>, new: PalletMetadata<'_>) -> PalletDiff {
+    let calls = diff_items(
+        old.call_variants()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|v| Some((v.name.as_str(), old.call_hash(&v.name)?))),
+        new.call_variants()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|v| Some((v.name.as_str(), new.call_hash(&v.name)?))),
+    );
+    let storage_entries = diff_items(
+        old.storage()
+            .map(|s| s.entries())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|e| Some((e.name(), old.storage_hash(e.name())?))),
+        new.storage()
+            .map(|s| s.entries())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|e| Some((e.name(), new.storage_hash(e.name())?))),
+    );
+    let constants = diff_items(
+        old.constants()
+            .filter_map(|c| Some((c.name(), old.constant_hash(c.name())?))),
+        new.constants()
+            .filter_map(|c| Some((c.name(), new.constant_hash(c.name())?))),
+    );
+    let events = diff_items(
+        old.event_variants()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|v| Some((v.name.as_str(), old.event_hash(&v.name)?))),
+        new.event_variants()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|v| Some((v.name.as_str(), new.event_hash(&v.name)?))),
+    );
+
+    PalletDiff {
+        name: old.name().to_string(),
+        kind: DiffKind::Changed,
+        calls: to_item_diffs(calls),
+        storage_entries: to_item_diffs(storage_entries),
+        constants: to_item_diffs(constants),
+        events: to_item_diffs(events),
+    }
+}
+
+fn runtime_api_method_diffs(
+    old: RuntimeApiMetadata<'_>,
+    new: RuntimeApiMetadata<'_>,
+) -> Vec<ItemDiff> {
+    let methods = diff_items(
+        old.methods().map(|m| (m.name(), m.hash())),
+        new.methods().map(|m| (m.name(), m.hash())),
+    );
+    to_item_diffs(methods)
+}
+
+fn to_item_diffs(items: Vec<(&str, DiffKind)>) -> Vec<ItemDiff> {
+    items
+        .into_iter()
+        .map(|(name, kind)| ItemDiff {
+            name: name.to_string(),
+            kind,
+        })
+        .collect()
+}
+
+/// Given the names and hashes of some old and new items, return the names of those which
+/// were removed or changed (in the order of the old items), followed by those which were
+/// added (in the order of the new items).
+fn diff_items<'a>(
+    old: impl Iterator<Item = (&'a str, [u8; HASH_LEN])>,
+    new: impl Iterator<Item = (&'a str, [u8; HASH_LEN])>,
+) -> Vec<(&'a str, DiffKind)> {
+    let new: Vec<_> = new.collect();
+    let new_hashes: HashMap<&str, [u8; HASH_LEN]> = new.iter().copied().collect();
+
+    let mut diffs = Vec::new();
+    let mut old_names = HashSet::new();
+    for (name, old_hash) in old {
+        old_names.insert(name);
+        match new_hashes.get(name) {
+            None => diffs.push((name, DiffKind::Removed)),
+            Some(new_hash) if *new_hash != old_hash => diffs.push((name, DiffKind::Changed)),
+            Some(_) => {}
+        }
+    }
+    for (name, _) in new {
+        if !old_names.contains(name) {
+            diffs.push((name, DiffKind::Added));
+        }
+    }
+    diffs
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::test_utils::{metadata_from_artifact, polkadot_metadata_small};
+
+    #[test]
+    fn identical_metadata_has_no_diff() {
+        let metadata = polkadot_metadata_small();
+        assert!(metadata.diff(&metadata).is_empty());
+    }
+
+    #[test]
+    fn diff_items_finds_added_removed_and_changed() {
+        let old = [("a", [0; 32]), ("b", [1; 32]), ("c", [2; 32])];
+        let new = [("d", [3; 32]), ("c", [9; 32]), ("a", [0; 32])];
+
+        let diffs = diff_items(old.into_iter(), new.into_iter());
+        assert_eq!(
+            diffs,
+            vec![
+                ("b", DiffKind::Removed),
+                ("c", DiffKind::Changed),
+                ("d", DiffKind::Added),
+            ]
+        );
+    }
+
+    #[test]
+    fn removed_pallets_are_reported() {
+        let small = polkadot_metadata_small();
+        let tiny = metadata_from_artifact("polkadot_metadata_tiny.scale");
+
+        let diff = small.diff(&tiny);
+        for pallet in small.pallets() {
+            if tiny.pallet_by_name(pallet.name()).is_none() {
+                let pallet_diff = diff
+                    .pallets()
+                    .iter()
+                    .find(|p| p.name() == pallet.name())
+                    .expect("removed pallet should be in the diff");
+                assert_eq!(pallet_diff.kind(), DiffKind::Removed);
+                assert!(pallet_diff.calls().is_empty());
+            }
+        }
+    }
+}
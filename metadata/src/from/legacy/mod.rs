@@ -3,6 +3,7 @@ mod portable_registry_builder;
 mod tests;
 
 use crate::Metadata;
+use crate::storage_keys::storage_key_prefixes;
 use crate::utils::ordered_map::OrderedMap;
 use crate::utils::variant_index::VariantIndex;
 use alloc::borrow::ToOwned;
@@ -295,12 +296,15 @@ macro_rules! from_historic {
             // Runtime API definitions live with type definitions.
             let apis = type_registry_to_runtime_apis(&types, &mut portable_registry_builder)?;
 
+            let storage_key_prefixes = storage_key_prefixes(&new_pallets);
+
             Ok(crate::Metadata {
                 types: portable_registry_builder.finish(),
                 pallets: new_pallets,
                 pallets_by_call_index,
                 pallets_by_error_index,
                 pallets_by_event_index,
+                storage_key_prefixes,
                 extrinsic: new_extrinsic,
                 outer_enums,
                 dispatch_error_ty,
@@ -345,6 +349,7 @@ pub fn type_registry_to_runtime_apis(
                         crate::RuntimeApiMetadataInner {
                             name: trait_name.into(),
                             methods: trait_methods,
+                            version: 0,
                             docs: Vec::new(),
                         },
                     );
@@ -4,6 +4,7 @@
 
 use super::TryFromError;
 
+use crate::storage_keys::storage_key_prefixes;
 use crate::utils::variant_index::VariantIndex;
 use crate::{
     ConstantMetadata, CustomMetadataInner, ExtrinsicMetadata, Metadata, OuterEnumsMetadata,
@@ -98,12 +99,15 @@ impl TryFrom<v14::RuntimeMetadataV14> for Metadata {
             .find(|ty| ty.ty.path.segments == ["sp_runtime", "DispatchError"])
             .map(|ty| ty.id);
 
+        let storage_key_prefixes = storage_key_prefixes(&pallets);
+
         Ok(Metadata {
             types: m.types,
             pallets,
             pallets_by_call_index: pallets_by_index.clone(),
             pallets_by_error_index: pallets_by_index.clone(),
             pallets_by_event_index: pallets_by_index,
+            storage_key_prefixes,
             extrinsic: from_extrinsic_metadata(m.extrinsic, missing_extrinsic_type_ids),
             dispatch_error_ty,
             outer_enums: OuterEnumsMetadata {
@@ -4,6 +4,7 @@
 
 use super::TryFromError;
 
+use crate::storage_keys::storage_key_prefixes;
 use crate::utils::variant_index::VariantIndex;
 use crate::{
     ConstantMetadata, ExtrinsicMetadata, Metadata, OuterEnumsMetadata, PalletMetadataInner,
@@ -131,6 +132,7 @@ impl TryFrom<v16::RuntimeMetadataV16> for Metadata {
                 let runtime_api_metadata = RuntimeApiMetadataInner {
                     name: trait_name.clone(),
                     methods,
+                    version: api.version.0,
                     docs: api.docs.clone(),
                 };
                 Ok((trait_name, runtime_api_metadata))
@@ -156,12 +158,15 @@ impl TryFrom<v16::RuntimeMetadataV16> for Metadata {
             .find(|ty| ty.ty.path.segments == ["sp_runtime", "DispatchError"])
             .map(|ty| ty.id);
 
+        let storage_key_prefixes = storage_key_prefixes(&pallets);
+
         Ok(Metadata {
             types: m.types,
             pallets,
             pallets_by_call_index: pallets_by_index.clone(),
             pallets_by_error_index: pallets_by_index.clone(),
             pallets_by_event_index: pallets_by_index,
+            storage_key_prefixes,
             extrinsic: from_extrinsic_metadata(m.extrinsic),
             dispatch_error_ty,
             apis,
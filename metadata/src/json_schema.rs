@@ -0,0 +1,497 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+//! Generate JSON Schema documents describing the JSON that `scale_value::Value`s
+//! serialize to (via `serde`) for types in the metadata.
+//!
+//! In this shape, structs with named fields are JSON objects, structs with unnamed
+//! fields, tuples, sequences and arrays are JSON arrays, and enum variants are objects
+//! of the form `{ "name": "VariantName", "values": <fields> }`. Compact values look like
+//! the value they wrap, 256 bit numbers are arrays of 32 bytes, and bit sequences are
+//! arrays of booleans.
+
+use crate::Metadata;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use scale_info::{Field, PortableRegistry, TypeDef, TypeDefPrimitive, form::PortableForm};
+use serde_json::{Map, Value, json};
+
+/// The JSON Schema dialect that generated schemas conform to.
+const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+impl Metadata {
+    /// Return a JSON Schema document for the type with the given ID.
+    pub fn type_json_schema(&self, type_id: u32) -> Value {
+        let mut builder = SchemaBuilder::new(&self.types);
+        let schema = builder.ty(type_id);
+        builder.document(schema)
+    }
+
+    /// Return a JSON Schema document for the arguments of the given call, which are
+    /// given as a JSON object if the call has named arguments and an array otherwise.
+    /// Returns `None` if the call does not exist.
+    pub fn call_json_schema(&self, pallet_name: &str, call_name: &str) -> Option<Value> {
+        let pallet = self.pallet_by_name(pallet_name)?;
+        let call = pallet.call_variant_by_name(call_name)?;
+        let mut builder = SchemaBuilder::new(&self.types);
+        let schema = builder.fields(&call.fields);
+        Some(builder.document(schema))
+    }
+
+    /// Return a JSON Schema document for the fields of the given event, which are given
+    /// as a JSON object if the event has named fields and an array otherwise. Returns
+    /// `None` if the event does not exist.
+    pub fn event_json_schema(&self, pallet_name: &str, event_name: &str) -> Option<Value> {
+        let pallet = self.pallet_by_name(pallet_name)?;
+        let event = pallet
+            .event_variants()?
+            .iter()
+            .find(|v| v.name == event_name)?;
+        let mut builder = SchemaBuilder::new(&self.types);
+        let schema = builder.fields(&event.fields);
+        Some(builder.document(schema))
+    }
+
+    /// Return a JSON Schema document for the values stored in the given storage entry.
+    /// Returns `None` if the storage entry does not exist.
+    pub fn storage_value_json_schema(&self, pallet_name: &str, entry_name: &str) -> Option<Value> {
+        let entry = self
+            .pallet_by_name(pallet_name)?
+            .storage()?
+            .entry_by_name(entry_name)?;
+        Some(self.type_json_schema(entry.value_ty()))
+    }
+
+    /// Return a JSON Schema document for the inputs to the given Runtime API method,
+    /// which are given as a JSON array with one item per input. Returns `None` if the
+    /// Runtime API method does not exist.
+    pub fn runtime_api_inputs_json_schema(
+        &self,
+        trait_name: &str,
+        method_name: &str,
+    ) -> Option<Value> {
+        let method = self
+            .runtime_api_trait_by_name(trait_name)?
+            .method_by_name(method_name)?;
+        let mut builder = SchemaBuilder::new(&self.types);
+        let items: Vec<Value> = method
+            .inputs()
+            .map(|input| {
+                let mut schema = builder.ty(input.id);
+                set_if_object(&mut schema, "title", Value::String(input.name.to_string()));
+                schema
+            })
+            .collect();
+        let schema = tuple_schema(items);
+        Some(builder.document(schema))
+    }
+
+    /// Return a JSON Schema document for the value returned from the given Runtime API
+    /// method. Returns `None` if the Runtime API method does not exist.
+    pub fn runtime_api_output_json_schema(
+        &self,
+        trait_name: &str,
+        method_name: &str,
+    ) -> Option<Value> {
+        let method = self
+            .runtime_api_trait_by_name(trait_name)?
+            .method_by_name(method_name)?;
+        Some(self.type_json_schema(method.output_ty()))
+    }
+}
+
+/// Builds schemas for types, placing named types into `$defs` so that they are only
+/// described once, and so that recursive types can refer to themselves.
+struct SchemaBuilder<'a> {
+    types: &'a PortableRegistry,
+    defs: Map<String, Value>,
+}
+
+impl<'a> SchemaBuilder<'a> {
+    fn new(types: &'a PortableRegistry) -> Self {
+        SchemaBuilder {
+            types,
+            defs: Map::new(),
+        }
+    }
+
+    // Wrap the given schema into a complete document, including any definitions it refers to.
+    fn document(self, schema: Value) -> Value {
+        let mut doc = Map::new();
+        doc.insert(
+            "$schema".to_string(),
+            Value::String(SCHEMA_DIALECT.to_string()),
+        );
+        match schema {
+            Value::Object(schema) => doc.extend(schema),
+            schema => {
+                doc.insert("allOf".to_string(), Value::Array(Vec::from([schema])));
+            }
+        }
+        if !self.defs.is_empty() {
+            doc.insert("$defs".to_string(), Value::Object(self.defs));
+        }
+        Value::Object(doc)
+    }
+
+    fn ty(&mut self, type_id: u32) -> Value {
+        let Some(ty) = self.types.resolve(type_id) else {
+            // We know nothing about the type, so anything is allowed.
+            return Value::Bool(true);
+        };
+
+        let is_named = !ty.path.segments.is_empty()
+            && matches!(ty.type_def, TypeDef::Composite(_) | TypeDef::Variant(_));
+        if !is_named {
+            return self.type_def(&ty.type_def);
+        }
+
+        let key = type_id.to_string();
+        if !self.defs.contains_key(&key) {
+            // Insert a placeholder first, so that recursive types refer to this definition
+            // rather than trying to describe themselves again.
+            self.defs.insert(key.clone(), Value::Bool(true));
+            let mut schema = self.type_def(&ty.type_def);
+            set_if_object(
+                &mut schema,
+                "title",
+                Value::String(ty.path.segments.join("::")),
+            );
+            if let Some(docs) = docs(&ty.docs) {
+                set_if_object(&mut schema, "description", Value::String(docs));
+            }
+            self.defs.insert(key.clone(), schema);
+        }
+        json!({ "$ref": format!("#/$defs/{key}") })
+    }
+
+    fn type_def(&mut self, type_def: &TypeDef<PortableForm>) -> Value {
+        match type_def {
+            TypeDef::Composite(composite) => self.fields(&composite.fields),
+            TypeDef::Variant(variant) => {
+                let variants: Vec<Value> = variant
+                    .variants
+                    .iter()
+                    .map(|var| {
+                        let mut schema = json!({
+                            "type": "object",
+                            "properties": {
+                                "name": { "const": var.name },
+                                "values": self.fields(&var.fields),
+                            },
+                            "required": ["name", "values"],
+                            "additionalProperties": false,
+                        });
+                        if let Some(docs) = docs(&var.docs) {
+                            set_if_object(&mut schema, "description", Value::String(docs));
+                        }
+                        schema
+                    })
+                    .collect();
+
+                if variants.is_empty() {
+                    // No value can be constructed for an enum with no variants.
+                    Value::Bool(false)
+                } else {
+                    json!({ "oneOf": variants })
+                }
+            }
+            TypeDef::Sequence(seq) => json!({
+                "type": "array",
+                "items": self.ty(seq.type_param.id),
+            }),
+            TypeDef::Array(arr) => json!({
+                "type": "array",
+                "items": self.ty(arr.type_param.id),
+                "minItems": arr.len,
+                "maxItems": arr.len,
+            }),
+            TypeDef::Tuple(tuple) => {
+                let items = tuple.fields.iter().map(|f| self.ty(f.id)).collect();
+                tuple_schema(items)
+            }
+            TypeDef::Primitive(primitive) => primitive_schema(primitive),
+            TypeDef::Compact(compact) => self.ty(compact.type_param.id),
+            TypeDef::BitSequence(_) => json!({
+                "type": "array",
+                "items": { "type": "boolean" },
+            }),
+        }
+    }
+
+    // Named fields are serialized as an object, and unnamed fields (or no fields) as an array.
+    fn fields(&mut self, fields: &[Field<PortableForm>]) -> Value {
+        let is_named = !fields.is_empty() && fields.iter().all(|f| f.name.is_some());
+        if !is_named {
+            let items = fields.iter().map(|f| self.ty(f.ty.id)).collect();
+            return tuple_schema(items);
+        }
+
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        for field in fields {
+            let name = field.name.clone().unwrap_or_default();
+            let mut schema = self.ty(field.ty.id);
+            if let Some(docs) = docs(&field.docs) {
+                set_if_object(&mut schema, "description", Value::String(docs));
+            }
+            properties.insert(name.clone(), schema);
+            required.push(Value::String(name));
+        }
+
+        json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+            "additionalProperties": false,
+        })
+    }
+}
+
+fn tuple_schema(items: Vec<Value>) -> Value {
+    if items.is_empty() {
+        return json!({ "type": "array", "maxItems": 0 });
+    }
+    let len = items.len();
+    json!({
+        "type": "array",
+        "prefixItems": items,
+        "items": false,
+        "minItems": len,
+        "maxItems": len,
+    })
+}
+
+fn primitive_schema(primitive: &TypeDefPrimitive) -> Value {
+    match primitive {
+        TypeDefPrimitive::Bool => json!({ "type": "boolean" }),
+        TypeDefPrimitive::Char => json!({ "type": "string", "minLength": 1, "maxLength": 1 }),
+        TypeDefPrimitive::Str => json!({ "type": "string" }),
+        TypeDefPrimitive::U8 => integer_schema(0, u8::MAX as i64),
+        TypeDefPrimitive::U16 => integer_schema(0, u16::MAX as i64),
+        TypeDefPrimitive::U32 => integer_schema(0, u32::MAX as i64),
+        TypeDefPrimitive::U64 => json!({ "type": "integer", "minimum": 0, "maximum": u64::MAX }),
+        // JSON numbers this large can't be described without losing precision:
+        TypeDefPrimitive::U128 => json!({ "type": "integer", "minimum": 0 }),
+        TypeDefPrimitive::I8 => integer_schema(i8::MIN as i64, i8::MAX as i64),
+        TypeDefPrimitive::I16 => integer_schema(i16::MIN as i64, i16::MAX as i64),
+        TypeDefPrimitive::I32 => integer_schema(i32::MIN as i64, i32::MAX as i64),
+        TypeDefPrimitive::I64 => integer_schema(i64::MIN, i64::MAX),
+        TypeDefPrimitive::I128 => json!({ "type": "integer" }),
+        // 256 bit numbers are serialized as their 32 little endian bytes:
+        TypeDefPrimitive::U256 | TypeDefPrimitive::I256 => json!({
+            "type": "array",
+            "items": integer_schema(0, u8::MAX as i64),
+            "minItems": 32,
+            "maxItems": 32,
+        }),
+    }
+}
+
+fn integer_schema(minimum: i64, maximum: i64) -> Value {
+    json!({ "type": "integer", "minimum": minimum, "maximum": maximum })
+}
+
+// Add an annotation such as a title or description to a schema. Boolean schemas
+// can't hold annotations, and so are left alone.
+fn set_if_object(schema: &mut Value, key: &str, value: Value) {
+    if let Value::Object(schema) = schema {
+        schema.insert(key.to_string(), value);
+    }
+}
+
+fn docs(docs: &[String]) -> Option<String> {
+    let lines: Vec<&str> = docs.iter().map(|line| line.trim()).collect();
+    let docs = lines.join("\n");
+    let docs = docs.trim();
+    (!docs.is_empty()).then(|| docs.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use codec::{Compact, Encode};
+    use scale_info::{MetaType, Registry, TypeInfo};
+
+    #[allow(dead_code)]
+    #[derive(Encode, TypeInfo)]
+    enum Address {
+        Id([u8; 4]),
+        Index(Compact<u32>),
+        Named { name: String, primary: bool },
+        Nothing,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Encode, TypeInfo)]
+    struct Transfer {
+        dest: Address,
+        amounts: Vec<(u16, i64)>,
+        memo: Option<char>,
+        next: Option<Box<Transfer>>,
+    }
+
+    // Check some JSON against a schema, understanding just the keywords that we generate.
+    fn matches(schema: &Value, value: &Value, defs: &Map<String, Value>) -> bool {
+        let schema = match schema {
+            Value::Bool(b) => return *b,
+            Value::Object(schema) => schema,
+            _ => panic!("invalid schema {schema}"),
+        };
+        if let Some(Value::String(r)) = schema.get("$ref") {
+            let key = r.trim_start_matches("#/$defs/");
+            return matches(&defs[key], value, defs);
+        }
+        if let Some(Value::Array(options)) = schema.get("oneOf") {
+            return options.iter().filter(|o| matches(o, value, defs)).count() == 1;
+        }
+        if let Some(c) = schema.get("const") {
+            return c == value;
+        }
+        let int = |k: &str| schema.get(k).and_then(|v| v.as_i64());
+        match (schema.get("type").and_then(|t| t.as_str()), value) {
+            (Some("boolean"), Value::Bool(_)) => true,
+            (Some("string"), Value::String(s)) => {
+                let len = s.chars().count() as i64;
+                int("minLength").is_none_or(|m| len >= m)
+                    && int("maxLength").is_none_or(|m| len <= m)
+            }
+            (Some("integer"), Value::Number(n)) => match n.as_i64() {
+                Some(n) => {
+                    int("minimum").is_none_or(|m| n >= m) && int("maximum").is_none_or(|m| n <= m)
+                }
+                None => n.is_u64(),
+            },
+            (Some("array"), Value::Array(items)) => {
+                let len = items.len() as i64;
+                if int("minItems").is_some_and(|m| len < m)
+                    || int("maxItems").is_some_and(|m| len > m)
+                {
+                    return false;
+                }
+                let prefix = schema.get("prefixItems").and_then(|p| p.as_array());
+                items
+                    .iter()
+                    .enumerate()
+                    .all(|(idx, item)| match prefix.and_then(|p| p.get(idx)) {
+                        Some(s) => matches(s, item, defs),
+                        None => schema.get("items").is_none_or(|s| matches(s, item, defs)),
+                    })
+            }
+            (Some("object"), Value::Object(fields)) => {
+                let props = schema["properties"].as_object().unwrap();
+                let required = schema["required"].as_array().unwrap();
+                required
+                    .iter()
+                    .all(|r| fields.contains_key(r.as_str().unwrap()))
+                    && fields
+                        .iter()
+                        .all(|(k, v)| props.get(k).is_some_and(|s| matches(s, v, defs)))
+            }
+            _ => false,
+        }
+    }
+
+    // Encode a value, decode it into a `scale_value::Value`, and check that the JSON it
+    // serializes to matches the schema generated for its type.
+    fn check<T: Encode + TypeInfo + 'static>(value: T) {
+        let mut registry = Registry::new();
+        let id = registry.register_type(&MetaType::new::<T>()).id;
+        let types: PortableRegistry = registry.into();
+
+        let bytes = value.encode();
+        let decoded = scale_value::scale::decode_as_type(&mut &*bytes, id, &types).unwrap();
+        let json = serde_json::to_value(&decoded).unwrap();
+
+        let mut builder = SchemaBuilder::new(&types);
+        let schema = builder.ty(id);
+        let doc = builder.document(schema);
+        let defs = doc
+            .get("$defs")
+            .and_then(|d| d.as_object())
+            .cloned()
+            .unwrap_or_default();
+
+        assert!(matches(&doc, &json, &defs), "{json} does not match {doc:#}");
+    }
+
+    #[test]
+    fn schemas_match_serialized_values() {
+        check(true);
+        check('x');
+        check(String::from("hello"));
+        check(-5i64);
+        check(1u128 << 40);
+        check((1u8, [2u16; 3], ()));
+        check(Compact(1234u64));
+        check(Address::Id([1, 2, 3, 4]));
+        check(Address::Index(Compact(5)));
+        check(Address::Nothing);
+        check(Transfer {
+            dest: Address::Named {
+                name: String::from("Alice"),
+                primary: true,
+            },
+            amounts: vec![(1, -1), (2, i64::MAX)],
+            memo: Some('m'),
+            next: Some(Box::new(Transfer {
+                dest: Address::Nothing,
+                amounts: Vec::new(),
+                memo: None,
+                next: None,
+            })),
+        });
+    }
+
+    #[test]
+    fn variants_are_described_by_name_and_values() {
+        let mut registry = Registry::new();
+        let id = registry.register_type(&MetaType::new::<Address>()).id;
+        let types: PortableRegistry = registry.into();
+
+        let mut builder = SchemaBuilder::new(&types);
+        let schema = builder.ty(id);
+        let doc = builder.document(schema);
+
+        let address = &doc["$defs"][id.to_string()];
+        assert_eq!(
+            address["title"],
+            "subxt_metadata::json_schema::test::Address"
+        );
+        let nothing = &address["oneOf"][3];
+        assert_eq!(nothing["properties"]["name"]["const"], "Nothing");
+        assert_eq!(
+            nothing["properties"]["values"],
+            json!({ "type": "array", "maxItems": 0 })
+        );
+    }
+
+    #[test]
+    fn serialized_values_that_do_not_match_are_rejected() {
+        let mut registry = Registry::new();
+        let id = registry.register_type(&MetaType::new::<Transfer>()).id;
+        let types: PortableRegistry = registry.into();
+
+        let mut builder = SchemaBuilder::new(&types);
+        let schema = builder.ty(id);
+        let doc = builder.document(schema);
+        let defs = doc["$defs"].as_object().unwrap().clone();
+
+        let wrong_variant = json!({
+            "dest": { "name": "Missing", "values": [] },
+            "amounts": [],
+            "memo": { "name": "None", "values": [] },
+            "next": { "name": "None", "values": [] },
+        });
+        assert!(!matches(&doc, &wrong_variant, &defs));
+
+        let missing_field = json!({
+            "dest": { "name": "Nothing", "values": [] },
+            "amounts": [],
+            "memo": { "name": "None", "values": [] },
+        });
+        assert!(!matches(&doc, &missing_field, &defs));
+    }
+}
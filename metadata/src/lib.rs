@@ -13,13 +13,28 @@
 //!    from a node (this uses [`codec::Decode`]).
 //! 2. Obtaining [`frame_metadata::RuntimeMetadataPrefixed`], and then
 //!    using `.try_into()` to convert it into [`Metadata`].
+//!
+//! [`Metadata`] can be converted back into V15 or V16 [`frame_metadata`] using
+//! `Metadata::to_v15()` or `Metadata::to_v16()`, or encoded using `Metadata::encode_v16()`.
+//!
+//! With the `json-schema` feature enabled, JSON Schemas can be generated for types in the
+//! metadata using methods like `Metadata::type_json_schema()`.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 #![deny(missing_docs)]
 
 extern crate alloc;
 
+mod codegen_compatibility;
+mod diff;
 mod from;
+#[cfg(feature = "json-schema")]
+mod json_schema;
+mod semantic_diff;
+mod storage_keys;
+mod to;
+mod type_paths;
+mod type_signature;
 mod utils;
 
 use alloc::borrow::Cow;
@@ -48,9 +63,22 @@ use utils::{
     variant_index::VariantIndex,
 };
 
+pub use codegen_compatibility::{
+    CodegenCompatibilityReport, CodegenIncompatibility, CodegenIncompatibilityReason, CodegenItem,
+    CodegenItemKind,
+};
+pub use diff::{DiffKind, ItemDiff, MetadataDiff, PalletDiff, RuntimeApiDiff};
 pub use frame_decode::storage::StorageHasher;
 pub use from::SUPPORTED_METADATA_VERSIONS;
 pub use from::TryFromError;
+pub use semantic_diff::{ChangeSeverity, ChangedItem, SemanticChange, SemanticDiff};
+pub use storage_keys::{
+    IdentifiedStorageKey, StorageEntryKey, StorageEntryKeyPart, StorageKeyPartsError,
+    StorageValueDecodeError, WellKnownStorageKey,
+};
+pub use to::ToFrameMetadataError;
+pub use type_paths::TypePathError;
+pub use type_signature::TypeSignature;
 pub use utils::validation::MetadataHasher;
 
 #[cfg(feature = "legacy")]
@@ -82,6 +110,9 @@ pub struct Metadata {
     /// for modern metadatas, this is the same as pallets_by_call_index,
     /// but for old metadatas this can vary.
     pallets_by_error_index: HashMap<u8, usize>,
+    /// Find the storage entry or pallet storage version that a storage key points to,
+    /// given the first 32 bytes of the key.
+    storage_key_prefixes: HashMap<[u8; 32], storage_keys::StorageKeyPrefix>,
     /// Metadata of the extrinsic.
     extrinsic: ExtrinsicMetadata,
     /// The types of the outer enums.
@@ -801,6 +832,11 @@ impl<'a> PalletMetadata<'a> {
         crate::utils::validation::get_call_hash(self, call_name)
     }
 
+    /// Return a hash for the event, or None if it was not found.
+    pub fn event_hash(&self, event_name: &str) -> Option<[u8; HASH_LEN]> {
+        crate::utils::validation::get_event_hash(self, event_name)
+    }
+
     /// Return a hash for the entire pallet.
     pub fn hash(&self) -> [u8; HASH_LEN] {
         crate::utils::validation::get_pallet_hash(*self)
@@ -1093,6 +1129,10 @@ impl<'a> RuntimeApiMetadata<'a> {
     pub fn docs(&self) -> &[String] {
         &self.inner.docs
     }
+    /// Trait version. This is only provided in V16+ metadata, and is 0 otherwise.
+    pub fn version(&self) -> u32 {
+        self.inner.version
+    }
     /// An iterator over the trait methods.
     pub fn methods(&self) -> impl ExactSizeIterator<Item = RuntimeApiMethodMetadata<'a>> + use<'a> {
         self.inner
@@ -1128,6 +1168,8 @@ struct RuntimeApiMetadataInner {
     name: String,
     /// Trait methods.
     methods: OrderedMap<String, RuntimeApiMethodMetadataInner>,
+    /// Trait version. This is only provided in V16+ metadata, and is 0 otherwise.
+    version: u32,
     /// Trait documentation.
     docs: Vec<String>,
 }
@@ -0,0 +1,1048 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+//! Compare two sets of metadata item by item, and classify each change as breaking,
+//! additive or cosmetic depending on whether it affects how things are encoded and decoded.
+
+use crate::{Metadata, PalletMetadata, RuntimeApiMetadata};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use hashbrown::HashSet;
+use scale_info::{Field, PortableRegistry, TypeDef, Variant, form::PortableForm};
+
+/// How much a change between two sets of metadata matters. Severities are ordered, so
+/// that the most severe of several changes can be found with [`Iterator::max()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ChangeSeverity {
+    /// Nothing is encoded or decoded any differently, for instance because only docs,
+    /// type paths or constant values changed.
+    Cosmetic,
+    /// Something new was added, for instance a call or a variant with a new index,
+    /// but everything which existed before is encoded and decoded in the same way.
+    Additive,
+    /// Something which existed before was removed, or is now encoded or decoded
+    /// differently, for instance because a field type changed or a variant was re-indexed.
+    Breaking,
+}
+
+impl ChangeSeverity {
+    /// A lowercase name for the severity, ie `"cosmetic"`, `"additive"` or `"breaking"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeSeverity::Cosmetic => "cosmetic",
+            ChangeSeverity::Additive => "additive",
+            ChangeSeverity::Breaking => "breaking",
+        }
+    }
+}
+
+impl fmt::Display for ChangeSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The item which changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ChangedItem {
+    /// A pallet was added or removed, or something about the pallet itself changed.
+    Pallet {
+        /// The pallet name.
+        pallet: String,
+    },
+    /// A pallet call.
+    Call {
+        /// The pallet name.
+        pallet: String,
+        /// The call name.
+        name: String,
+    },
+    /// A pallet event.
+    Event {
+        /// The pallet name.
+        pallet: String,
+        /// The event name.
+        name: String,
+    },
+    /// A pallet error.
+    Error {
+        /// The pallet name.
+        pallet: String,
+        /// The error name.
+        name: String,
+    },
+    /// A pallet storage entry.
+    StorageEntry {
+        /// The pallet name.
+        pallet: String,
+        /// The storage entry name.
+        name: String,
+    },
+    /// A pallet constant.
+    Constant {
+        /// The pallet name.
+        pallet: String,
+        /// The constant name.
+        name: String,
+    },
+    /// A pallet View Function.
+    ViewFunction {
+        /// The pallet name.
+        pallet: String,
+        /// The View Function name.
+        name: String,
+    },
+    /// A Runtime API trait was added or removed, or its docs changed.
+    RuntimeApi {
+        /// The Runtime API trait name.
+        trait_name: String,
+    },
+    /// A Runtime API method.
+    RuntimeApiMethod {
+        /// The Runtime API trait name.
+        trait_name: String,
+        /// The method name.
+        name: String,
+    },
+    /// The extrinsic format, for instance its address or signature type, or its
+    /// transaction extensions.
+    Extrinsic,
+}
+
+impl ChangedItem {
+    /// A short name for the kind of item, for instance `"call"` or `"storage entry"`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ChangedItem::Pallet { .. } => "pallet",
+            ChangedItem::Call { .. } => "call",
+            ChangedItem::Event { .. } => "event",
+            ChangedItem::Error { .. } => "error",
+            ChangedItem::StorageEntry { .. } => "storage entry",
+            ChangedItem::Constant { .. } => "constant",
+            ChangedItem::ViewFunction { .. } => "view function",
+            ChangedItem::RuntimeApi { .. } => "runtime api",
+            ChangedItem::RuntimeApiMethod { .. } => "runtime api method",
+            ChangedItem::Extrinsic => "extrinsic",
+        }
+    }
+}
+
+impl fmt::Display for ChangedItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangedItem::Pallet { pallet } => write!(f, "pallet {pallet}"),
+            ChangedItem::Call { pallet, name }
+            | ChangedItem::Event { pallet, name }
+            | ChangedItem::Error { pallet, name }
+            | ChangedItem::StorageEntry { pallet, name }
+            | ChangedItem::Constant { pallet, name }
+            | ChangedItem::ViewFunction { pallet, name } => {
+                write!(f, "{} {pallet}::{name}", self.kind())
+            }
+            ChangedItem::RuntimeApi { trait_name } => write!(f, "runtime api {trait_name}"),
+            ChangedItem::RuntimeApiMethod { trait_name, name } => {
+                write!(f, "runtime api method {trait_name}::{name}")
+            }
+            ChangedItem::Extrinsic => f.write_str("extrinsic"),
+        }
+    }
+}
+
+/// A single item which differs between two sets of metadata, along with the most
+/// severe change found in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SemanticChange {
+    item: ChangedItem,
+    severity: ChangeSeverity,
+    description: String,
+    type_path: Option<String>,
+}
+
+impl SemanticChange {
+    /// The item which changed.
+    pub fn item(&self) -> &ChangedItem {
+        &self.item
+    }
+
+    /// How severe the change is.
+    pub fn severity(&self) -> ChangeSeverity {
+        self.severity
+    }
+
+    /// A human readable description of the change.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Where in the item's types the change was found, if it was found in a type. For
+    /// example, `dest -> sp_runtime::multiaddress::MultiAddress::Id.0` means that the `0`th
+    /// field of the `Id` variant of the `dest` argument's type changed.
+    pub fn type_path(&self) -> Option<&str> {
+        self.type_path.as_deref()
+    }
+}
+
+/// The classified differences between two sets of metadata. See [`Metadata::semantic_diff()`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SemanticDiff {
+    changes: Vec<SemanticChange>,
+}
+
+impl SemanticDiff {
+    /// Are the two sets of metadata the same?
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Each item which changed.
+    pub fn changes(&self) -> &[SemanticChange] {
+        &self.changes
+    }
+
+    /// The most severe change found, or `None` if nothing changed.
+    pub fn severity(&self) -> Option<ChangeSeverity> {
+        self.changes.iter().map(|c| c.severity).max()
+    }
+
+    /// Were any breaking changes found?
+    pub fn is_breaking(&self) -> bool {
+        self.severity() == Some(ChangeSeverity::Breaking)
+    }
+}
+
+impl Metadata {
+    /// Compare this (old) metadata with some new metadata, and classify the change to each
+    /// item as breaking, additive or cosmetic (see [`ChangeSeverity`]). Unlike
+    /// [`Metadata::diff()`], types are compared structurally rather than by hash, so that the
+    /// change can be classified and the path to the type that changed can be given.
+    pub fn semantic_diff(&self, new: &Metadata) -> SemanticDiff {
+        let mut differ = Differ {
+            old_types: &self.types,
+            new_types: &new.types,
+            changes: Vec::new(),
+        };
+
+        for old_pallet in self.pallets() {
+            match new.pallet_by_name(old_pallet.name()) {
+                Some(new_pallet) => differ.pallet(old_pallet, new_pallet),
+                None => differ.push_removed(ChangedItem::Pallet {
+                    pallet: old_pallet.name().to_string(),
+                }),
+            }
+        }
+        for new_pallet in new.pallets() {
+            if self.pallet_by_name(new_pallet.name()).is_none() {
+                differ.push_added(ChangedItem::Pallet {
+                    pallet: new_pallet.name().to_string(),
+                });
+            }
+        }
+
+        for old_api in self.runtime_api_traits() {
+            match new.runtime_api_trait_by_name(old_api.name()) {
+                Some(new_api) => differ.runtime_api(old_api, new_api),
+                None => differ.push_removed(ChangedItem::RuntimeApi {
+                    trait_name: old_api.name().to_string(),
+                }),
+            }
+        }
+        for new_api in new.runtime_api_traits() {
+            if self.runtime_api_trait_by_name(new_api.name()).is_none() {
+                differ.push_added(ChangedItem::RuntimeApi {
+                    trait_name: new_api.name().to_string(),
+                });
+            }
+        }
+
+        differ.extrinsic(self, new);
+
+        SemanticDiff {
+            changes: differ.changes,
+        }
+    }
+}
+
+/// A change found while comparing some item.
+struct Finding {
+    severity: ChangeSeverity,
+    description: String,
+    type_path: Option<String>,
+}
+
+struct Differ<'a> {
+    old_types: &'a PortableRegistry,
+    new_types: &'a PortableRegistry,
+    changes: Vec<SemanticChange>,
+}
+
+impl<'a> Differ<'a> {
+    fn push_added(&mut self, item: ChangedItem) {
+        self.changes.push(SemanticChange {
+            item,
+            severity: ChangeSeverity::Additive,
+            description: "added".to_string(),
+            type_path: None,
+        });
+    }
+
+    fn push_removed(&mut self, item: ChangedItem) {
+        self.changes.push(SemanticChange {
+            item,
+            severity: ChangeSeverity::Breaking,
+            description: "removed".to_string(),
+            type_path: None,
+        });
+    }
+
+    fn push_finding(&mut self, item: ChangedItem, finding: Option<Finding>) {
+        if let Some(finding) = finding {
+            self.changes.push(SemanticChange {
+                item,
+                severity: finding.severity,
+                description: finding.description,
+                type_path: finding.type_path,
+            });
+        }
+    }
+
+    fn types(&self) -> TypeDiffer<'a> {
+        TypeDiffer {
+            old: self.old_types,
+            new: self.new_types,
+            seen: HashSet::new(),
+            path: Vec::new(),
+            finding: None,
+        }
+    }
+
+    fn pallet(&mut self, old: PalletMetadata<'_>, new: PalletMetadata<'_>) {
+        let pallet = old.name();
+
+        let mut t = self.types();
+        if old.call_index() != new.call_index() {
+            t.found(
+                ChangeSeverity::Breaking,
+                format!(
+                    "index changed from {} to {}",
+                    old.call_index(),
+                    new.call_index()
+                ),
+            );
+        }
+        t.docs(old.docs(), new.docs());
+        self.push_finding(
+            ChangedItem::Pallet {
+                pallet: pallet.to_string(),
+            },
+            t.finding,
+        );
+
+        let item = |name: &str| (pallet.to_string(), name.to_string());
+        self.variants(
+            old.call_variants().unwrap_or_default(),
+            new.call_variants().unwrap_or_default(),
+            |name| {
+                let (pallet, name) = item(name);
+                ChangedItem::Call { pallet, name }
+            },
+        );
+        self.variants(
+            old.event_variants().unwrap_or_default(),
+            new.event_variants().unwrap_or_default(),
+            |name| {
+                let (pallet, name) = item(name);
+                ChangedItem::Event { pallet, name }
+            },
+        );
+        self.variants(
+            old.error_variants().unwrap_or_default(),
+            new.error_variants().unwrap_or_default(),
+            |name| {
+                let (pallet, name) = item(name);
+                ChangedItem::Error { pallet, name }
+            },
+        );
+
+        // Storage entries:
+        let old_entries = old.storage().map(|s| s.entries()).unwrap_or_default();
+        let new_entries = new.storage().map(|s| s.entries()).unwrap_or_default();
+        for old_entry in old_entries {
+            let (pallet, name) = item(old_entry.name());
+            let changed_item = ChangedItem::StorageEntry { pallet, name };
+            let Some(new_entry) = new
+                .storage()
+                .and_then(|s| s.entry_by_name(old_entry.name()))
+            else {
+                self.push_removed(changed_item);
+                continue;
+            };
+
+            let mut t = self.types();
+            let old_hashers: Vec<_> = old_entry.keys().map(|k| k.hasher).collect();
+            let new_hashers: Vec<_> = new_entry.keys().map(|k| k.hasher).collect();
+            if old_hashers != new_hashers {
+                t.found(
+                    ChangeSeverity::Breaking,
+                    format!("key hashers changed from {old_hashers:?} to {new_hashers:?}"),
+                );
+            }
+            for (idx, (old_key, new_key)) in old_entry.keys().zip(new_entry.keys()).enumerate() {
+                t.with_path(format!("key {idx}"), |t| {
+                    t.ty(old_key.key_id, new_key.key_id)
+                });
+            }
+            t.with_path("value".to_string(), |t| {
+                t.ty(old_entry.value_ty(), new_entry.value_ty())
+            });
+            if old_entry.default_value() != new_entry.default_value() {
+                t.found(
+                    ChangeSeverity::Breaking,
+                    "default value changed".to_string(),
+                );
+            }
+            t.docs(old_entry.docs(), new_entry.docs());
+            self.push_finding(changed_item, t.finding);
+        }
+        for new_entry in new_entries {
+            if old
+                .storage()
+                .and_then(|s| s.entry_by_name(new_entry.name()))
+                .is_none()
+            {
+                let (pallet, name) = item(new_entry.name());
+                self.push_added(ChangedItem::StorageEntry { pallet, name });
+            }
+        }
+
+        // Constants:
+        for old_constant in old.constants() {
+            let (pallet, name) = item(old_constant.name());
+            let changed_item = ChangedItem::Constant { pallet, name };
+            let Some(new_constant) = new.constant_by_name(old_constant.name()) else {
+                self.push_removed(changed_item);
+                continue;
+            };
+
+            let mut t = self.types();
+            t.ty(old_constant.ty(), new_constant.ty());
+            if old_constant.value() != new_constant.value() {
+                t.found(ChangeSeverity::Cosmetic, "value changed".to_string());
+            }
+            t.docs(old_constant.docs(), new_constant.docs());
+            self.push_finding(changed_item, t.finding);
+        }
+        for new_constant in new.constants() {
+            if old.constant_by_name(new_constant.name()).is_none() {
+                let (pallet, name) = item(new_constant.name());
+                self.push_added(ChangedItem::Constant { pallet, name });
+            }
+        }
+
+        // View Functions:
+        for old_vf in old.view_functions() {
+            let (pallet, name) = item(old_vf.name());
+            let changed_item = ChangedItem::ViewFunction { pallet, name };
+            let Some(new_vf) = new.view_function_by_name(old_vf.name()) else {
+                self.push_removed(changed_item);
+                continue;
+            };
+
+            let mut t = self.types();
+            if old_vf.query_id() != new_vf.query_id() {
+                t.found(ChangeSeverity::Breaking, "query ID changed".to_string());
+            }
+            t.inputs(
+                old_vf.inputs().map(|i| (&*i.name, i.id)),
+                new_vf.inputs().map(|i| (&*i.name, i.id)),
+            );
+            t.with_path("output".to_string(), |t| {
+                t.ty(old_vf.output_ty(), new_vf.output_ty())
+            });
+            t.docs(old_vf.docs(), new_vf.docs());
+            self.push_finding(changed_item, t.finding);
+        }
+        for new_vf in new.view_functions() {
+            if old.view_function_by_name(new_vf.name()).is_none() {
+                let (pallet, name) = item(new_vf.name());
+                self.push_added(ChangedItem::ViewFunction { pallet, name });
+            }
+        }
+    }
+
+    // Compare the variants of some call, event or error enum, where each variant is an item.
+    fn variants(
+        &mut self,
+        old: &[Variant<PortableForm>],
+        new: &[Variant<PortableForm>],
+        to_item: impl Fn(&str) -> ChangedItem,
+    ) {
+        for old_variant in old {
+            let Some(new_variant) = new.iter().find(|v| v.name == old_variant.name) else {
+                self.push_removed(to_item(&old_variant.name));
+                continue;
+            };
+
+            let mut t = self.types();
+            if old_variant.index != new_variant.index {
+                t.found(
+                    ChangeSeverity::Breaking,
+                    format!(
+                        "index changed from {} to {}",
+                        old_variant.index, new_variant.index
+                    ),
+                );
+            }
+            t.fields(&old_variant.fields, &new_variant.fields);
+            t.docs(&old_variant.docs, &new_variant.docs);
+            self.push_finding(to_item(&old_variant.name), t.finding);
+        }
+        for new_variant in new {
+            if !old.iter().any(|v| v.name == new_variant.name) {
+                self.push_added(to_item(&new_variant.name));
+            }
+        }
+    }
+
+    fn runtime_api(&mut self, old: RuntimeApiMetadata<'_>, new: RuntimeApiMetadata<'_>) {
+        let trait_name = old.name();
+
+        let mut t = self.types();
+        t.docs(old.docs(), new.docs());
+        self.push_finding(
+            ChangedItem::RuntimeApi {
+                trait_name: trait_name.to_string(),
+            },
+            t.finding,
+        );
+
+        let item = |name: &str| ChangedItem::RuntimeApiMethod {
+            trait_name: trait_name.to_string(),
+            name: name.to_string(),
+        };
+        for old_method in old.methods() {
+            let Some(new_method) = new.method_by_name(old_method.name()) else {
+                self.push_removed(item(old_method.name()));
+                continue;
+            };
+
+            let mut t = self.types();
+            t.inputs(
+                old_method.inputs().map(|i| (&*i.name, i.id)),
+                new_method.inputs().map(|i| (&*i.name, i.id)),
+            );
+            t.with_path("output".to_string(), |t| {
+                t.ty(old_method.output_ty(), new_method.output_ty())
+            });
+            t.docs(old_method.docs(), new_method.docs());
+            self.push_finding(item(old_method.name()), t.finding);
+        }
+        for new_method in new.methods() {
+            if old.method_by_name(new_method.name()).is_none() {
+                self.push_added(item(new_method.name()));
+            }
+        }
+    }
+
+    fn extrinsic(&mut self, old: &Metadata, new: &Metadata) {
+        let old_ext = &old.extrinsic;
+        let new_ext = &new.extrinsic;
+        let mut t = self.types();
+
+        let removed_versions = old_ext
+            .supported_versions
+            .iter()
+            .any(|v| !new_ext.supported_versions.contains(v));
+        let added_versions = new_ext
+            .supported_versions
+            .iter()
+            .any(|v| !old_ext.supported_versions.contains(v));
+        if removed_versions || added_versions {
+            let severity = if removed_versions {
+                ChangeSeverity::Breaking
+            } else {
+                ChangeSeverity::Additive
+            };
+            t.found(
+                severity,
+                format!(
+                    "supported versions changed from {:?} to {:?}",
+                    old_ext.supported_versions, new_ext.supported_versions
+                ),
+            );
+        }
+
+        t.with_path("address".to_string(), |t| {
+            t.ty(old_ext.address_ty, new_ext.address_ty)
+        });
+        t.with_path("signature".to_string(), |t| {
+            t.ty(old_ext.signature_ty, new_ext.signature_ty)
+        });
+
+        let old_ids: Vec<_> = old_ext
+            .transaction_extensions
+            .iter()
+            .map(|e| &*e.identifier)
+            .collect();
+        let new_ids: Vec<_> = new_ext
+            .transaction_extensions
+            .iter()
+            .map(|e| &*e.identifier)
+            .collect();
+        if old_ids != new_ids
+            || old_ext.transaction_extensions_by_version
+                != new_ext.transaction_extensions_by_version
+        {
+            t.found(
+                ChangeSeverity::Breaking,
+                format!("transaction extensions changed from {old_ids:?} to {new_ids:?}"),
+            );
+        }
+        for (old_e, new_e) in old_ext
+            .transaction_extensions
+            .iter()
+            .zip(&new_ext.transaction_extensions)
+        {
+            t.with_path(old_e.identifier.clone(), |t| {
+                t.ty(old_e.extra_ty, new_e.extra_ty);
+                t.ty(old_e.additional_ty, new_e.additional_ty);
+            });
+        }
+
+        self.push_finding(ChangedItem::Extrinsic, t.finding);
+    }
+}
+
+/// Structurally compare types from two registries, remembering the most severe change found.
+struct TypeDiffer<'a> {
+    old: &'a PortableRegistry,
+    new: &'a PortableRegistry,
+    // Pairs of types which have been (or are being) compared already.
+    seen: HashSet<(u32, u32)>,
+    // The path to the type currently being compared.
+    path: Vec<String>,
+    finding: Option<Finding>,
+}
+
+impl TypeDiffer<'_> {
+    // Record a change at the current path, unless a change at least as severe was found already.
+    fn found(&mut self, severity: ChangeSeverity, description: String) {
+        if self
+            .finding
+            .as_ref()
+            .is_some_and(|f| f.severity >= severity)
+        {
+            return;
+        }
+        let type_path = self.path.concat();
+        let type_path = type_path.trim_start_matches(" -> ");
+        self.finding = Some(Finding {
+            severity,
+            description,
+            type_path: (!type_path.is_empty()).then(|| type_path.to_string()),
+        });
+    }
+
+    fn is_breaking(&self) -> bool {
+        self.finding
+            .as_ref()
+            .is_some_and(|f| f.severity == ChangeSeverity::Breaking)
+    }
+
+    fn with_path(&mut self, segment: String, f: impl FnOnce(&mut Self)) {
+        self.path.push(segment);
+        f(self);
+        self.path.pop();
+    }
+
+    fn docs(&mut self, old: &[String], new: &[String]) {
+        if old != new {
+            self.found(ChangeSeverity::Cosmetic, "docs changed".to_string());
+        }
+    }
+
+    // Compare the named inputs to some Runtime API or View Function.
+    fn inputs<'b>(
+        &mut self,
+        old: impl ExactSizeIterator<Item = (&'b str, u32)>,
+        new: impl ExactSizeIterator<Item = (&'b str, u32)>,
+    ) {
+        if old.len() != new.len() {
+            self.found(
+                ChangeSeverity::Breaking,
+                format!(
+                    "number of inputs changed from {} to {}",
+                    old.len(),
+                    new.len()
+                ),
+            );
+            return;
+        }
+        for ((old_name, old_id), (new_name, new_id)) in old.zip(new) {
+            if old_name != new_name {
+                self.found(
+                    ChangeSeverity::Breaking,
+                    format!("input {old_name} renamed to {new_name}"),
+                );
+            }
+            self.with_path(old_name.to_string(), |t| t.ty(old_id, new_id));
+        }
+    }
+
+    fn fields(&mut self, old: &[Field<PortableForm>], new: &[Field<PortableForm>]) {
+        if old.len() != new.len() {
+            self.found(
+                ChangeSeverity::Breaking,
+                format!(
+                    "number of fields changed from {} to {}",
+                    old.len(),
+                    new.len()
+                ),
+            );
+            return;
+        }
+        for (idx, (old_field, new_field)) in old.iter().zip(new).enumerate() {
+            let segment = match &old_field.name {
+                Some(name) => name.clone(),
+                None => idx.to_string(),
+            };
+            let segment = if self.path.is_empty() {
+                segment
+            } else {
+                format!(".{segment}")
+            };
+            self.with_path(segment, |t| {
+                if old_field.name != new_field.name {
+                    let old_name = old_field.name.as_deref().unwrap_or("_");
+                    let new_name = new_field.name.as_deref().unwrap_or("_");
+                    t.found(
+                        ChangeSeverity::Breaking,
+                        format!("field {old_name} renamed to {new_name}"),
+                    );
+                }
+                if old_field.type_name != new_field.type_name {
+                    t.found(
+                        ChangeSeverity::Cosmetic,
+                        "field type name changed".to_string(),
+                    );
+                }
+                t.docs(&old_field.docs, &new_field.docs);
+                t.ty(old_field.ty.id, new_field.ty.id);
+            });
+        }
+    }
+
+    fn ty(&mut self, old_id: u32, new_id: u32) {
+        // Stop as soon as we find something breaking, and don't compare types twice (this
+        // also prevents us from recursing forever into recursive types).
+        if self.is_breaking() || !self.seen.insert((old_id, new_id)) {
+            return;
+        }
+
+        let (Some(old_ty), Some(new_ty)) = (self.old.resolve(old_id), self.new.resolve(new_id))
+        else {
+            self.found(ChangeSeverity::Breaking, "type not found".to_string());
+            return;
+        };
+
+        let is_named = !old_ty.path.segments.is_empty();
+        if is_named {
+            self.path
+                .push(format!(" -> {}", old_ty.path.segments.join("::")));
+        }
+
+        if old_ty.path != new_ty.path {
+            self.found(
+                ChangeSeverity::Cosmetic,
+                format!("type path changed to {}", new_ty.path.segments.join("::")),
+            );
+        }
+        self.docs(&old_ty.docs, &new_ty.docs);
+
+        match (&old_ty.type_def, &new_ty.type_def) {
+            (TypeDef::Composite(old), TypeDef::Composite(new)) => {
+                self.fields(&old.fields, &new.fields);
+            }
+            (TypeDef::Variant(old), TypeDef::Variant(new)) => {
+                for old_variant in &old.variants {
+                    let name = &old_variant.name;
+                    self.with_path(format!("::{name}"), |t| {
+                        let Some(new_variant) = new.variants.iter().find(|v| &v.name == name)
+                        else {
+                            t.found(ChangeSeverity::Breaking, format!("variant {name} removed"));
+                            return;
+                        };
+                        if old_variant.index != new_variant.index {
+                            t.found(
+                                ChangeSeverity::Breaking,
+                                format!(
+                                    "variant {name} index changed from {} to {}",
+                                    old_variant.index, new_variant.index
+                                ),
+                            );
+                        }
+                        t.docs(&old_variant.docs, &new_variant.docs);
+                        t.fields(&old_variant.fields, &new_variant.fields);
+                    });
+                }
+                for new_variant in &new.variants {
+                    if !old.variants.iter().any(|v| v.name == new_variant.name) {
+                        self.found(
+                            ChangeSeverity::Additive,
+                            format!("variant {} added", new_variant.name),
+                        );
+                    }
+                }
+            }
+            (TypeDef::Sequence(old), TypeDef::Sequence(new)) => {
+                self.with_path("[_]".to_string(), |t| {
+                    t.ty(old.type_param.id, new.type_param.id)
+                });
+            }
+            (TypeDef::Array(old), TypeDef::Array(new)) => {
+                if old.len != new.len {
+                    self.found(
+                        ChangeSeverity::Breaking,
+                        format!("array length changed from {} to {}", old.len, new.len),
+                    );
+                }
+                self.with_path("[_]".to_string(), |t| {
+                    t.ty(old.type_param.id, new.type_param.id)
+                });
+            }
+            (TypeDef::Tuple(old), TypeDef::Tuple(new)) => {
+                if old.fields.len() != new.fields.len() {
+                    self.found(
+                        ChangeSeverity::Breaking,
+                        format!(
+                            "tuple length changed from {} to {}",
+                            old.fields.len(),
+                            new.fields.len()
+                        ),
+                    );
+                }
+                for (idx, (old, new)) in old.fields.iter().zip(&new.fields).enumerate() {
+                    self.with_path(format!(".{idx}"), |t| t.ty(old.id, new.id));
+                }
+            }
+            (TypeDef::Primitive(old), TypeDef::Primitive(new)) => {
+                if old != new {
+                    self.found(
+                        ChangeSeverity::Breaking,
+                        format!("primitive type changed from {old:?} to {new:?}"),
+                    );
+                }
+            }
+            (TypeDef::Compact(old), TypeDef::Compact(new)) => {
+                self.ty(old.type_param.id, new.type_param.id);
+            }
+            (TypeDef::BitSequence(old), TypeDef::BitSequence(new)) => {
+                self.ty(old.bit_store_type.id, new.bit_store_type.id);
+                self.ty(old.bit_order_type.id, new.bit_order_type.id);
+            }
+            (old, new) => {
+                self.found(
+                    ChangeSeverity::Breaking,
+                    format!(
+                        "type changed from {} to {}",
+                        type_def_name(old),
+                        type_def_name(new)
+                    ),
+                );
+            }
+        }
+
+        if is_named {
+            self.path.pop();
+        }
+    }
+}
+
+fn type_def_name(type_def: &TypeDef<PortableForm>) -> &'static str {
+    match type_def {
+        TypeDef::Composite(_) => "a composite",
+        TypeDef::Variant(_) => "a variant",
+        TypeDef::Sequence(_) => "a sequence",
+        TypeDef::Array(_) => "an array",
+        TypeDef::Tuple(_) => "a tuple",
+        TypeDef::Primitive(_) => "a primitive",
+        TypeDef::Compact(_) => "a compact",
+        TypeDef::BitSequence(_) => "a bit sequence",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::test_utils::{metadata_from_artifact, polkadot_metadata_small};
+    use scale_info::{MetaType, Registry, TypeInfo};
+
+    // Compare the first type in each of two registries built from the given types.
+    fn compare<Old: TypeInfo + 'static, New: TypeInfo + 'static>() -> Option<Finding> {
+        fn registry<T: TypeInfo + 'static>() -> (PortableRegistry, u32) {
+            let mut registry = Registry::new();
+            let id = registry.register_type(&MetaType::new::<T>()).id;
+            (registry.into(), id)
+        }
+        let (old, old_id) = registry::<Old>();
+        let (new, new_id) = registry::<New>();
+
+        let mut t = TypeDiffer {
+            old: &old,
+            new: &new,
+            seen: HashSet::new(),
+            path: Vec::new(),
+            finding: None,
+        };
+        t.ty(old_id, new_id);
+        t.finding
+    }
+
+    mod old {
+        #[allow(dead_code)]
+        #[derive(scale_info::TypeInfo)]
+        pub struct Account {
+            pub id: [u8; 32],
+            pub address: Address,
+        }
+
+        #[allow(dead_code)]
+        #[derive(scale_info::TypeInfo)]
+        pub enum Address {
+            Id(u64),
+            Raw(Vec<u8>),
+        }
+    }
+
+    mod additive {
+        #[allow(dead_code)]
+        #[derive(scale_info::TypeInfo)]
+        pub struct Account {
+            pub id: [u8; 32],
+            pub address: Address,
+        }
+
+        #[allow(dead_code)]
+        #[derive(scale_info::TypeInfo)]
+        pub enum Address {
+            Id(u64),
+            Raw(Vec<u8>),
+            /// A new variant.
+            Index(u32),
+        }
+    }
+
+    mod breaking {
+        #[allow(dead_code)]
+        #[derive(scale_info::TypeInfo)]
+        pub struct Account {
+            pub id: [u8; 32],
+            pub address: Address,
+        }
+
+        #[allow(dead_code)]
+        #[derive(scale_info::TypeInfo)]
+        pub enum Address {
+            Id(u32),
+            Raw(Vec<u8>),
+        }
+    }
+
+    mod renamed {
+        #[allow(dead_code)]
+        #[derive(scale_info::TypeInfo)]
+        pub struct Account {
+            /// Docs are cosmetic too.
+            pub id: [u8; 32],
+            pub address: Address,
+        }
+
+        #[allow(dead_code)]
+        #[derive(scale_info::TypeInfo)]
+        pub enum Address {
+            Id(u64),
+            Raw(Vec<u8>),
+        }
+    }
+
+    mod reordered {
+        #[allow(dead_code)]
+        #[derive(scale_info::TypeInfo)]
+        pub enum Address {
+            Raw(Vec<u8>),
+            Id(u64),
+        }
+    }
+
+    #[test]
+    fn identical_types_have_no_changes() {
+        assert!(compare::<old::Account, old::Account>().is_none());
+    }
+
+    #[test]
+    fn type_paths_and_docs_are_cosmetic() {
+        let finding = compare::<old::Account, renamed::Account>().unwrap();
+        assert_eq!(finding.severity, ChangeSeverity::Cosmetic);
+        assert_eq!(
+            finding.description,
+            "type path changed to subxt_metadata::semantic_diff::test::renamed::Account"
+        );
+    }
+
+    #[test]
+    fn changes_are_classified_and_pinpointed() {
+        let finding = compare::<old::Account, additive::Account>().unwrap();
+        assert_eq!(finding.severity, ChangeSeverity::Additive);
+        assert_eq!(finding.description, "variant Index added");
+        assert_eq!(
+            finding.type_path.as_deref(),
+            Some(
+                "subxt_metadata::semantic_diff::test::old::Account.address -> subxt_metadata::semantic_diff::test::old::Address"
+            )
+        );
+
+        let finding = compare::<old::Account, breaking::Account>().unwrap();
+        assert_eq!(finding.severity, ChangeSeverity::Breaking);
+        assert_eq!(
+            finding.description,
+            "primitive type changed from U64 to U32"
+        );
+        assert_eq!(
+            finding.type_path.as_deref(),
+            Some(
+                "subxt_metadata::semantic_diff::test::old::Account.address -> subxt_metadata::semantic_diff::test::old::Address::Id.0"
+            )
+        );
+
+        let finding = compare::<old::Address, reordered::Address>().unwrap();
+        assert_eq!(finding.severity, ChangeSeverity::Breaking);
+        assert_eq!(finding.description, "variant Id index changed from 0 to 1");
+    }
+
+    #[test]
+    fn metadata_diffs_against_itself_are_empty() {
+        let metadata = polkadot_metadata_small();
+        assert!(metadata.semantic_diff(&metadata).is_empty());
+    }
+
+    #[test]
+    fn removed_pallets_are_breaking() {
+        let small = polkadot_metadata_small();
+        let tiny = metadata_from_artifact("polkadot_metadata_tiny.scale");
+
+        let diff = small.semantic_diff(&tiny);
+        assert!(diff.is_breaking());
+        for pallet in small.pallets() {
+            if tiny.pallet_by_name(pallet.name()).is_none() {
+                let item = ChangedItem::Pallet {
+                    pallet: pallet.name().to_string(),
+                };
+                let change = diff.changes().iter().find(|c| c.item() == &item).unwrap();
+                assert_eq!(change.severity(), ChangeSeverity::Breaking);
+            }
+        }
+    }
+}
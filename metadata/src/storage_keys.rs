@@ -0,0 +1,438 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+//! Work out which storage entry some raw storage key belongs to, and decode it.
+
+use crate::utils::ordered_map::OrderedMap;
+use crate::{Metadata, PalletMetadata, PalletMetadataInner, StorageEntryMetadata};
+use alloc::string::String;
+use alloc::vec::Vec;
+use frame_decode::storage::{StorageHasher, StorageKey, StorageKeyDecodeError};
+use hashbrown::HashMap;
+use scale_decode::DecodeAsType;
+use scale_info::PortableRegistry;
+use sp_crypto_hashing::twox_128;
+use thiserror::Error as DeriveError;
+
+/// The suffix used in the key for a pallet's storage version, ie
+/// `twox_128(pallet_name) ++ twox_128(":__STORAGE_VERSION__:")`.
+const STORAGE_VERSION_KEY_SUFFIX: &[u8] = b":__STORAGE_VERSION__:";
+// Note: these should match the constants in `sp_core::storage::well_known_keys`.
+const CODE_KEY: &[u8] = b":code";
+const HEAP_PAGES_KEY: &[u8] = b":heappages";
+const EXTRINSIC_INDEX_KEY: &[u8] = b":extrinsic_index";
+const INTRABLOCK_ENTROPY_KEY: &[u8] = b":intrablock_entropy";
+/// The prefix of keys which point to default child tries.
+const DEFAULT_CHILD_STORAGE_KEY_PREFIX: &[u8] = b":child_storage:default:";
+
+/// What some raw storage key was identified as. See [`Metadata::identify_storage_key()`].
+#[derive(Debug, Clone)]
+pub enum IdentifiedStorageKey<'a, 'k> {
+    /// The key belongs to a storage entry described in the metadata.
+    Entry(StorageEntryKey<'a, 'k>),
+    /// The key points to the storage version of a pallet.
+    StorageVersion {
+        /// The pallet whose storage version this is.
+        pallet: PalletMetadata<'a>,
+    },
+    /// The key is a well known key which is not described in the metadata.
+    WellKnown(WellKnownStorageKey<'k>),
+}
+
+/// A well known storage key. These are not described by the metadata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WellKnownStorageKey<'k> {
+    /// `:code`; the runtime WASM blob.
+    Code,
+    /// `:heappages`; the number of heap pages available to the runtime.
+    HeapPages,
+    /// `:extrinsic_index`; the index of the extrinsic currently being executed.
+    ExtrinsicIndex,
+    /// `:intrablock_entropy`; entropy which is reset at the start of each block.
+    IntraBlockEntropy,
+    /// `:child_storage:default:` followed by the key of a default child trie.
+    DefaultChildStorage {
+        /// The storage key of the child trie.
+        child_key: &'k [u8],
+    },
+}
+
+impl<'k> WellKnownStorageKey<'k> {
+    /// Identify some well known storage key.
+    pub fn from_key(key: &'k [u8]) -> Option<Self> {
+        match key {
+            CODE_KEY => Some(Self::Code),
+            HEAP_PAGES_KEY => Some(Self::HeapPages),
+            EXTRINSIC_INDEX_KEY => Some(Self::ExtrinsicIndex),
+            INTRABLOCK_ENTROPY_KEY => Some(Self::IntraBlockEntropy),
+            _ => key
+                .strip_prefix(DEFAULT_CHILD_STORAGE_KEY_PREFIX)
+                .map(|child_key| Self::DefaultChildStorage { child_key }),
+        }
+    }
+
+    /// The raw storage key. For [`WellKnownStorageKey::DefaultChildStorage`], this is the
+    /// `:child_storage:default:` prefix followed by the child key.
+    pub fn to_key(&self) -> Vec<u8> {
+        match self {
+            Self::Code => CODE_KEY.to_vec(),
+            Self::HeapPages => HEAP_PAGES_KEY.to_vec(),
+            Self::ExtrinsicIndex => EXTRINSIC_INDEX_KEY.to_vec(),
+            Self::IntraBlockEntropy => INTRABLOCK_ENTROPY_KEY.to_vec(),
+            Self::DefaultChildStorage { child_key } => {
+                [DEFAULT_CHILD_STORAGE_KEY_PREFIX, *child_key].concat()
+            }
+        }
+    }
+}
+
+/// An error decoding the parts of a storage key.
+#[derive(Debug, DeriveError)]
+#[non_exhaustive]
+pub enum StorageKeyPartsError {
+    /// The key could not be decoded according to the storage entry hashers.
+    #[error("Cannot decode the storage key: {0}")]
+    CannotDecode(StorageKeyDecodeError<u32>),
+    /// The key was decoded but some bytes were left over.
+    #[error("Decoding the storage key left {remaining} bytes unconsumed")]
+    LeftoverBytes {
+        /// The number of bytes left over.
+        remaining: usize,
+    },
+}
+
+/// An error decoding the value stored at some storage key.
+/// See [`StorageEntryKey::decode_value()`].
+#[derive(Debug, DeriveError)]
+#[non_exhaustive]
+pub enum StorageValueDecodeError {
+    /// The value could not be decoded into the storage entry value type.
+    #[error("Cannot decode the storage value: {0}")]
+    CannotDecode(scale_decode::Error),
+    /// The value was decoded but some bytes were left over.
+    #[error("Decoding the storage value left {remaining} bytes unconsumed")]
+    LeftoverBytes {
+        /// The number of bytes left over.
+        remaining: usize,
+    },
+}
+
+/// A raw storage key which belongs to some storage entry in the metadata.
+#[derive(Debug, Clone)]
+pub struct StorageEntryKey<'a, 'k> {
+    metadata: &'a Metadata,
+    pallet: PalletMetadata<'a>,
+    entry: &'a StorageEntryMetadata,
+    key: &'k [u8],
+}
+
+impl<'a, 'k> StorageEntryKey<'a, 'k> {
+    /// The pallet that the storage entry is in.
+    pub fn pallet(&self) -> PalletMetadata<'a> {
+        self.pallet
+    }
+
+    /// Details about the storage entry.
+    pub fn entry(&self) -> &'a StorageEntryMetadata {
+        self.entry
+    }
+
+    /// The raw storage key bytes.
+    pub fn key_bytes(&self) -> &'k [u8] {
+        self.key
+    }
+
+    /// Decode the parts of the storage key which follow the entry prefix, one for each hasher
+    /// used by the storage entry. Where a hasher is transparent (ie `Blake2_128Concat`,
+    /// `Twox64Concat` and `Identity`), the value that was hashed can be decoded from the part.
+    pub fn parts(&self) -> Result<Vec<StorageEntryKeyPart<'a, 'k>>, StorageKeyPartsError> {
+        let cursor = &mut &*self.key;
+        let parts: StorageKey<u32> = frame_decode::storage::decode_storage_key_with_info(
+            cursor,
+            &self.entry.info,
+            self.metadata.types(),
+        )
+        .map_err(StorageKeyPartsError::CannotDecode)?;
+
+        if !cursor.is_empty() {
+            return Err(StorageKeyPartsError::LeftoverBytes {
+                remaining: cursor.len(),
+            });
+        }
+
+        let parts = (0..parts.parts().len())
+            .map(|index| &parts[index])
+            .map(|part| StorageEntryKeyPart {
+                hasher: part.hasher(),
+                hash_bytes: &self.key[part.hash_range()],
+                value: part.value().map(|v| (&self.key[v.range()], *v.ty())),
+                types: self.metadata.types(),
+            })
+            .collect();
+
+        Ok(parts)
+    }
+
+    /// Decode some value bytes stored at this key into the given type
+    /// (for example a `scale_value::Value`).
+    pub fn decode_value<V: DecodeAsType>(
+        &self,
+        value_bytes: &[u8],
+    ) -> Result<V, StorageValueDecodeError> {
+        let cursor = &mut &*value_bytes;
+        let value = V::decode_as_type(cursor, self.entry.value_ty(), self.metadata.types())
+            .map_err(StorageValueDecodeError::CannotDecode)?;
+
+        if !cursor.is_empty() {
+            return Err(StorageValueDecodeError::LeftoverBytes {
+                remaining: cursor.len(),
+            });
+        }
+        Ok(value)
+    }
+}
+
+/// One part of a storage key, corresponding to one of the hashers used by the storage entry.
+#[derive(Debug, Clone, Copy)]
+pub struct StorageEntryKeyPart<'a, 'k> {
+    hasher: StorageHasher,
+    hash_bytes: &'k [u8],
+    value: Option<(&'k [u8], u32)>,
+    types: &'a PortableRegistry,
+}
+
+impl<'a, 'k> StorageEntryKeyPart<'a, 'k> {
+    /// The hasher used for this part of the key.
+    pub fn hasher(&self) -> StorageHasher {
+        self.hasher
+    }
+
+    /// The bytes corresponding to the hash. This is empty for the `Identity` hasher.
+    pub fn hash_bytes(&self) -> &'k [u8] {
+        self.hash_bytes
+    }
+
+    /// The bytes of the value that was hashed, if the hasher is transparent.
+    pub fn value_bytes(&self) -> Option<&'k [u8]> {
+        self.value.map(|(bytes, _)| bytes)
+    }
+
+    /// The type ID of the value that was hashed, if the hasher is transparent.
+    pub fn value_type_id(&self) -> Option<u32> {
+        self.value.map(|(_, ty)| ty)
+    }
+
+    /// Decode the value that was hashed into the given type, if the hasher is transparent.
+    /// Returns `None` if the value cannot be recovered from the hash.
+    pub fn decode_value<V: DecodeAsType>(&self) -> Option<Result<V, scale_decode::Error>> {
+        let (bytes, ty) = self.value?;
+        Some(V::decode_as_type(&mut &*bytes, ty, self.types))
+    }
+}
+
+impl Metadata {
+    /// Work out what some raw storage key points to. This recognises the keys of storage
+    /// entries described in the metadata (by matching the `twox_128` hashes of the pallet
+    /// prefix and entry name), the keys of pallet storage versions, and well known keys
+    /// like `:code`. Returns `None` if the key is not recognised.
+    ///
+    /// The hashes of each pallet prefix and entry name are computed once when the metadata
+    /// is constructed, so identifying a key doesn't hash anything.
+    pub fn identify_storage_key<'a, 'k>(
+        &'a self,
+        key: &'k [u8],
+    ) -> Option<IdentifiedStorageKey<'a, 'k>> {
+        if let Some(well_known) = WellKnownStorageKey::from_key(key) {
+            return Some(IdentifiedStorageKey::WellKnown(well_known));
+        }
+
+        let prefix: [u8; 32] = key.get(..32)?.try_into().expect("slice has 32 bytes; qed");
+        match *self.storage_key_prefixes.get(&prefix)? {
+            StorageKeyPrefix::StorageVersion { pallet } => {
+                if key.len() != 32 {
+                    return None;
+                }
+                let pallet = self.pallet_by_position(pallet)?;
+                Some(IdentifiedStorageKey::StorageVersion { pallet })
+            }
+            StorageKeyPrefix::Entry { pallet, entry } => {
+                let pallet = self.pallet_by_position(pallet)?;
+                let entry = pallet.storage()?.entries().get(entry)?;
+                Some(IdentifiedStorageKey::Entry(StorageEntryKey {
+                    metadata: self,
+                    pallet,
+                    entry,
+                    key,
+                }))
+            }
+        }
+    }
+
+    fn pallet_by_position(&self, position: usize) -> Option<PalletMetadata<'_>> {
+        let inner = self.pallets.get_by_index(position)?;
+        Some(PalletMetadata {
+            inner,
+            types: self.types(),
+        })
+    }
+}
+
+/// What the first 32 bytes of some storage key point to. Pallets and storage entries are
+/// given by their position in the metadata.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum StorageKeyPrefix {
+    Entry { pallet: usize, entry: usize },
+    StorageVersion { pallet: usize },
+}
+
+/// Compute the first 32 bytes of the key of every storage entry and pallet storage version,
+/// so that [`Metadata::identify_storage_key()`] doesn't need to hash anything.
+pub(crate) fn storage_key_prefixes(
+    pallets: &OrderedMap<String, PalletMetadataInner>,
+) -> HashMap<[u8; 32], StorageKeyPrefix> {
+    let mut prefixes = HashMap::new();
+    for (pallet_pos, pallet) in pallets.values().iter().enumerate() {
+        prefixes
+            .entry(key_prefix(&pallet.name, STORAGE_VERSION_KEY_SUFFIX))
+            .or_insert(StorageKeyPrefix::StorageVersion { pallet: pallet_pos });
+
+        let Some(storage) = &pallet.storage else {
+            continue;
+        };
+        for (entry_pos, entry) in storage.entries().iter().enumerate() {
+            prefixes
+                .entry(key_prefix(storage.prefix(), entry.name()))
+                .or_insert(StorageKeyPrefix::Entry {
+                    pallet: pallet_pos,
+                    entry: entry_pos,
+                });
+        }
+    }
+    prefixes
+}
+
+fn key_prefix(pallet: impl AsRef<[u8]>, entry: impl AsRef<[u8]>) -> [u8; 32] {
+    let mut prefix = [0u8; 32];
+    prefix[..16].copy_from_slice(&twox_128(pallet.as_ref()));
+    prefix[16..].copy_from_slice(&twox_128(entry.as_ref()));
+    prefix
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::test_utils::polkadot_metadata_small;
+
+    fn prefix(pallet: &str, entry: &str) -> Vec<u8> {
+        let mut key = twox_128(pallet.as_bytes()).to_vec();
+        key.extend(twox_128(entry.as_bytes()));
+        key
+    }
+
+    #[test]
+    fn identifies_well_known_keys() {
+        let metadata = polkadot_metadata_small();
+        assert!(matches!(
+            metadata.identify_storage_key(b":code"),
+            Some(IdentifiedStorageKey::WellKnown(WellKnownStorageKey::Code))
+        ));
+        assert!(matches!(
+            metadata.identify_storage_key(b":child_storage:default:foo"),
+            Some(IdentifiedStorageKey::WellKnown(
+                WellKnownStorageKey::DefaultChildStorage { child_key: b"foo" }
+            ))
+        ));
+        assert!(metadata.identify_storage_key(b":unknown").is_none());
+    }
+
+    #[test]
+    fn well_known_keys_round_trip() {
+        let keys = [
+            WellKnownStorageKey::Code,
+            WellKnownStorageKey::HeapPages,
+            WellKnownStorageKey::ExtrinsicIndex,
+            WellKnownStorageKey::IntraBlockEntropy,
+            WellKnownStorageKey::DefaultChildStorage { child_key: b"foo" },
+        ];
+        for key in keys {
+            assert_eq!(WellKnownStorageKey::from_key(&key.to_key()), Some(key));
+        }
+    }
+
+    #[test]
+    fn identifies_storage_versions() {
+        let metadata = polkadot_metadata_small();
+        let key = prefix("System", ":__STORAGE_VERSION__:");
+        let Some(IdentifiedStorageKey::StorageVersion { pallet }) =
+            metadata.identify_storage_key(&key)
+        else {
+            panic!("expected storage version key");
+        };
+        assert_eq!(pallet.name(), "System");
+    }
+
+    #[test]
+    fn identifies_and_decodes_map_keys() {
+        let metadata = polkadot_metadata_small();
+
+        let account = [7u8; 32];
+        let mut key = prefix("System", "Account");
+        key.extend(sp_crypto_hashing::blake2_128(&account));
+        key.extend(account);
+
+        let Some(IdentifiedStorageKey::Entry(entry_key)) = metadata.identify_storage_key(&key)
+        else {
+            panic!("expected storage entry key");
+        };
+        assert_eq!(entry_key.pallet().name(), "System");
+        assert_eq!(entry_key.entry().name(), "Account");
+
+        let parts = entry_key.parts().unwrap();
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].hasher(), StorageHasher::Blake2_128Concat);
+        assert_eq!(parts[0].value_bytes(), Some(&account[..]));
+
+        let decoded: [u8; 32] = parts[0].decode_value().unwrap().unwrap();
+        assert_eq!(decoded, account);
+
+        // Too many bytes in the key:
+        key.push(0);
+        let Some(IdentifiedStorageKey::Entry(entry_key)) = metadata.identify_storage_key(&key)
+        else {
+            panic!("expected storage entry key");
+        };
+        assert!(matches!(
+            entry_key.parts(),
+            Err(StorageKeyPartsError::LeftoverBytes { remaining: 1 })
+        ));
+    }
+
+    #[test]
+    fn decodes_values() {
+        let metadata = polkadot_metadata_small();
+        let key = prefix("System", "Number");
+        let Some(IdentifiedStorageKey::Entry(entry_key)) = metadata.identify_storage_key(&key)
+        else {
+            panic!("expected storage entry key");
+        };
+
+        let number: u32 = entry_key.decode_value(&[5, 0, 0, 0]).unwrap();
+        assert_eq!(number, 5);
+        assert!(matches!(
+            entry_key.decode_value::<u32>(&[5, 0, 0, 0, 0]),
+            Err(StorageValueDecodeError::LeftoverBytes { remaining: 1 })
+        ));
+    }
+
+    #[test]
+    fn unknown_keys_are_not_identified() {
+        let metadata = polkadot_metadata_small();
+        assert!(metadata.identify_storage_key(&[1u8; 40]).is_none());
+        assert!(
+            metadata
+                .identify_storage_key(&prefix("System", "NotAnEntry"))
+                .is_none()
+        );
+    }
+}
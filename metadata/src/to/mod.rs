@@ -0,0 +1,320 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+use crate::{Metadata, StorageEntryMetadata};
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use scale_info::form::PortableForm;
+use scale_info::{Path, PortableType, Type, TypeDef, TypeDefComposite, TypeDefTuple};
+use thiserror::Error as DeriveError;
+
+mod v15;
+mod v16;
+
+/// An error emitted if something goes wrong converting [`crate::Metadata`]
+/// back into [`frame_metadata`] types.
+#[derive(Debug, PartialEq, Eq, DeriveError)]
+#[non_exhaustive]
+pub enum ToFrameMetadataError {
+    /// Pallets in V15 and V16 metadata have a single index, but this pallet has different indexes
+    /// for its calls, events and errors. This is the case for most pallets in metadata converted
+    /// from V8 to V11 metadata, which is not supported.
+    #[error(
+        "Pallet {pallet_name} has call index {call_index}, event index {event_index} and error index {error_index}, but they must all be the same"
+    )]
+    PalletIndexesDiffer {
+        /// The name of the pallet.
+        pallet_name: String,
+        /// The index of the pallet's calls.
+        call_index: u8,
+        /// The index of the pallet's events.
+        event_index: u8,
+        /// The index of the pallet's errors.
+        error_index: u8,
+    },
+    /// The storage entry uses hashers from V9 or older metadata, which work differently to
+    /// the hashers in modern metadata and are not supported.
+    #[error("Storage entry {pallet_name}.{entry_name} uses V9 (or older) storage hashers")]
+    OldStorageHashers {
+        /// The name of the pallet.
+        pallet_name: String,
+        /// The name of the storage entry.
+        entry_name: String,
+    },
+    /// V15 metadata can only describe transaction extensions with version 0.
+    #[error("No transaction extensions with version 0 exist, so they cannot be written to V15")]
+    NoVersion0TransactionExtensions,
+    /// V15 metadata can only describe V4 extrinsics, but the metadata does not support them.
+    #[error(
+        "V4 extrinsics are not supported (supported versions: {supported_versions:?}), so they cannot be written to V15"
+    )]
+    UnsupportedExtrinsicVersion {
+        /// The extrinsic versions that the metadata does support.
+        supported_versions: Vec<u8>,
+    },
+}
+
+impl Metadata {
+    /// Convert this into V15 [`frame_metadata`]. This can fail if the metadata cannot be
+    /// represented as V15 (see [`ToFrameMetadataError`]).
+    ///
+    /// V15 metadata has no transaction extension versions, so only the version 0 extensions
+    /// are kept, and no Runtime API versions, pallet associated types or View Functions.
+    ///
+    /// # Legacy metadata
+    ///
+    /// Only metadata converted from V12 or newer metadata is supported in general:
+    ///
+    /// - V8 to V11 metadata gives pallets separate call, event and error indexes. These can't be
+    ///   written out as one pallet index without renumbering the variants of the outer call, event
+    ///   and error enums, which we don't do, and so nearly all such metadata is rejected with
+    ///   [`ToFrameMetadataError::PalletIndexesDiffer`].
+    /// - Storage entries which use the hashers from V9 or older metadata are rejected with
+    ///   [`ToFrameMetadataError::OldStorageHashers`].
+    pub fn to_v15(&self) -> Result<frame_metadata::v15::RuntimeMetadataV15, ToFrameMetadataError> {
+        v15::to_v15(self)
+    }
+
+    /// Convert this into V16 [`frame_metadata`]. This can fail if the metadata cannot be
+    /// represented as V16 (see [`ToFrameMetadataError`]).
+    ///
+    /// Nothing is marked as deprecated in the output, since deprecation information is not
+    /// retained in [`Metadata`].
+    ///
+    /// The same limits on legacy metadata apply as for [`Metadata::to_v15()`]; only metadata
+    /// converted from V12 or newer metadata is supported in general.
+    pub fn to_v16(&self) -> Result<frame_metadata::v16::RuntimeMetadataV16, ToFrameMetadataError> {
+        v16::to_v16(self)
+    }
+
+    /// Convert this into V15 [`frame_metadata`] via [`Metadata::to_v15()`], and SCALE encode
+    /// it as [`frame_metadata::RuntimeMetadataPrefixed`]. The bytes can be decoded back into
+    /// [`Metadata`] using [`Metadata::decode_from()`].
+    pub fn encode_v15(&self) -> Result<Vec<u8>, ToFrameMetadataError> {
+        let metadata = frame_metadata::RuntimeMetadata::V15(self.to_v15()?);
+        Ok(encode_prefixed(metadata))
+    }
+
+    /// Convert this into V16 [`frame_metadata`] via [`Metadata::to_v16()`], and SCALE encode
+    /// it as [`frame_metadata::RuntimeMetadataPrefixed`]. The bytes can be decoded back into
+    /// [`Metadata`] using [`Metadata::decode_from()`].
+    pub fn encode_v16(&self) -> Result<Vec<u8>, ToFrameMetadataError> {
+        let metadata = frame_metadata::RuntimeMetadata::V16(self.to_v16()?);
+        Ok(encode_prefixed(metadata))
+    }
+}
+
+fn encode_prefixed(metadata: frame_metadata::RuntimeMetadata) -> Vec<u8> {
+    use codec::Encode;
+    frame_metadata::RuntimeMetadataPrefixed(frame_metadata::META_RESERVED, metadata).encode()
+}
+
+/// Return an error if any pallet has differing call, event and error indexes, since these
+/// can't be represented by the single pallet index in V15 and V16 metadata. Remapping them
+/// would also mean renumbering the variants of the outer call, event and error enums, so
+/// we don't attempt it.
+fn check_pallet_indexes(m: &Metadata) -> Result<(), ToFrameMetadataError> {
+    let pallet = m
+        .pallets
+        .values()
+        .iter()
+        .find(|p| p.call_index != p.event_index || p.call_index != p.error_index);
+    match pallet {
+        Some(p) => Err(ToFrameMetadataError::PalletIndexesDiffer {
+            pallet_name: p.name.clone(),
+            call_index: p.call_index,
+            event_index: p.event_index,
+            error_index: p.error_index,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Return an error if the storage entry uses old hashers that we can't write out.
+fn check_storage_hashers(
+    pallet_name: &str,
+    entry: &StorageEntryMetadata,
+) -> Result<(), ToFrameMetadataError> {
+    if entry.info.use_old_v9_storage_hashers {
+        return Err(ToFrameMetadataError::OldStorageHashers {
+            pallet_name: pallet_name.to_string(),
+            entry_name: entry.name.clone(),
+        });
+    }
+    Ok(())
+}
+
+/// The bytes to use as the default value for a storage entry. Entries without a default
+/// value are "optional", and so default to an encoded `None`.
+fn storage_default_value(entry: &StorageEntryMetadata) -> Vec<u8> {
+    match &entry.info.default_value {
+        Some(value) => value.to_vec(),
+        None => vec![0],
+    }
+}
+
+/// Return the ID of a tuple type containing the given type IDs, adding one to the
+/// registry if no such tuple exists already.
+fn find_or_add_tuple(types: &mut scale_info::PortableRegistry, ids: &[u32]) -> u32 {
+    let existing = types.types.iter().find(|ty| match &ty.ty.type_def {
+        TypeDef::Tuple(tuple) => tuple.fields.iter().map(|f| f.id).eq(ids.iter().copied()),
+        _ => false,
+    });
+    if let Some(ty) = existing {
+        return ty.id;
+    }
+
+    let fields = ids.iter().map(|&id| id.into()).collect();
+    add_type(
+        types,
+        Path {
+            segments: Vec::new(),
+        },
+        TypeDef::Tuple(TypeDefTuple { fields }),
+    )
+}
+
+/// Return the ID of the type representing the runtime itself, which sits next to the outer
+/// call enum (eg `polkadot_runtime::Runtime` next to `polkadot_runtime::RuntimeCall`). If no
+/// such type exists, an empty composite type with that path is added to the registry.
+fn find_or_add_runtime_ty(types: &mut scale_info::PortableRegistry, call_enum_ty: u32) -> u32 {
+    let mut segments: Vec<String> = types
+        .resolve(call_enum_ty)
+        .map(|ty| ty.path.segments.clone())
+        .unwrap_or_default();
+    segments.pop();
+    segments.push("Runtime".to_string());
+
+    let existing = types
+        .types
+        .iter()
+        .find(|ty| ty.ty.path.segments == segments);
+    if let Some(ty) = existing {
+        return ty.id;
+    }
+
+    add_type(
+        types,
+        Path { segments },
+        TypeDef::Composite(TypeDefComposite { fields: Vec::new() }),
+    )
+}
+
+fn add_type(
+    types: &mut scale_info::PortableRegistry,
+    path: Path<PortableForm>,
+    type_def: TypeDef<PortableForm>,
+) -> u32 {
+    let id = types.types.len() as u32;
+    types.types.push(PortableType {
+        id,
+        ty: Type {
+            path,
+            type_params: Vec::new(),
+            type_def,
+            docs: Vec::new(),
+        },
+    });
+    id
+}
+
+#[cfg(test)]
+mod test {
+    use super::ToFrameMetadataError;
+    use crate::Metadata;
+    use crate::utils::test_utils::polkadot_metadata_small;
+
+    #[test]
+    fn v16_round_trip_is_lossless() {
+        let metadata = polkadot_metadata_small();
+
+        let bytes = metadata.encode_v16().unwrap();
+        let round_tripped = Metadata::decode_from(&bytes).unwrap();
+
+        assert_eq!(metadata.hasher().hash(), round_tripped.hasher().hash());
+        assert_eq!(metadata.types(), round_tripped.types());
+        assert_eq!(bytes, round_tripped.encode_v16().unwrap());
+    }
+
+    #[test]
+    fn v15_round_trip_is_lossless() {
+        let metadata = polkadot_metadata_small();
+
+        let bytes = metadata.encode_v15().unwrap();
+        let round_tripped = Metadata::decode_from(&bytes).unwrap();
+
+        assert_eq!(metadata.hasher().hash(), round_tripped.hasher().hash());
+        assert_eq!(bytes, round_tripped.encode_v15().unwrap());
+    }
+
+    #[test]
+    fn v15_requires_v4_extrinsics() {
+        let mut metadata = polkadot_metadata_small();
+        metadata.extrinsic.supported_versions = vec![5];
+
+        assert_eq!(
+            metadata.to_v15().unwrap_err(),
+            ToFrameMetadataError::UnsupportedExtrinsicVersion {
+                supported_versions: vec![5]
+            }
+        );
+        // V16 can describe V5 extrinsics.
+        assert!(metadata.to_v16().is_ok());
+    }
+
+    #[cfg(feature = "legacy")]
+    fn legacy_kusama_metadata(filename: &str, spec_version: u64) -> Metadata {
+        use codec::Decode;
+        use frame_metadata::RuntimeMetadata;
+
+        let bytes = std::fs::read(format!("../artifacts/kusama/{filename}"))
+            .expect("metadata artifact should exist");
+        let metadata = RuntimeMetadata::decode(&mut &*bytes).expect("metadata should decode");
+
+        let mut types = frame_decode::legacy_types::polkadot::relay_chain()
+            .for_spec_version(spec_version)
+            .to_owned();
+        types.prepend(frame_decode::helpers::type_registry_from_metadata_any(&metadata).unwrap());
+
+        match &metadata {
+            RuntimeMetadata::V11(m) => Metadata::from_v11(m, &types),
+            RuntimeMetadata::V13(m) => Metadata::from_v13(m, &types),
+            _ => panic!("Metadata version {} not expected", metadata.version()),
+        }
+        .expect("legacy metadata should convert")
+    }
+
+    #[cfg(feature = "legacy")]
+    #[test]
+    fn legacy_metadata_with_one_index_per_pallet_converts() {
+        let metadata = legacy_kusama_metadata("metadata_v13_9030.scale", 9030);
+
+        let bytes = metadata.encode_v16().unwrap();
+        let round_tripped = Metadata::decode_from(&bytes).unwrap();
+
+        let pallet_names = |m: &Metadata| {
+            m.pallets()
+                .map(|p| p.name().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(pallet_names(&metadata), pallet_names(&round_tripped));
+        assert!(metadata.to_v15().is_ok());
+    }
+
+    #[cfg(feature = "legacy")]
+    #[test]
+    fn legacy_metadata_with_separate_indexes_is_rejected() {
+        let metadata = legacy_kusama_metadata("metadata_v11_1045.scale", 1045);
+
+        assert!(matches!(
+            metadata.to_v15(),
+            Err(ToFrameMetadataError::PalletIndexesDiffer { .. })
+        ));
+        assert!(matches!(
+            metadata.to_v16(),
+            Err(ToFrameMetadataError::PalletIndexesDiffer { .. })
+        ));
+    }
+}
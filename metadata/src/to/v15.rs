@@ -0,0 +1,230 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+use super::{
+    ToFrameMetadataError, check_pallet_indexes, check_storage_hashers, find_or_add_runtime_ty,
+    find_or_add_tuple, storage_default_value,
+};
+use crate::{
+    ConstantMetadata, ExtrinsicMetadata, Metadata, PalletMetadataInner, RuntimeApiMetadataInner,
+    StorageEntryMetadata, StorageHasher,
+};
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use frame_metadata::v15;
+use scale_info::PortableRegistry;
+use scale_info::form::PortableForm;
+
+/// The only extrinsic version that V15 metadata can describe.
+const V15_EXTRINSIC_VERSION: u8 = 4;
+
+pub fn to_v15(m: &Metadata) -> Result<v15::RuntimeMetadataV15, ToFrameMetadataError> {
+    check_pallet_indexes(m)?;
+    let mut types = m.types.clone();
+
+    let pallets = m
+        .pallets
+        .values()
+        .iter()
+        .map(|p| to_pallet_metadata(p, &mut types))
+        .collect::<Result<_, _>>()?;
+
+    let apis = m
+        .apis
+        .values()
+        .iter()
+        .map(to_runtime_api_metadata)
+        .collect();
+
+    let extrinsic = to_extrinsic_metadata(&m.extrinsic, m.outer_enums.call_enum_ty, &mut types)?;
+    let ty = find_or_add_runtime_ty(&mut types, m.outer_enums.call_enum_ty);
+
+    Ok(v15::RuntimeMetadataV15 {
+        types,
+        pallets,
+        extrinsic,
+        ty: ty.into(),
+        apis,
+        outer_enums: v15::OuterEnums {
+            call_enum_ty: m.outer_enums.call_enum_ty.into(),
+            event_enum_ty: m.outer_enums.event_enum_ty.into(),
+            error_enum_ty: m.outer_enums.error_enum_ty.into(),
+        },
+        custom: m.custom.clone(),
+    })
+}
+
+fn to_pallet_metadata(
+    p: &PalletMetadataInner,
+    types: &mut PortableRegistry,
+) -> Result<v15::PalletMetadata<PortableForm>, ToFrameMetadataError> {
+    // Every pallet has already been checked to have a single index.
+    let index = p.call_index;
+
+    let storage = match &p.storage {
+        None => None,
+        Some(s) => Some(v15::PalletStorageMetadata {
+            prefix: s.prefix.clone(),
+            entries: s
+                .entries
+                .values()
+                .iter()
+                .map(|entry| {
+                    check_storage_hashers(&p.name, entry)?;
+                    Ok(to_storage_entry_metadata(entry, types))
+                })
+                .collect::<Result<_, ToFrameMetadataError>>()?,
+        }),
+    };
+
+    Ok(v15::PalletMetadata {
+        name: p.name.clone(),
+        storage,
+        calls: p
+            .call_ty
+            .map(|ty| v15::PalletCallMetadata { ty: ty.into() }),
+        event: p
+            .event_ty
+            .map(|ty| v15::PalletEventMetadata { ty: ty.into() }),
+        constants: p
+            .constants
+            .values()
+            .iter()
+            .map(to_constant_metadata)
+            .collect(),
+        error: p
+            .error_ty
+            .map(|ty| v15::PalletErrorMetadata { ty: ty.into() }),
+        index,
+        docs: p.docs.clone(),
+    })
+}
+
+fn to_storage_entry_metadata(
+    entry: &StorageEntryMetadata,
+    types: &mut PortableRegistry,
+) -> v15::StorageEntryMetadata<PortableForm> {
+    let keys = &*entry.info.keys;
+    let value = entry.info.value_id.into();
+
+    let ty = match keys {
+        [] => v15::StorageEntryType::Plain(value),
+        [key] => v15::StorageEntryType::Map {
+            hashers: vec![to_storage_hasher(key.hasher)],
+            key: key.key_id.into(),
+            value,
+        },
+        keys => {
+            let key_ids: Vec<u32> = keys.iter().map(|k| k.key_id).collect();
+            v15::StorageEntryType::Map {
+                hashers: keys.iter().map(|k| to_storage_hasher(k.hasher)).collect(),
+                key: find_or_add_tuple(types, &key_ids).into(),
+                value,
+            }
+        }
+    };
+
+    let modifier = match entry.info.default_value {
+        Some(_) => v15::StorageEntryModifier::Default,
+        None => v15::StorageEntryModifier::Optional,
+    };
+
+    v15::StorageEntryMetadata {
+        name: entry.name.clone(),
+        modifier,
+        ty,
+        default: storage_default_value(entry),
+        docs: entry.docs.clone(),
+    }
+}
+
+fn to_storage_hasher(hasher: StorageHasher) -> v15::StorageHasher {
+    match hasher {
+        StorageHasher::Blake2_128 => v15::StorageHasher::Blake2_128,
+        StorageHasher::Blake2_256 => v15::StorageHasher::Blake2_256,
+        StorageHasher::Blake2_128Concat => v15::StorageHasher::Blake2_128Concat,
+        StorageHasher::Twox128 => v15::StorageHasher::Twox128,
+        StorageHasher::Twox256 => v15::StorageHasher::Twox256,
+        StorageHasher::Twox64Concat => v15::StorageHasher::Twox64Concat,
+        StorageHasher::Identity => v15::StorageHasher::Identity,
+    }
+}
+
+fn to_constant_metadata(c: &ConstantMetadata) -> v15::PalletConstantMetadata<PortableForm> {
+    v15::PalletConstantMetadata {
+        name: c.name.clone(),
+        ty: c.ty.into(),
+        value: c.value.clone(),
+        docs: c.docs.clone(),
+    }
+}
+
+fn to_runtime_api_metadata(api: &RuntimeApiMetadataInner) -> v15::RuntimeApiMetadata<PortableForm> {
+    v15::RuntimeApiMetadata {
+        name: api.name.clone(),
+        methods: api
+            .methods
+            .values()
+            .iter()
+            .map(|method| v15::RuntimeApiMethodMetadata {
+                name: method.name.clone(),
+                inputs: method
+                    .info
+                    .inputs
+                    .iter()
+                    .map(|input| v15::RuntimeApiMethodParamMetadata {
+                        name: String::from(&*input.name),
+                        ty: input.id.into(),
+                    })
+                    .collect(),
+                output: method.info.output_id.into(),
+                docs: method.docs.clone(),
+            })
+            .collect(),
+        docs: api.docs.clone(),
+    }
+}
+
+fn to_extrinsic_metadata(
+    e: &ExtrinsicMetadata,
+    call_enum_ty: u32,
+    types: &mut PortableRegistry,
+) -> Result<v15::ExtrinsicMetadata<PortableForm>, ToFrameMetadataError> {
+    if !e.supported_versions.contains(&V15_EXTRINSIC_VERSION) {
+        return Err(ToFrameMetadataError::UnsupportedExtrinsicVersion {
+            supported_versions: e.supported_versions.clone(),
+        });
+    }
+
+    // V15 has no notion of transaction extension versions; the extensions it lists are the
+    // ones used in V4 extrinsics, which are those with version 0.
+    let signed_extensions: Vec<_> = match e.transaction_extensions_by_version.get(&0) {
+        Some(idxs) => idxs
+            .iter()
+            .map(|&idx| &e.transaction_extensions[idx as usize])
+            .collect(),
+        None if e.transaction_extensions.is_empty() => Vec::new(),
+        None => return Err(ToFrameMetadataError::NoVersion0TransactionExtensions),
+    };
+
+    let extra_ids: Vec<u32> = signed_extensions.iter().map(|ext| ext.extra_ty).collect();
+    let extra_ty = find_or_add_tuple(types, &extra_ids);
+
+    Ok(v15::ExtrinsicMetadata {
+        version: V15_EXTRINSIC_VERSION,
+        address_ty: e.address_ty.into(),
+        call_ty: call_enum_ty.into(),
+        signature_ty: e.signature_ty.into(),
+        extra_ty: extra_ty.into(),
+        signed_extensions: signed_extensions
+            .into_iter()
+            .map(|ext| v15::SignedExtensionMetadata {
+                identifier: ext.identifier.clone(),
+                ty: ext.extra_ty.into(),
+                additional_signed: ext.additional_ty.into(),
+            })
+            .collect(),
+    })
+}
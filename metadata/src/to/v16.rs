@@ -0,0 +1,264 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+use super::{
+    ToFrameMetadataError, check_pallet_indexes, check_storage_hashers, find_or_add_tuple,
+    storage_default_value,
+};
+use crate::{
+    ConstantMetadata, ExtrinsicMetadata, Metadata, PalletMetadataInner, RuntimeApiMetadataInner,
+    StorageEntryMetadata, StorageHasher,
+};
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use codec::Compact;
+use frame_metadata::v16;
+use scale_info::PortableRegistry;
+use scale_info::form::PortableForm;
+
+pub fn to_v16(m: &Metadata) -> Result<v16::RuntimeMetadataV16, ToFrameMetadataError> {
+    check_pallet_indexes(m)?;
+    let mut types = m.types.clone();
+
+    let pallets = m
+        .pallets
+        .values()
+        .iter()
+        .map(|p| to_pallet_metadata(p, &mut types))
+        .collect::<Result<_, _>>()?;
+
+    let apis = m
+        .apis
+        .values()
+        .iter()
+        .map(to_runtime_api_metadata)
+        .collect();
+
+    let custom = v16::CustomMetadata {
+        map: m
+            .custom
+            .map
+            .iter()
+            .map(|(name, value)| {
+                let value = v16::CustomValueMetadata {
+                    ty: value.ty,
+                    value: value.value.clone(),
+                };
+                (name.clone(), value)
+            })
+            .collect(),
+    };
+
+    Ok(v16::RuntimeMetadataV16 {
+        types,
+        pallets,
+        extrinsic: to_extrinsic_metadata(&m.extrinsic, m.outer_enums.call_enum_ty),
+        apis,
+        outer_enums: v16::OuterEnums {
+            call_enum_ty: m.outer_enums.call_enum_ty.into(),
+            event_enum_ty: m.outer_enums.event_enum_ty.into(),
+            error_enum_ty: m.outer_enums.error_enum_ty.into(),
+        },
+        custom,
+    })
+}
+
+fn to_pallet_metadata(
+    p: &PalletMetadataInner,
+    types: &mut PortableRegistry,
+) -> Result<v16::PalletMetadata<PortableForm>, ToFrameMetadataError> {
+    // Every pallet has already been checked to have a single index.
+    let index = p.call_index;
+
+    let storage = match &p.storage {
+        None => None,
+        Some(s) => Some(v16::PalletStorageMetadata {
+            prefix: s.prefix.clone(),
+            entries: s
+                .entries
+                .values()
+                .iter()
+                .map(|entry| {
+                    check_storage_hashers(&p.name, entry)?;
+                    Ok(to_storage_entry_metadata(entry, types))
+                })
+                .collect::<Result<_, ToFrameMetadataError>>()?,
+        }),
+    };
+
+    let view_functions = p
+        .view_functions
+        .values()
+        .iter()
+        .map(|vf| v16::PalletViewFunctionMetadata {
+            name: vf.name.clone(),
+            id: vf.info.query_id,
+            inputs: vf
+                .info
+                .inputs
+                .iter()
+                .map(|input| v16::FunctionParamMetadata {
+                    name: String::from(&*input.name),
+                    ty: input.id.into(),
+                })
+                .collect(),
+            output: vf.info.output_id.into(),
+            docs: vf.docs.clone(),
+            deprecation_info: v16::ItemDeprecationInfo::NotDeprecated,
+        })
+        .collect();
+
+    Ok(v16::PalletMetadata {
+        name: p.name.clone(),
+        storage,
+        calls: p.call_ty.map(|ty| v16::PalletCallMetadata {
+            ty: ty.into(),
+            deprecation_info: v16::EnumDeprecationInfo::nothing_deprecated(),
+        }),
+        event: p.event_ty.map(|ty| v16::PalletEventMetadata {
+            ty: ty.into(),
+            deprecation_info: v16::EnumDeprecationInfo::nothing_deprecated(),
+        }),
+        constants: p
+            .constants
+            .values()
+            .iter()
+            .map(to_constant_metadata)
+            .collect(),
+        error: p.error_ty.map(|ty| v16::PalletErrorMetadata {
+            ty: ty.into(),
+            deprecation_info: v16::EnumDeprecationInfo::nothing_deprecated(),
+        }),
+        associated_types: p
+            .associated_types
+            .iter()
+            .map(|(name, ty)| v16::PalletAssociatedTypeMetadata {
+                name: name.clone(),
+                ty: (*ty).into(),
+                docs: Vec::new(),
+            })
+            .collect(),
+        view_functions,
+        index,
+        docs: p.docs.clone(),
+        deprecation_info: v16::ItemDeprecationInfo::NotDeprecated,
+    })
+}
+
+fn to_storage_entry_metadata(
+    entry: &StorageEntryMetadata,
+    types: &mut PortableRegistry,
+) -> v16::StorageEntryMetadata<PortableForm> {
+    let keys = &*entry.info.keys;
+    let value = entry.info.value_id.into();
+
+    let ty = match keys {
+        [] => v16::StorageEntryType::Plain(value),
+        [key] => v16::StorageEntryType::Map {
+            hashers: vec![to_storage_hasher(key.hasher)],
+            key: key.key_id.into(),
+            value,
+        },
+        keys => {
+            let key_ids: Vec<u32> = keys.iter().map(|k| k.key_id).collect();
+            v16::StorageEntryType::Map {
+                hashers: keys.iter().map(|k| to_storage_hasher(k.hasher)).collect(),
+                key: find_or_add_tuple(types, &key_ids).into(),
+                value,
+            }
+        }
+    };
+
+    let modifier = match entry.info.default_value {
+        Some(_) => v16::StorageEntryModifier::Default,
+        None => v16::StorageEntryModifier::Optional,
+    };
+
+    v16::StorageEntryMetadata {
+        name: entry.name.clone(),
+        modifier,
+        ty,
+        default: storage_default_value(entry),
+        docs: entry.docs.clone(),
+        deprecation_info: v16::ItemDeprecationInfo::NotDeprecated,
+    }
+}
+
+fn to_storage_hasher(hasher: StorageHasher) -> v16::StorageHasher {
+    match hasher {
+        StorageHasher::Blake2_128 => v16::StorageHasher::Blake2_128,
+        StorageHasher::Blake2_256 => v16::StorageHasher::Blake2_256,
+        StorageHasher::Blake2_128Concat => v16::StorageHasher::Blake2_128Concat,
+        StorageHasher::Twox128 => v16::StorageHasher::Twox128,
+        StorageHasher::Twox256 => v16::StorageHasher::Twox256,
+        StorageHasher::Twox64Concat => v16::StorageHasher::Twox64Concat,
+        StorageHasher::Identity => v16::StorageHasher::Identity,
+    }
+}
+
+fn to_constant_metadata(c: &ConstantMetadata) -> v16::PalletConstantMetadata<PortableForm> {
+    v16::PalletConstantMetadata {
+        name: c.name.clone(),
+        ty: c.ty.into(),
+        value: c.value.clone(),
+        docs: c.docs.clone(),
+        deprecation_info: v16::ItemDeprecationInfo::NotDeprecated,
+    }
+}
+
+fn to_runtime_api_metadata(api: &RuntimeApiMetadataInner) -> v16::RuntimeApiMetadata<PortableForm> {
+    v16::RuntimeApiMetadata {
+        name: api.name.clone(),
+        methods: api
+            .methods
+            .values()
+            .iter()
+            .map(|method| v16::RuntimeApiMethodMetadata {
+                name: method.name.clone(),
+                inputs: method
+                    .info
+                    .inputs
+                    .iter()
+                    .map(|input| v16::FunctionParamMetadata {
+                        name: String::from(&*input.name),
+                        ty: input.id.into(),
+                    })
+                    .collect(),
+                output: method.info.output_id.into(),
+                docs: method.docs.clone(),
+                deprecation_info: v16::ItemDeprecationInfo::NotDeprecated,
+            })
+            .collect(),
+        docs: api.docs.clone(),
+        version: Compact(api.version),
+        deprecation_info: v16::ItemDeprecationInfo::NotDeprecated,
+    }
+}
+
+fn to_extrinsic_metadata(
+    e: &ExtrinsicMetadata,
+    call_enum_ty: u32,
+) -> v16::ExtrinsicMetadata<PortableForm> {
+    v16::ExtrinsicMetadata {
+        versions: e.supported_versions.clone(),
+        address_ty: e.address_ty.into(),
+        call_ty: call_enum_ty.into(),
+        signature_ty: e.signature_ty.into(),
+        transaction_extensions_by_version: e
+            .transaction_extensions_by_version
+            .iter()
+            .map(|(version, idxs)| (*version, idxs.iter().map(|idx| Compact(*idx)).collect()))
+            .collect(),
+        transaction_extensions: e
+            .transaction_extensions
+            .iter()
+            .map(|ext| v16::TransactionExtensionMetadata {
+                identifier: ext.identifier.clone(),
+                ty: ext.extra_ty.into(),
+                implicit: ext.additional_ty.into(),
+            })
+            .collect(),
+    }
+}
@@ -0,0 +1,294 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+//! Look up types in the metadata type registry by their Rust path, and
+//! decode or encode SCALE bytes against them.
+
+use crate::Metadata;
+use alloc::string::String;
+use alloc::vec::Vec;
+use scale_decode::DecodeAsType;
+use scale_encode::EncodeAsType;
+use scale_info::{Type, form::PortableForm};
+use thiserror::Error as DeriveError;
+
+/// An error looking up, decoding or encoding a type by its path.
+#[derive(Debug, DeriveError)]
+#[non_exhaustive]
+pub enum TypePathError {
+    /// No type with the given path (and generic parameters) exists in the registry.
+    #[error("No type with the path '{path}' and the given generic parameters was found")]
+    TypeNotFound {
+        /// The path that we looked for.
+        path: String,
+    },
+    /// More than one type has the given path, and no generic parameters were given
+    /// to tell them apart.
+    #[error(
+        "{count} types with the path '{path}' were found; provide generic parameters to pick one"
+    )]
+    AmbiguousPath {
+        /// The path that we looked for.
+        path: String,
+        /// How many types were found with this path.
+        count: usize,
+    },
+    /// The bytes could not be decoded into the given type.
+    #[error("Cannot decode type {type_id}: {error}")]
+    CannotDecode {
+        /// The ID of the type we tried to decode.
+        type_id: u32,
+        /// The decode error.
+        error: scale_decode::Error,
+    },
+    /// Decoding succeeded but some bytes were not consumed.
+    #[error("Decoding type {type_id} left {remaining} bytes unconsumed")]
+    LeftoverBytes {
+        /// The ID of the type we decoded.
+        type_id: u32,
+        /// The number of bytes left over.
+        remaining: usize,
+    },
+    /// The value could not be encoded into the given type.
+    #[error("Cannot encode type {type_id}: {error}")]
+    CannotEncode {
+        /// The ID of the type we tried to encode.
+        type_id: u32,
+        /// The encode error.
+        error: scale_encode::Error,
+    },
+}
+
+impl Metadata {
+    /// An iterator over the IDs of every type in the registry whose path matches the
+    /// one given, for example `"staging_xcm::VersionedXcm"`. More than one ID may be
+    /// returned if the type appears with different generic parameters.
+    pub fn type_ids_by_path<'a>(&'a self, path: &'a str) -> impl Iterator<Item = u32> + 'a {
+        self.types
+            .types
+            .iter()
+            .filter(move |t| path_matches(&t.ty, path))
+            .map(|t| t.id)
+    }
+
+    /// Find the ID of the type with the given path, for example
+    /// `"staging_xcm::VersionedXcm"`.
+    ///
+    /// `generics` are the type IDs of the generic parameters of the type, in order.
+    /// If none are given, the path must identify exactly one type in the registry.
+    pub fn type_id_by_path(&self, path: &str, generics: &[u32]) -> Result<u32, TypePathError> {
+        let mut ids = self.type_ids_by_path(path).filter(|&id| {
+            generics.is_empty()
+                || self.types.resolve(id).is_some_and(|ty| {
+                    ty.type_params.len() == generics.len()
+                        && ty
+                            .type_params
+                            .iter()
+                            .zip(generics)
+                            .all(|(param, &g)| param.ty.as_ref().map(|ty| ty.id) == Some(g))
+                })
+        });
+
+        let Some(id) = ids.next() else {
+            return Err(TypePathError::TypeNotFound { path: path.into() });
+        };
+        let rest = ids.count();
+        if rest > 0 {
+            return Err(TypePathError::AmbiguousPath {
+                path: path.into(),
+                count: rest + 1,
+            });
+        }
+        Ok(id)
+    }
+
+    /// Decode the given bytes into some type `T` (for example a `scale_value::Value`),
+    /// treating them as an instance of the type with the given ID. All of the bytes
+    /// must be consumed.
+    pub fn decode_as_type<T: DecodeAsType>(
+        &self,
+        bytes: &[u8],
+        type_id: u32,
+    ) -> Result<T, TypePathError> {
+        let cursor = &mut &*bytes;
+        let value = T::decode_as_type(cursor, type_id, &self.types)
+            .map_err(|error| TypePathError::CannotDecode { type_id, error })?;
+
+        if !cursor.is_empty() {
+            return Err(TypePathError::LeftoverBytes {
+                type_id,
+                remaining: cursor.len(),
+            });
+        }
+        Ok(value)
+    }
+
+    /// Like [`Metadata::decode_as_type`], but the type is looked up using
+    /// [`Metadata::type_id_by_path`].
+    pub fn decode_as_type_by_path<T: DecodeAsType>(
+        &self,
+        bytes: &[u8],
+        path: &str,
+        generics: &[u32],
+    ) -> Result<T, TypePathError> {
+        let type_id = self.type_id_by_path(path, generics)?;
+        self.decode_as_type(bytes, type_id)
+    }
+
+    /// SCALE encode some value (for example a `scale_value::Value`) into the shape of
+    /// the type with the given ID.
+    pub fn encode_as_type<V: EncodeAsType + ?Sized>(
+        &self,
+        value: &V,
+        type_id: u32,
+    ) -> Result<Vec<u8>, TypePathError> {
+        value
+            .encode_as_type(type_id, &self.types)
+            .map_err(|error| TypePathError::CannotEncode { type_id, error })
+    }
+
+    /// Like [`Metadata::encode_as_type`], but the type is looked up using
+    /// [`Metadata::type_id_by_path`].
+    pub fn encode_as_type_by_path<V: EncodeAsType + ?Sized>(
+        &self,
+        value: &V,
+        path: &str,
+        generics: &[u32],
+    ) -> Result<Vec<u8>, TypePathError> {
+        let type_id = self.type_id_by_path(path, generics)?;
+        self.encode_as_type(value, type_id)
+    }
+}
+
+// Compare the path segments of a type with a `::` separated path, without allocating.
+fn path_matches(ty: &Type<PortableForm>, path: &str) -> bool {
+    let segments = &ty.path.segments;
+    !segments.is_empty()
+        && segments.len() == path.split("::").count()
+        && segments.iter().zip(path.split("::")).all(|(a, b)| a == b)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use codec::Encode;
+    use frame_metadata::v15;
+    use scale_info::meta_type;
+    use scale_value::{Value, value};
+
+    #[allow(dead_code)]
+    #[derive(scale_info::TypeInfo, Encode)]
+    struct Wrapper<T> {
+        inner: T,
+        count: u8,
+    }
+
+    #[allow(dead_code)]
+    #[derive(scale_info::TypeInfo)]
+    enum Call {
+        A(Wrapper<u32>),
+        B(Wrapper<bool>),
+    }
+
+    fn metadata() -> Metadata {
+        let pallet = v15::PalletMetadata {
+            name: "Test",
+            storage: None,
+            calls: Some(v15::PalletCallMetadata {
+                ty: meta_type::<Call>(),
+            }),
+            event: None,
+            constants: vec![],
+            error: None,
+            index: 0,
+            docs: vec![],
+        };
+
+        v15::RuntimeMetadataV15::new(
+            vec![pallet],
+            v15::ExtrinsicMetadata {
+                version: 0,
+                signed_extensions: vec![],
+                address_ty: meta_type::<()>(),
+                call_ty: meta_type::<()>(),
+                signature_ty: meta_type::<()>(),
+                extra_ty: meta_type::<()>(),
+            },
+            meta_type::<()>(),
+            vec![],
+            v15::OuterEnums {
+                call_enum_ty: meta_type::<()>(),
+                event_enum_ty: meta_type::<()>(),
+                error_enum_ty: meta_type::<()>(),
+            },
+            v15::CustomMetadata {
+                map: Default::default(),
+            },
+        )
+        .try_into()
+        .expect("can build valid metadata")
+    }
+
+    fn primitive_id(metadata: &Metadata, prim: scale_info::TypeDefPrimitive) -> u32 {
+        metadata
+            .types()
+            .types
+            .iter()
+            .find(|t| t.ty.type_def == scale_info::TypeDef::Primitive(prim.clone()))
+            .expect("primitive should exist")
+            .id
+    }
+
+    #[test]
+    fn lookup_by_path_and_generics() {
+        let metadata = metadata();
+        let path = "subxt_metadata::type_paths::test::Wrapper";
+
+        assert_eq!(metadata.type_ids_by_path(path).count(), 2);
+        assert!(matches!(
+            metadata.type_id_by_path(path, &[]),
+            Err(TypePathError::AmbiguousPath { count: 2, .. })
+        ));
+        assert!(matches!(
+            metadata.type_id_by_path("subxt_metadata::type_paths::Wrapper", &[]),
+            Err(TypePathError::TypeNotFound { .. })
+        ));
+
+        let u32_id = primitive_id(&metadata, scale_info::TypeDefPrimitive::U32);
+        let bool_id = primitive_id(&metadata, scale_info::TypeDefPrimitive::Bool);
+        let u32_wrapper = metadata.type_id_by_path(path, &[u32_id]).unwrap();
+        let bool_wrapper = metadata.type_id_by_path(path, &[bool_id]).unwrap();
+        assert_ne!(u32_wrapper, bool_wrapper);
+    }
+
+    #[test]
+    fn decode_and_encode_by_path() {
+        let metadata = metadata();
+        let path = "subxt_metadata::type_paths::test::Wrapper";
+        let u32_id = primitive_id(&metadata, scale_info::TypeDefPrimitive::U32);
+
+        let bytes = Wrapper {
+            inner: 1234u32,
+            count: 7,
+        }
+        .encode();
+
+        let decoded: Value = metadata
+            .decode_as_type_by_path(&bytes, path, &[u32_id])
+            .unwrap();
+        assert_eq!(decoded, value!({ inner: 1234u32, count: 7u8 }));
+
+        let encoded = metadata
+            .encode_as_type_by_path(&decoded, path, &[u32_id])
+            .unwrap();
+        assert_eq!(encoded, bytes);
+
+        let mut too_long = bytes.clone();
+        too_long.push(0);
+        assert!(matches!(
+            metadata.decode_as_type_by_path::<Value>(&too_long, path, &[u32_id]),
+            Err(TypePathError::LeftoverBytes { remaining: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,477 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+//! Render types in the metadata type registry as human readable, Rust-like
+//! signatures, and build example values for them.
+
+use crate::Metadata;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+use scale_info::{Field, PortableRegistry, Type, TypeDef, TypeDefPrimitive, form::PortableForm};
+
+impl Metadata {
+    /// Render the type with the given ID as a Rust-like signature, for example
+    /// `Vec<(AccountId32, Compact<u128>)>`. See [`TypeSignature`] for the available
+    /// options.
+    pub fn type_signature(&self, type_id: u32) -> TypeSignature<'_> {
+        TypeSignature {
+            types: &self.types,
+            type_id,
+            max_depth: 0,
+            resolve_generics: true,
+        }
+    }
+}
+
+/// A Rust-like signature for some type, which is rendered via its [`fmt::Display`]
+/// implementation. This is returned from [`Metadata::type_signature()`].
+#[derive(Debug, Clone, Copy)]
+pub struct TypeSignature<'a> {
+    types: &'a PortableRegistry,
+    type_id: u32,
+    max_depth: usize,
+    resolve_generics: bool,
+}
+
+impl<'a> TypeSignature<'a> {
+    /// How many levels of named structs and enums to expand into their fields and variants.
+    /// By default this is 0, and so only the names of such types are shown. Types which
+    /// contain themselves are never expanded inside of themselves.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// If true (the default), the generic parameters of named types and the types of
+    /// fields are rendered from the types they resolve to, eg `BoundedVec<u8>`. If false,
+    /// the names given in the source code are shown where we have them, eg
+    /// `BoundedVec<T, S>` and `T::AccountId`.
+    pub fn resolve_generics(mut self, resolve_generics: bool) -> Self {
+        self.resolve_generics = resolve_generics;
+        self
+    }
+
+    /// Return a JSON-like template showing an example value of this type, for example
+    /// `{ "free": 0, "reserved": 0, "frozen": 0, "flags": 0 }`. Enums are shown using their
+    /// first variant, and bytes are shown as hex strings.
+    pub fn example(&self) -> String {
+        let mut out = String::new();
+        let mut stack = Vec::new();
+        write_example(self.types, self.type_id, &mut stack, &mut out);
+        out
+    }
+
+    fn write_type(
+        &self,
+        type_id: u32,
+        depth: usize,
+        stack: &mut Vec<u32>,
+        f: &mut impl Write,
+    ) -> fmt::Result {
+        let Some(ty) = self.types.resolve(type_id) else {
+            return write!(f, "<unknown type {type_id}>");
+        };
+
+        match &ty.type_def {
+            TypeDef::Composite(composite) => {
+                if ty.path.segments.is_empty() {
+                    // Anonymous structs are always expanded, since they have no name to show.
+                    if composite.fields.is_empty() {
+                        return f.write_str("()");
+                    }
+                    let mut fields = String::new();
+                    self.write_fields(&composite.fields, depth, stack, &mut fields)?;
+                    return f.write_str(fields.trim_start());
+                }
+                if depth >= self.max_depth || stack.contains(&type_id) {
+                    return self.write_name(ty, stack, f);
+                }
+                f.write_str("struct ")?;
+                self.write_name(ty, stack, f)?;
+                stack.push(type_id);
+                self.write_fields(&composite.fields, depth + 1, stack, f)?;
+                stack.pop();
+                Ok(())
+            }
+            TypeDef::Variant(variant) => {
+                if depth >= self.max_depth || stack.contains(&type_id) {
+                    return self.write_name(ty, stack, f);
+                }
+                f.write_str("enum ")?;
+                self.write_name(ty, stack, f)?;
+                f.write_str(" {")?;
+                stack.push(type_id);
+                for (idx, var) in variant.variants.iter().enumerate() {
+                    f.write_str(if idx == 0 { " " } else { ", " })?;
+                    f.write_str(&var.name)?;
+                    self.write_fields(&var.fields, depth + 1, stack, f)?;
+                }
+                stack.pop();
+                f.write_str(if variant.variants.is_empty() {
+                    "}"
+                } else {
+                    " }"
+                })
+            }
+            TypeDef::Sequence(seq) => {
+                f.write_str("Vec<")?;
+                self.write_type(seq.type_param.id, depth, stack, f)?;
+                f.write_char('>')
+            }
+            TypeDef::Array(arr) => {
+                f.write_char('[')?;
+                self.write_type(arr.type_param.id, depth, stack, f)?;
+                write!(f, "; {}]", arr.len)
+            }
+            TypeDef::Tuple(tuple) => {
+                f.write_char('(')?;
+                for (idx, field) in tuple.fields.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_str(", ")?;
+                    }
+                    self.write_type(field.id, depth, stack, f)?;
+                }
+                if tuple.fields.len() == 1 {
+                    f.write_char(',')?;
+                }
+                f.write_char(')')
+            }
+            TypeDef::Primitive(primitive) => f.write_str(primitive_name(primitive)),
+            TypeDef::Compact(compact) => {
+                f.write_str("Compact<")?;
+                self.write_type(compact.type_param.id, depth, stack, f)?;
+                f.write_char('>')
+            }
+            TypeDef::BitSequence(bits) => {
+                f.write_str("BitVec<")?;
+                self.write_type(bits.bit_store_type.id, depth, stack, f)?;
+                f.write_str(", ")?;
+                self.write_type(bits.bit_order_type.id, depth, stack, f)?;
+                f.write_char('>')
+            }
+        }
+    }
+
+    // Write the last segment of a type's path, followed by any generic parameters.
+    fn write_name(
+        &self,
+        ty: &Type<PortableForm>,
+        stack: &mut Vec<u32>,
+        f: &mut impl Write,
+    ) -> fmt::Result {
+        f.write_str(ty.path.segments.last().map(|s| &**s).unwrap_or_default())?;
+
+        if self.resolve_generics {
+            let params: Vec<u32> = ty
+                .type_params
+                .iter()
+                .filter_map(|p| p.ty)
+                .map(|t| t.id)
+                .collect();
+            if params.is_empty() {
+                return Ok(());
+            }
+            f.write_char('<')?;
+            for (idx, &param) in params.iter().enumerate() {
+                if idx > 0 {
+                    f.write_str(", ")?;
+                }
+                // Generic parameters are never expanded.
+                self.write_type(param, self.max_depth, stack, f)?;
+            }
+            f.write_char('>')?;
+        } else if !ty.type_params.is_empty() {
+            f.write_char('<')?;
+            for (idx, param) in ty.type_params.iter().enumerate() {
+                if idx > 0 {
+                    f.write_str(", ")?;
+                }
+                f.write_str(&param.name)?;
+            }
+            f.write_char('>')?;
+        }
+        Ok(())
+    }
+
+    // Write the fields of a struct or enum variant, eg ` { a: u8, b: bool }` or `(u8, bool)`.
+    // Named fields are preceded by a space so that they can follow a type or variant name.
+    fn write_fields(
+        &self,
+        fields: &[Field<PortableForm>],
+        depth: usize,
+        stack: &mut Vec<u32>,
+        f: &mut impl Write,
+    ) -> fmt::Result {
+        if fields.is_empty() {
+            return Ok(());
+        }
+
+        let is_named = fields.iter().all(|field| field.name.is_some());
+        f.write_str(if is_named { " { " } else { "(" })?;
+        for (idx, field) in fields.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(", ")?;
+            }
+            if let Some(name) = field.name.as_ref().filter(|_| is_named) {
+                write!(f, "{name}: ")?;
+            }
+            match field.type_name.as_ref().filter(|_| !self.resolve_generics) {
+                Some(type_name) => f.write_str(type_name)?,
+                None => self.write_type(field.ty.id, depth, stack, f)?,
+            }
+        }
+        f.write_str(if is_named { " }" } else { ")" })
+    }
+}
+
+impl fmt::Display for TypeSignature<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut stack = Vec::new();
+        self.write_type(self.type_id, 0, &mut stack, f)
+    }
+}
+
+fn primitive_name(primitive: &TypeDefPrimitive) -> &'static str {
+    match primitive {
+        TypeDefPrimitive::Bool => "bool",
+        TypeDefPrimitive::Char => "char",
+        TypeDefPrimitive::Str => "String",
+        TypeDefPrimitive::U8 => "u8",
+        TypeDefPrimitive::U16 => "u16",
+        TypeDefPrimitive::U32 => "u32",
+        TypeDefPrimitive::U64 => "u64",
+        TypeDefPrimitive::U128 => "u128",
+        TypeDefPrimitive::U256 => "U256",
+        TypeDefPrimitive::I8 => "i8",
+        TypeDefPrimitive::I16 => "i16",
+        TypeDefPrimitive::I32 => "i32",
+        TypeDefPrimitive::I64 => "i64",
+        TypeDefPrimitive::I128 => "i128",
+        TypeDefPrimitive::I256 => "I256",
+    }
+}
+
+// Write an example value for the given type. Any type found inside of itself is
+// written as `null` so that recursive types have a finite example.
+fn write_example(types: &PortableRegistry, type_id: u32, stack: &mut Vec<u32>, out: &mut String) {
+    let Some(ty) = types.resolve(type_id) else {
+        out.push_str("null");
+        return;
+    };
+    if stack.contains(&type_id) {
+        out.push_str("null");
+        return;
+    }
+    stack.push(type_id);
+
+    match &ty.type_def {
+        TypeDef::Composite(composite) => {
+            write_fields_example(types, &composite.fields, stack, out);
+        }
+        TypeDef::Variant(variant) => match variant.variants.first() {
+            None => out.push_str("null"),
+            Some(var) if var.fields.is_empty() => {
+                let _ = write!(out, "\"{}\"", var.name);
+            }
+            Some(var) => {
+                let _ = write!(out, "{{ \"{}\": ", var.name);
+                write_fields_example(types, &var.fields, stack, out);
+                out.push_str(" }");
+            }
+        },
+        TypeDef::Sequence(seq) => {
+            if is_u8(types, seq.type_param.id) {
+                out.push_str("\"0x\"");
+            } else {
+                out.push('[');
+                write_example(types, seq.type_param.id, stack, out);
+                out.push(']');
+            }
+        }
+        TypeDef::Array(arr) => {
+            if is_u8(types, arr.type_param.id) {
+                out.push_str("\"0x");
+                for _ in 0..arr.len {
+                    out.push_str("00");
+                }
+                out.push('"');
+            } else {
+                out.push('[');
+                for idx in 0..arr.len {
+                    if idx > 0 {
+                        out.push_str(", ");
+                    }
+                    write_example(types, arr.type_param.id, stack, out);
+                }
+                out.push(']');
+            }
+        }
+        TypeDef::Tuple(tuple) => {
+            if tuple.fields.is_empty() {
+                out.push_str("null");
+            } else {
+                out.push('[');
+                for (idx, field) in tuple.fields.iter().enumerate() {
+                    if idx > 0 {
+                        out.push_str(", ");
+                    }
+                    write_example(types, field.id, stack, out);
+                }
+                out.push(']');
+            }
+        }
+        TypeDef::Primitive(primitive) => out.push_str(match primitive {
+            TypeDefPrimitive::Bool => "false",
+            TypeDefPrimitive::Char => "\"a\"",
+            TypeDefPrimitive::Str => "\"\"",
+            _ => "0",
+        }),
+        TypeDef::Compact(compact) => write_example(types, compact.type_param.id, stack, out),
+        TypeDef::BitSequence(_) => out.push_str("[]"),
+    }
+
+    stack.pop();
+}
+
+// Write an example value for some fields. Named fields are written as an object, a single
+// unnamed field is written as its inner value, and several unnamed fields as an array.
+fn write_fields_example(
+    types: &PortableRegistry,
+    fields: &[Field<PortableForm>],
+    stack: &mut Vec<u32>,
+    out: &mut String,
+) {
+    match fields {
+        [] => out.push_str("null"),
+        [field] if field.name.is_none() => write_example(types, field.ty.id, stack, out),
+        fields if fields.iter().all(|field| field.name.is_some()) => {
+            out.push_str("{ ");
+            for (idx, field) in fields.iter().enumerate() {
+                if idx > 0 {
+                    out.push_str(", ");
+                }
+                let name = field.name.as_deref().unwrap_or_default();
+                let _ = write!(out, "\"{name}\": ");
+                write_example(types, field.ty.id, stack, out);
+            }
+            out.push_str(" }");
+        }
+        fields => {
+            out.push('[');
+            for (idx, field) in fields.iter().enumerate() {
+                if idx > 0 {
+                    out.push_str(", ");
+                }
+                write_example(types, field.ty.id, stack, out);
+            }
+            out.push(']');
+        }
+    }
+}
+
+fn is_u8(types: &PortableRegistry, type_id: u32) -> bool {
+    types
+        .resolve(type_id)
+        .is_some_and(|ty| matches!(ty.type_def, TypeDef::Primitive(TypeDefPrimitive::U8)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use codec::Compact;
+    use scale_info::{MetaType, Registry, TypeInfo};
+
+    #[allow(dead_code)]
+    #[derive(TypeInfo)]
+    struct AccountData<Balance> {
+        free: Balance,
+        flags: [u8; 2],
+    }
+
+    #[allow(dead_code)]
+    #[derive(TypeInfo)]
+    enum MultiAddress {
+        Id([u8; 4]),
+        Index(Compact<u32>),
+        Raw(Vec<u8>),
+    }
+
+    #[allow(dead_code)]
+    #[derive(TypeInfo)]
+    struct Node {
+        value: u8,
+        children: Vec<Node>,
+    }
+
+    fn signature<T: TypeInfo + 'static>(
+        f: impl FnOnce(TypeSignature<'_>) -> TypeSignature<'_>,
+    ) -> (String, String) {
+        let mut registry = Registry::new();
+        let id = registry.register_type(&MetaType::new::<T>()).id;
+        let types: PortableRegistry = registry.into();
+        let signature = f(TypeSignature {
+            types: &types,
+            type_id: id,
+            max_depth: 0,
+            resolve_generics: true,
+        });
+        (signature.to_string(), signature.example())
+    }
+
+    #[test]
+    fn renders_unnamed_types() {
+        let (sig, example) = signature::<Vec<(bool, Compact<u128>)>>(|s| s);
+        assert_eq!(sig, "Vec<(bool, Compact<u128>)>");
+        assert_eq!(example, "[[false, 0]]");
+
+        let (sig, example) = signature::<([u8; 2], (String,))>(|s| s);
+        assert_eq!(sig, "([u8; 2], (String,))");
+        assert_eq!(example, "[\"0x0000\", [\"\"]]");
+    }
+
+    #[test]
+    fn renders_generics() {
+        let (sig, _) = signature::<AccountData<u64>>(|s| s);
+        assert_eq!(sig, "AccountData<u64>");
+
+        let (sig, _) = signature::<AccountData<u64>>(|s| s.resolve_generics(false));
+        assert_eq!(sig, "AccountData<Balance>");
+
+        let (sig, _) = signature::<AccountData<u64>>(|s| s.resolve_generics(false).max_depth(1));
+        assert_eq!(
+            sig,
+            "struct AccountData<Balance> { free: Balance, flags: [u8; 2] }"
+        );
+    }
+
+    #[test]
+    fn expands_to_the_given_depth() {
+        let (sig, example) = signature::<Option<MultiAddress>>(|s| s);
+        assert_eq!(sig, "Option<MultiAddress>");
+        assert_eq!(example, "\"None\"");
+
+        let (sig, _) = signature::<Option<MultiAddress>>(|s| s.max_depth(1));
+        assert_eq!(
+            sig,
+            "enum Option<MultiAddress> { None, Some(MultiAddress) }"
+        );
+
+        let (sig, _) = signature::<Option<MultiAddress>>(|s| s.max_depth(2));
+        assert_eq!(
+            sig,
+            "enum Option<MultiAddress> { None, Some(enum MultiAddress { Id([u8; 4]), Index(Compact<u32>), Raw(Vec<u8>) }) }"
+        );
+
+        let (_, example) = signature::<MultiAddress>(|s| s);
+        assert_eq!(example, "{ \"Id\": \"0x00000000\" }");
+    }
+
+    #[test]
+    fn handles_recursive_types() {
+        let (sig, example) = signature::<Node>(|s| s.max_depth(10));
+        assert_eq!(sig, "struct Node { value: u8, children: Vec<Node> }");
+        assert_eq!(example, "{ \"value\": 0, \"children\": [null] }");
+    }
+}
@@ -5,3 +5,6 @@
 pub mod ordered_map;
 pub mod validation;
 pub mod variant_index;
+
+#[cfg(test)]
+pub mod test_utils;
@@ -0,0 +1,19 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+//! Helpers shared by the tests in this crate.
+
+use crate::Metadata;
+
+/// Decode the metadata artifact with the given file name, from the `artifacts` folder.
+pub fn metadata_from_artifact(name: &str) -> Metadata {
+    let bytes =
+        std::fs::read(format!("../artifacts/{name}")).expect("metadata artifact should exist");
+    Metadata::decode_from(&bytes).expect("metadata should decode")
+}
+
+/// The small Polkadot metadata artifact that most tests are written against.
+pub fn polkadot_metadata_small() -> Metadata {
+    metadata_from_artifact("polkadot_metadata_small.scale")
+}
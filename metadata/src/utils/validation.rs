@@ -354,6 +354,18 @@ pub fn get_call_hash(pallet: &PalletMetadata, call_name: &str) -> Option<Hash> {
     Some(hash)
 }
 
+/// Obtain the hash for a specific event, or an error if it's not found.
+pub fn get_event_hash(pallet: &PalletMetadata, event_name: &str) -> Option<Hash> {
+    let event_variant = pallet
+        .event_variants()?
+        .iter()
+        .find(|variant| variant.name == event_name)?;
+
+    // hash the specific variant representing the event we are interested in.
+    let hash = get_variant_hash(pallet.types, event_variant, &mut HashMap::new());
+    Some(hash)
+}
+
 /// Obtain the hash of a specific runtime API method, or an error if it's not found.
 pub fn get_runtime_api_hash(runtime_api: &RuntimeApiMethodMetadata) -> Hash {
     let registry = runtime_api.types;
@@ -409,6 +409,36 @@ impl<T: RpcConfig> ChainHeadRpcMethods<T> {
 
         Ok(ArchiveStorageSubscription { sub, done: false })
     }
+
+    /// Compare the storage at `block_hash` with the storage at `previous_block_hash` (or the
+    /// parent of `block_hash` if not given), and return a subscription which streams back the
+    /// entries under the given keys which differ between the two.
+    pub async fn archive_v1_storage_diff(
+        &self,
+        block_hash: T::Hash,
+        items: impl IntoIterator<Item = ArchiveStorageDiffQuery<&[u8]>>,
+        previous_block_hash: Option<T::Hash>,
+    ) -> Result<ArchiveStorageDiffSubscription<T::Hash>, Error> {
+        let items: Vec<ArchiveStorageDiffQuery<String>> = items
+            .into_iter()
+            .map(|item| ArchiveStorageDiffQuery {
+                key: to_hex(item.key),
+                return_type: item.return_type,
+                child_trie_key: item.child_trie_key.map(to_hex),
+            })
+            .collect();
+
+        let sub = self
+            .client
+            .subscribe(
+                "archive_v1_storageDiff",
+                rpc_params![block_hash, items, previous_block_hash],
+                "archive_v1_stopStorageDiff",
+            )
+            .await?;
+
+        Ok(ArchiveStorageDiffSubscription { sub, done: false })
+    }
 }
 
 /// This represents events generated by the `follow` method.
@@ -737,6 +767,29 @@ pub struct ArchiveStorageQuery<Key> {
     pub pagination_start_key: Option<Key>,
 }
 
+/// The storage item received as parameter to [`ChainHeadRpcMethods::archive_v1_storage_diff()`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveStorageDiffQuery<Key> {
+    /// The key prefix; every entry whose key begins with this is compared.
+    pub key: Key,
+    /// Whether to return the value or the hash of the value of entries that differ.
+    pub return_type: ArchiveStorageDiffReturnType,
+    /// If given, the entries are compared in this default child trie rather than the main trie.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub child_trie_key: Option<Key>,
+}
+
+/// What to return for each entry which differs in an archive storage diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ArchiveStorageDiffReturnType {
+    /// Return the value of the entry.
+    Value,
+    /// Return the hash of the value of the entry.
+    Hash,
+}
+
 /// The type of the storage query.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -992,6 +1045,95 @@ impl<Hash> ArchiveStorageEvent<Hash> {
     }
 }
 
+/// A subscription which returns archive storage diff events, and ends when a Done or Error event occurs.
+pub struct ArchiveStorageDiffSubscription<Hash> {
+    sub: RpcSubscription<ArchiveStorageDiffEvent<Hash>>,
+    done: bool,
+}
+
+impl<H: Hash> ArchiveStorageDiffSubscription<H> {
+    /// Fetch the next item in the stream.
+    pub async fn next(&mut self) -> Option<<Self as Stream>::Item> {
+        <Self as StreamExt>::next(self).await
+    }
+    /// Fetch the subscription ID for the stream.
+    pub fn subscription_id(&self) -> Option<&str> {
+        self.sub.subscription_id()
+    }
+}
+
+impl<H: Hash> Stream for ArchiveStorageDiffSubscription<H> {
+    type Item = <RpcSubscription<ArchiveStorageDiffEvent<H>> as Stream>::Item;
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+
+        let res = self.sub.poll_next_unpin(cx);
+
+        if let Poll::Ready(Some(Ok(
+            ArchiveStorageDiffEvent::Done | ArchiveStorageDiffEvent::Error(..),
+        ))) = &res
+        {
+            // No more events will occur after "done" or "error" events.
+            self.done = true;
+        }
+
+        res
+    }
+}
+
+/// Responses returned from [`ArchiveStorageDiffSubscription`].
+#[derive(Debug, Deserialize)]
+#[serde(tag = "event")]
+pub enum ArchiveStorageDiffEvent<Hash> {
+    /// An entry which differs between the two blocks.
+    #[serde(rename = "storageDiff")]
+    Item(ArchiveStorageDiffEventItem<Hash>),
+    /// A human-readable error indicating what went wrong. No more storage diff events
+    /// will be emitted after this.
+    #[serde(rename = "storageDiffError")]
+    Error(ArchiveStorageEventError),
+    /// No more storage diff events will be emitted after this.
+    #[serde(rename = "storageDiffDone")]
+    Done,
+}
+
+/// An entry returned from the [`ChainHeadRpcMethods::archive_v1_storage_diff()`] subscription.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveStorageDiffEventItem<Hash> {
+    /// String containing the hexadecimal-encoded key of the storage entry.
+    pub key: Bytes,
+    /// String containing the hexadecimal-encoded value of the storage entry.
+    /// Returned when the return type is [`ArchiveStorageDiffReturnType::Value`] and the entry was not deleted.
+    pub value: Option<Bytes>,
+    /// String containing the hexadecimal-encoded hash of the storage entry.
+    /// Returned when the return type is [`ArchiveStorageDiffReturnType::Hash`] and the entry was not deleted.
+    pub hash: Option<Hash>,
+    /// How the entry differs between the two blocks.
+    #[serde(rename = "type")]
+    pub diff_type: ArchiveStorageDiffType,
+    /// String containing the hexadecimal-encoded key of the child trie of the "default" namespace if the storage entry
+    /// is part of a child trie. If the storage entry is part of the main trie, this field is not present.
+    pub child_trie_key: Option<Bytes>,
+}
+
+/// How some entry differs between the two blocks compared in an archive storage diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ArchiveStorageDiffType {
+    /// The entry exists only in the newer block.
+    Added,
+    /// The entry exists in both blocks but has a different value.
+    Modified,
+    /// The entry exists only in the previous block.
+    Deleted,
+}
+
 /// Something went wrong during the [`ChainHeadRpcMethods::archive_v1_storage()`] subscription.
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 #[serde(rename_all = "camelCase")]
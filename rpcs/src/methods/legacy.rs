@@ -41,6 +41,16 @@ impl<T: RpcConfig> LegacyRpcMethods<T> {
         Ok(data.map(|b| b.0))
     }
 
+    /// Fetch the hash of the value at a given storage key.
+    pub async fn state_get_storage_hash(
+        &self,
+        key: &[u8],
+        hash: Option<T::Hash>,
+    ) -> Result<Option<T::Hash>, Error> {
+        let params = rpc_params![to_hex(key), hash];
+        self.client.request("state_getStorageHash", params).await
+    }
+
     /// Returns the keys with prefix with pagination support.
     /// Up to `count` keys will be returned.
     /// If `start_key` is passed, return next keys in storage in lexicographic order.
@@ -85,6 +95,63 @@ impl<T: RpcConfig> LegacyRpcMethods<T> {
         self.client.request("state_queryStorageAt", params).await
     }
 
+    /// Fetch the raw bytes for a given storage key in some child trie. The `child_storage_key`
+    /// is the prefixed key of the child trie, ie `:child_storage:default:` followed by the
+    /// child trie key.
+    pub async fn childstate_get_storage(
+        &self,
+        child_storage_key: &[u8],
+        key: &[u8],
+        hash: Option<T::Hash>,
+    ) -> Result<Option<StorageData>, Error> {
+        let params = rpc_params![to_hex(child_storage_key), to_hex(key), hash];
+        let data: Option<Bytes> = self.client.request("childstate_getStorage", params).await?;
+        Ok(data.map(|b| b.0))
+    }
+
+    /// Fetch the raw bytes for each of the given storage keys in some child trie. The
+    /// values are returned in the same order as the keys, and are `None` where no value exists.
+    pub async fn childstate_get_storage_entries(
+        &self,
+        child_storage_key: &[u8],
+        keys: impl IntoIterator<Item = &[u8]>,
+        hash: Option<T::Hash>,
+    ) -> Result<Vec<Option<StorageData>>, Error> {
+        let keys: Vec<String> = keys.into_iter().map(to_hex).collect();
+        let params = rpc_params![to_hex(child_storage_key), keys, hash];
+        let data: Vec<Option<Bytes>> = self
+            .client
+            .request("childstate_getStorageEntries", params)
+            .await?;
+        Ok(data.into_iter().map(|b| b.map(|b| b.0)).collect())
+    }
+
+    /// Returns the keys with prefix in some child trie, with pagination support.
+    /// Up to `count` keys will be returned.
+    /// If `start_key` is passed, return next keys in storage in lexicographic order.
+    pub async fn childstate_get_keys_paged(
+        &self,
+        child_storage_key: &[u8],
+        prefix: &[u8],
+        count: u32,
+        start_key: Option<&[u8]>,
+        at: Option<T::Hash>,
+    ) -> Result<Vec<StorageKey>, Error> {
+        let start_key = start_key.map(to_hex);
+        let params = rpc_params![
+            to_hex(child_storage_key),
+            to_hex(prefix),
+            count,
+            start_key,
+            at
+        ];
+        let data: Vec<Bytes> = self
+            .client
+            .request("childstate_getKeysPaged", params)
+            .await?;
+        Ok(data.into_iter().map(|b| b.0).collect())
+    }
+
     /// Fetch the genesis hash
     pub async fn genesis_hash(&self) -> Result<T::Hash, Error> {
         let block_zero = 0u32;
@@ -200,6 +267,19 @@ impl<T: RpcConfig> LegacyRpcMethods<T> {
         Ok(stats)
     }
 
+    /// Prove the finality of the given block number. This returns the SCALE encoded
+    /// `FinalityProof`, which contains a GRANDPA justification for a block which is either the
+    /// last block in the authority set of the given block, or the latest finalized block. `None`
+    /// is returned if the given block has not been finalized yet.
+    pub async fn grandpa_prove_finality(
+        &self,
+        block_number: BlockNumber,
+    ) -> Result<Option<Bytes>, Error> {
+        let params = rpc_params![block_number];
+        let proof = self.client.request("grandpa_proveFinality", params).await?;
+        Ok(proof)
+    }
+
     /// Get proof of storage entries at a specific block's state.
     pub async fn state_get_read_proof(
         &self,
@@ -19,6 +19,7 @@ use async_trait::async_trait;
 use futures::{Stream, StreamExt};
 use std::pin::Pin;
 use std::sync::Arc;
+use subxt_rpcs::methods::legacy::RuntimeVersion;
 
 // Expose our various backends.
 pub use archive::ArchiveBackend;
@@ -26,6 +27,9 @@ pub use chain_head::{ChainHeadBackend, ChainHeadBackendBuilder, ChainHeadBackend
 pub use combined::{CombinedBackend, CombinedBackendBuilder, CombinedBackendDriver};
 pub use legacy::{LegacyBackend, LegacyBackendBuilder};
 
+#[cfg(test)]
+pub(crate) use legacy::test_utils as legacy_test_utils;
+
 // Exposed to test but shouldn't be used.
 #[doc(hidden)]
 pub use legacy::subscribe_to_block_headers_filling_in_gaps;
@@ -59,6 +63,67 @@ pub trait Backend<T: Config>: sealed::Sealed + Send + Sync + 'static {
         &self,
         key: Vec<u8>,
         at: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageResponse>, BackendError> {
+        self.storage_fetch_descendant_values_with_options(key, Default::default(), at)
+            .await
+    }
+
+    /// Fetch values underneath the given key from storage, with options to control which key
+    /// we start from and how many values we ask for at once. See [`StorageFetchDescendantsOptions`].
+    async fn storage_fetch_descendant_values_with_options(
+        &self,
+        key: Vec<u8>,
+        options: StorageFetchDescendantsOptions,
+        at: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageResponse>, BackendError>;
+
+    /// Fetch the hashes of the values at the given keys. Keys with no value are skipped.
+    async fn storage_fetch_hashes(
+        &self,
+        keys: Vec<Vec<u8>>,
+        at: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageHashResponse<HashFor<T>>>, BackendError>;
+
+    /// Fetch the Merkle value of the closest descendant of the given key in the trie (which
+    /// may be the node at the key itself). This changes if anything at or under the key changes.
+    /// `None` is returned if there is nothing at or under the key.
+    async fn storage_closest_descendant_merkle_value(
+        &self,
+        key: Vec<u8>,
+        at: HashFor<T>,
+    ) -> Result<Option<Vec<u8>>, BackendError>;
+
+    /// Fetch the value at the given key in each block from `from` to `to` (inclusive) where it
+    /// changes. The first item is always the value in the `from` block, and subsequent items are
+    /// only returned for blocks in which the value differs from that in the previous block.
+    ///
+    /// Depending on the backend, this may need to make one or more requests for every block in
+    /// the range, and so can be slow for large ranges.
+    async fn storage_value_changes(
+        &self,
+        key: Vec<u8>,
+        from: HashFor<T>,
+        to: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageValueChange<HashFor<T>>>, BackendError>;
+
+    /// Fetch values from the default child trie with the given `child_key`. The `child_key`
+    /// is the key of the child trie without the `:child_storage:default:` prefix.
+    async fn storage_fetch_child_values(
+        &self,
+        child_key: Vec<u8>,
+        keys: Vec<Vec<u8>>,
+        at: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageResponse>, BackendError>;
+
+    /// Fetch values underneath the given key from the default child trie with the given
+    /// `child_key`. See [`Backend::storage_fetch_child_values`] and
+    /// [`Backend::storage_fetch_descendant_values_with_options`].
+    async fn storage_fetch_child_descendant_values(
+        &self,
+        child_key: Vec<u8>,
+        key: Vec<u8>,
+        options: StorageFetchDescendantsOptions,
+        at: HashFor<T>,
     ) -> Result<StreamOfResults<StorageResponse>, BackendError>;
 
     /// Fetch the genesis hash
@@ -81,6 +146,14 @@ pub trait Backend<T: Config>: sealed::Sealed + Send + Sync + 'static {
     /// first bytes in the vector will decode to the compact encoded length of the extrinsic)
     async fn block_body(&self, at: HashFor<T>) -> Result<Option<Vec<Vec<u8>>>, BackendError>;
 
+    /// Return the justifications for the given block, or `None` if the block cannot be found.
+    /// Each justification is the ID of the consensus engine that it belongs to, paired with
+    /// the SCALE encoded justification bytes for that engine.
+    async fn block_justifications(
+        &self,
+        at: HashFor<T>,
+    ) -> Result<Option<Vec<BlockJustification>>, BackendError>;
+
     /// Get the most recent finalized block hash.
     /// Note: needed only in blocks client for finalized block stream; can prolly be removed.
     async fn latest_finalized_block_ref(&self) -> Result<BlockRef<HashFor<T>>, BackendError>;
@@ -103,6 +176,15 @@ pub trait Backend<T: Config>: sealed::Sealed + Send + Sync + 'static {
         hasher: T::Hasher,
     ) -> Result<StreamOfResults<(T::Header, BlockRef<HashFor<T>>)>, BackendError>;
 
+    /// A stream of the runtime versions of finalized blocks. The first item is the runtime version
+    /// of the latest finalized block, and after that, an item is handed back each time that a
+    /// finalized block uses a different runtime version to the previous one. The same version may be
+    /// handed back more than once, for instance if the backend has to resubscribe to the chain.
+    async fn stream_finalized_runtime_versions(
+        &self,
+        hasher: T::Hasher,
+    ) -> Result<StreamOfResults<RuntimeVersionChange<HashFor<T>>>, BackendError>;
+
     /// Submit a transaction. This will return a stream of events about it.
     async fn submit_transaction(
         &self,
@@ -116,8 +198,18 @@ pub trait Backend<T: Config>: sealed::Sealed + Send + Sync + 'static {
         call_parameters: Option<&[u8]>,
         at: HashFor<T>,
     ) -> Result<Vec<u8>, BackendError>;
+
+    /// The maximum number of [`Backend::call`]s that should be in progress at once, or `None`
+    /// if the backend has no particular limit.
+    fn max_concurrent_calls(&self) -> Option<usize> {
+        None
+    }
 }
 
+/// A justification for a block; the ID of the consensus engine that it belongs to
+/// (for example `*b"FRNK"` for GRANDPA), and the SCALE encoded justification itself.
+pub type BlockJustification = ([u8; 4], Vec<u8>);
+
 /// Utility methods derived from those provided on [`Backend`]. These are not made
 /// public and only exist to help us in Subxt; Users should rely on the high level
 /// Subxt interface over the backend.
@@ -300,6 +392,44 @@ pub enum TransactionStatus<Hash> {
     },
 }
 
+/// Options for [`Backend::storage_fetch_descendant_values_with_options`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StorageFetchDescendantsOptions {
+    /// If set, only values whose keys come after this key will be returned. This allows
+    /// iteration to be resumed from the last key seen.
+    pub start_after: Option<Vec<u8>>,
+    /// If set, the number of values to ask for in each request. This is only used by
+    /// backends which fetch values in pages, and ignored otherwise.
+    pub page_size: Option<u32>,
+}
+
+/// A response from [`Backend::storage_fetch_hashes`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct StorageHashResponse<Hash> {
+    /// The key.
+    pub key: Vec<u8>,
+    /// The hash of the associated value.
+    pub hash: Hash,
+}
+
+/// A response from [`Backend::storage_value_changes`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct StorageValueChange<Hash> {
+    /// The hash of the block in which the value changed.
+    pub block_hash: Hash,
+    /// The new value, or `None` if there is no longer a value at the key.
+    pub value: Option<Vec<u8>>,
+}
+
+/// A response from [`Backend::stream_finalized_runtime_versions`].
+#[derive(Clone, Debug)]
+pub struct RuntimeVersionChange<Hash> {
+    /// The first finalized block to use this runtime version.
+    pub block_ref: BlockRef<Hash>,
+    /// The runtime version.
+    pub version: RuntimeVersion,
+}
+
 /// A response from calls like [`Backend::storage_fetch_values`] or
 /// [`Backend::storage_fetch_descendant_values`].
 #[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Debug)]
@@ -13,16 +13,21 @@
 mod storage_stream;
 
 use crate::backend::{
-    Backend, BlockRef, StorageResponse, StreamOf, StreamOfResults, TransactionStatus, utils::retry,
+    Backend, BackendExt, BlockJustification, BlockRef, RuntimeVersionChange,
+    StorageFetchDescendantsOptions, StorageHashResponse, StorageResponse, StorageValueChange,
+    StreamOf, StreamOfResults, TransactionStatus, utils::retry,
 };
-use crate::config::{Config, HashFor, RpcConfigFor};
+use crate::config::{Config, HashFor, Header, RpcConfigFor};
 use crate::error::BackendError;
 use async_trait::async_trait;
 use futures::StreamExt;
 use storage_stream::ArchiveStorageStream;
 use subxt_rpcs::RpcClient;
 use subxt_rpcs::methods::ChainHeadRpcMethods;
-use subxt_rpcs::methods::chain_head::{ArchiveCallResult, ArchiveStorageQuery, StorageQueryType};
+use subxt_rpcs::methods::chain_head::{
+    ArchiveCallResult, ArchiveStorageDiffEvent, ArchiveStorageDiffQuery,
+    ArchiveStorageDiffReturnType, ArchiveStorageDiffType, ArchiveStorageQuery, StorageQueryType,
+};
 
 /// The archive backend.
 #[derive(Debug, Clone)]
@@ -38,15 +43,14 @@ impl<T: Config> ArchiveBackend<T> {
 
         ArchiveBackend { methods }
     }
-}
 
-#[async_trait]
-impl<T: Config> Backend<T> for ArchiveBackend<T> {
-    async fn storage_fetch_values(
+    // Fetch the values at the given keys, optionally from some default child trie.
+    fn fetch_values(
         &self,
         keys: Vec<Vec<u8>>,
+        child_key: Option<Vec<u8>>,
         at: HashFor<T>,
-    ) -> Result<StreamOfResults<StorageResponse>, BackendError> {
+    ) -> StreamOfResults<StorageResponse> {
         let queries = keys
             .into_iter()
             .map(|key| ArchiveStorageQuery {
@@ -56,7 +60,7 @@ impl<T: Config> Backend<T> for ArchiveBackend<T> {
             })
             .collect();
 
-        let stream = ArchiveStorageStream::new(at, self.methods.clone(), queries)
+        let stream = ArchiveStorageStream::new(at, self.methods.clone(), queries, child_key)
             .map(|item| match item {
                 Err(e) => Some(Err(e)),
                 Ok(item) => item.value.map(|val| {
@@ -68,7 +72,114 @@ impl<T: Config> Backend<T> for ArchiveBackend<T> {
             })
             .filter_map(async |item| item);
 
-        Ok(StreamOf(Box::pin(stream)))
+        StreamOf(Box::pin(stream))
+    }
+
+    // Fetch the values underneath the given key, optionally from some default child trie.
+    fn fetch_descendant_values(
+        &self,
+        key: Vec<u8>,
+        options: StorageFetchDescendantsOptions,
+        child_key: Option<Vec<u8>>,
+        at: HashFor<T>,
+    ) -> StreamOfResults<StorageResponse> {
+        // The archive methods decide on their own page size, so we ignore that option. The
+        // storage stream ignores any values at or before the pagination start key.
+        let queries = std::iter::once(ArchiveStorageQuery {
+            key,
+            query_type: StorageQueryType::DescendantsValues,
+            pagination_start_key: options.start_after,
+        })
+        .collect();
+
+        let stream = ArchiveStorageStream::new(at, self.methods.clone(), queries, child_key)
+            .map(|item| match item {
+                Err(e) => Some(Err(e)),
+                Ok(item) => item.value.map(|val| {
+                    Ok(StorageResponse {
+                        key: item.key.0,
+                        value: val.0,
+                    })
+                }),
+            })
+            .filter_map(async |item| item);
+
+        StreamOf(Box::pin(stream))
+    }
+
+    // Fetch the number of the block with the given hash.
+    async fn block_number(&self, at: HashFor<T>) -> Result<u64, BackendError> {
+        let header = self.block_header(at).await?.ok_or_else(|| {
+            BackendError::other(format!("Cannot find the block header for block {at:?}"))
+        })?;
+        Ok(header.number())
+    }
+
+    // Compare the value at the given key in some block with that in a previous block, returning
+    // the new value if it differs.
+    async fn fetch_value_diff(
+        &self,
+        key: &[u8],
+        at: HashFor<T>,
+        previous: HashFor<T>,
+    ) -> Result<Option<StorageValueChange<HashFor<T>>>, BackendError> {
+        let mut sub = retry(|| async {
+            let query = std::iter::once(ArchiveStorageDiffQuery {
+                key,
+                return_type: ArchiveStorageDiffReturnType::Value,
+                child_trie_key: None,
+            });
+            let sub = self
+                .methods
+                .archive_v1_storage_diff(at, query, Some(previous))
+                .await?;
+            Ok(sub)
+        })
+        .await?;
+
+        let mut change = None;
+        while let Some(event) = sub.next().await {
+            match event? {
+                // The key is treated as a prefix, so ignore any descendant keys.
+                ArchiveStorageDiffEvent::Item(item) if item.key.0 == key => {
+                    let value = match item.diff_type {
+                        ArchiveStorageDiffType::Deleted => None,
+                        ArchiveStorageDiffType::Added | ArchiveStorageDiffType::Modified => {
+                            Some(item.value.map(|v| v.0).ok_or_else(|| {
+                                BackendError::other(
+                                    "archive_v1_storageDiff did not return the changed value",
+                                )
+                            })?)
+                        }
+                    };
+                    change = Some(StorageValueChange {
+                        block_hash: at,
+                        value,
+                    });
+                }
+                ArchiveStorageDiffEvent::Item(_) => {}
+                ArchiveStorageDiffEvent::Error(e) => {
+                    return Err(BackendError::other(format!(
+                        "archive_v1_storageDiff failed: {}",
+                        e.error
+                    )));
+                }
+                ArchiveStorageDiffEvent::Done => break,
+            }
+        }
+
+        Ok(change)
+    }
+}
+
+#[async_trait]
+impl<T: Config> Backend<T> for ArchiveBackend<T> {
+    async fn storage_fetch_values(
+        &self,
+        keys: Vec<Vec<u8>>,
+        at: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageResponse>, BackendError> {
+        Ok(self.fetch_values(keys, None, at))
     }
 
     async fn storage_fetch_descendant_keys(
@@ -84,34 +195,46 @@ impl<T: Config> Backend<T> for ArchiveBackend<T> {
         })
         .collect();
 
-        let stream =
-            ArchiveStorageStream::new(at, self.methods.clone(), queries).map(|item| match item {
+        let stream = ArchiveStorageStream::new(at, self.methods.clone(), queries, None).map(
+            |item| match item {
                 Err(e) => Err(e),
                 Ok(item) => Ok(item.key.0),
-            });
+            },
+        );
 
         Ok(StreamOf(Box::pin(stream)))
     }
 
-    async fn storage_fetch_descendant_values(
+    async fn storage_fetch_descendant_values_with_options(
         &self,
         key: Vec<u8>,
+        options: StorageFetchDescendantsOptions,
         at: HashFor<T>,
     ) -> Result<StreamOfResults<StorageResponse>, BackendError> {
-        let queries = std::iter::once(ArchiveStorageQuery {
-            key,
-            query_type: StorageQueryType::DescendantsValues,
-            pagination_start_key: None,
-        })
-        .collect();
+        Ok(self.fetch_descendant_values(key, options, None, at))
+    }
+
+    async fn storage_fetch_hashes(
+        &self,
+        keys: Vec<Vec<u8>>,
+        at: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageHashResponse<HashFor<T>>>, BackendError> {
+        let queries = keys
+            .into_iter()
+            .map(|key| ArchiveStorageQuery {
+                key,
+                query_type: StorageQueryType::Hash,
+                pagination_start_key: None,
+            })
+            .collect();
 
-        let stream = ArchiveStorageStream::new(at, self.methods.clone(), queries)
+        let stream = ArchiveStorageStream::new(at, self.methods.clone(), queries, None)
             .map(|item| match item {
                 Err(e) => Some(Err(e)),
-                Ok(item) => item.value.map(|val| {
-                    Ok(StorageResponse {
+                Ok(item) => item.hash.map(|hash| {
+                    Ok(StorageHashResponse {
                         key: item.key.0,
-                        value: val.0,
+                        hash,
                     })
                 }),
             })
@@ -120,6 +243,106 @@ impl<T: Config> Backend<T> for ArchiveBackend<T> {
         Ok(StreamOf(Box::pin(stream)))
     }
 
+    async fn storage_closest_descendant_merkle_value(
+        &self,
+        key: Vec<u8>,
+        at: HashFor<T>,
+    ) -> Result<Option<Vec<u8>>, BackendError> {
+        let queries = std::iter::once(ArchiveStorageQuery {
+            key,
+            query_type: StorageQueryType::ClosestDescendantMerkleValue,
+            pagination_start_key: None,
+        })
+        .collect();
+
+        let mut stream = ArchiveStorageStream::new(at, self.methods.clone(), queries, None);
+        while let Some(item) = stream.next().await {
+            if let Some(merkle_value) = item?.closest_descendant_merkle_value {
+                return Ok(Some(merkle_value.0));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn storage_value_changes(
+        &self,
+        key: Vec<u8>,
+        from: HashFor<T>,
+        to: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageValueChange<HashFor<T>>>, BackendError> {
+        let from_number = self.block_number(from).await?;
+        let to_number = self.block_number(to).await?;
+        let initial_value = self.storage_fetch_value(key.clone(), from).await?;
+        let this = self.clone();
+
+        // Diff the storage at the end of some span of blocks with that at the start of it, and
+        // only split the span in two (finding the hash of the block in the middle) if the value
+        // changed, until we're left with single blocks in which the value changed. This makes a
+        // handful of requests per change rather than some per block, at the cost of missing any
+        // changes which are undone again within a span that we don't split.
+        let initial = StorageValueChange {
+            block_hash: from,
+            value: initial_value,
+        };
+        let spans = if from_number < to_number {
+            vec![((from_number, from), (to_number, to))]
+        } else {
+            Vec::new()
+        };
+        let changes = futures::stream::try_unfold(spans, move |mut spans| {
+            let this = this.clone();
+            let key = key.clone();
+            async move {
+                // Spans are pushed right half first, so we pop them from left to right.
+                while let Some(((start_number, start), (end_number, end))) = spans.pop() {
+                    let Some(change) = this.fetch_value_diff(&key, end, start).await? else {
+                        continue;
+                    };
+                    if end_number == start_number + 1 {
+                        return Ok(Some((change, spans)));
+                    }
+
+                    let middle_number = start_number + (end_number - start_number) / 2;
+                    let middle = this
+                        .block_number_to_hash(middle_number)
+                        .await?
+                        .ok_or_else(|| {
+                            BackendError::other(format!(
+                                "Cannot find a unique block hash for block {middle_number}"
+                            ))
+                        })?
+                        .hash();
+                    spans.push(((middle_number, middle), (end_number, end)));
+                    spans.push(((start_number, start), (middle_number, middle)));
+                }
+                Ok(None)
+            }
+        });
+
+        let stream = futures::stream::once(async move { Ok(initial) }).chain(changes);
+
+        Ok(StreamOf(Box::pin(stream)))
+    }
+
+    async fn storage_fetch_child_values(
+        &self,
+        child_key: Vec<u8>,
+        keys: Vec<Vec<u8>>,
+        at: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageResponse>, BackendError> {
+        Ok(self.fetch_values(keys, Some(child_key), at))
+    }
+
+    async fn storage_fetch_child_descendant_values(
+        &self,
+        child_key: Vec<u8>,
+        key: Vec<u8>,
+        options: StorageFetchDescendantsOptions,
+        at: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageResponse>, BackendError> {
+        Ok(self.fetch_descendant_values(key, options, Some(child_key), at))
+    }
+
     async fn genesis_hash(&self) -> Result<HashFor<T>, BackendError> {
         retry(|| async {
             let hash = self.methods.archive_v1_genesis_hash().await?;
@@ -166,6 +389,15 @@ impl<T: Config> Backend<T> for ArchiveBackend<T> {
         .await
     }
 
+    async fn block_justifications(
+        &self,
+        _at: HashFor<T>,
+    ) -> Result<Option<Vec<BlockJustification>>, BackendError> {
+        Err(BackendError::other(
+            "The archive backend cannot obtain block justifications",
+        ))
+    }
+
     async fn latest_finalized_block_ref(&self) -> Result<BlockRef<HashFor<T>>, BackendError> {
         retry(|| async {
             let height = self.methods.archive_v1_finalized_height().await?;
@@ -207,6 +439,15 @@ impl<T: Config> Backend<T> for ArchiveBackend<T> {
         ))
     }
 
+    async fn stream_finalized_runtime_versions(
+        &self,
+        _hasher: T::Hasher,
+    ) -> Result<StreamOfResults<RuntimeVersionChange<HashFor<T>>>, BackendError> {
+        Err(BackendError::other(
+            "The archive backend cannot stream runtime versions",
+        ))
+    }
+
     async fn submit_transaction(
         &self,
         extrinsic: &[u8],
@@ -233,3 +474,206 @@ impl<T: Config> Backend<T> for ArchiveBackend<T> {
 }
 
 impl<T: Config> crate::backend::sealed::Sealed for ArchiveBackend<T> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::SubstrateConfig;
+    use crate::config::substrate::H256;
+    use futures::TryStreamExt;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use subxt_rpcs::client::MockRpcClient;
+    use subxt_rpcs::client::mock_rpc_client::Json;
+
+    // Respond to `archive_v1_storage` hash and Merkle value queries. Only keys
+    // beginning with `0x01` have any values beneath them.
+    fn backend() -> ArchiveBackend<SubstrateConfig> {
+        let mock_client = MockRpcClient::builder()
+            .subscription_handler("archive_v1_storage", |params, _unsub| async move {
+                let (_at, queries, _child_key): (
+                    H256,
+                    Vec<ArchiveStorageQuery<String>>,
+                    Option<String>,
+                ) = serde_json::from_str(params.unwrap().get()).unwrap();
+
+                let mut events: Vec<_> = queries
+                    .into_iter()
+                    .filter(|query| query.key.starts_with("0x01"))
+                    .map(|query| match query.query_type {
+                        StorageQueryType::Hash => serde_json::json!({
+                            "event": "storage",
+                            "key": query.key,
+                            "hash": H256::repeat_byte(1),
+                        }),
+                        StorageQueryType::ClosestDescendantMerkleValue => serde_json::json!({
+                            "event": "storage",
+                            "key": query.key,
+                            "closestDescendantMerkleValue": "0xaabb",
+                        }),
+                        other => panic!("unexpected query type {other:?}"),
+                    })
+                    .collect();
+                events.push(serde_json::json!({ "event": "storageDone" }));
+
+                events.into_iter().map(Json).collect::<Vec<_>>()
+            })
+            .build();
+
+        ArchiveBackend::new(RpcClient::new(mock_client))
+    }
+
+    #[tokio::test]
+    async fn storage_fetch_hashes_skips_missing_values() {
+        let hashes: Vec<_> = backend()
+            .storage_fetch_hashes(vec![vec![1, 1], vec![2], vec![1, 2]], H256::zero())
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            hashes,
+            vec![
+                StorageHashResponse {
+                    key: vec![1, 1],
+                    hash: H256::repeat_byte(1)
+                },
+                StorageHashResponse {
+                    key: vec![1, 2],
+                    hash: H256::repeat_byte(1)
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn storage_closest_descendant_merkle_value_is_returned() {
+        let backend = backend();
+
+        let merkle_value = backend
+            .storage_closest_descendant_merkle_value(vec![1], H256::zero())
+            .await
+            .unwrap();
+        assert_eq!(merkle_value, Some(vec![0xaa, 0xbb]));
+
+        let merkle_value = backend
+            .storage_closest_descendant_merkle_value(vec![2], H256::zero())
+            .await
+            .unwrap();
+        assert_eq!(merkle_value, None);
+    }
+
+    #[tokio::test]
+    async fn storage_value_changes_bisects_changed_spans() {
+        // The value at key 0x01 in block `n` (whose hash is `[n; 32]`) for blocks 0 to 32. It is
+        // deleted in block 2 and set again in block 31.
+        fn value_at(n: u8) -> Option<&'static str> {
+            match n {
+                0..2 => Some("0x0a"),
+                2..31 => None,
+                _ => Some("0x0b"),
+            }
+        }
+
+        let diff_calls = Arc::new(AtomicUsize::new(0));
+        let mock_client = MockRpcClient::builder()
+            .method_handler("archive_v1_header", |params| async move {
+                let (hash,): (H256,) = serde_json::from_str(params.unwrap().get()).unwrap();
+                let header = crate::config::substrate::SubstrateHeader {
+                    parent_hash: H256::repeat_byte(hash[0].wrapping_sub(1)),
+                    number: hash[0].into(),
+                    state_root: H256::zero(),
+                    extrinsics_root: H256::zero(),
+                    digest: Default::default(),
+                };
+                Json(format!("0x{}", hex::encode(codec::Encode::encode(&header))))
+            })
+            .method_handler("archive_v1_hashByHeight", |params| async move {
+                let (number,): (u8,) = serde_json::from_str(params.unwrap().get()).unwrap();
+                Json(vec![H256::repeat_byte(number)])
+            })
+            .subscription_handler("archive_v1_storage", |params, _unsub| async move {
+                let (at, _queries, _child_key): (H256, serde_json::Value, Option<String>) =
+                    serde_json::from_str(params.unwrap().get()).unwrap();
+                let mut events = Vec::new();
+                if let Some(value) = value_at(at[0]) {
+                    events.push(serde_json::json!({
+                        "event": "storage",
+                        "key": "0x01",
+                        "value": value,
+                    }));
+                }
+                events.push(serde_json::json!({ "event": "storageDone" }));
+                events.into_iter().map(Json).collect::<Vec<_>>()
+            })
+            .subscription_handler("archive_v1_storageDiff", {
+                let diff_calls = diff_calls.clone();
+                move |params, _unsub| {
+                    diff_calls.fetch_add(1, Ordering::SeqCst);
+                    async move {
+                        let (at, queries, previous): (
+                            H256,
+                            Vec<ArchiveStorageDiffQuery<String>>,
+                            H256,
+                        ) = serde_json::from_str(params.unwrap().get()).unwrap();
+                        assert_eq!(queries[0].key, "0x01");
+                        assert!(previous[0] < at[0]);
+
+                        // A descendant key which has changed is also handed back.
+                        let mut events = vec![serde_json::json!({
+                            "event": "storageDiff",
+                            "key": "0x0102",
+                            "value": "0x00",
+                            "type": "added",
+                        })];
+                        let diff = match (value_at(previous[0]), value_at(at[0])) {
+                            (old, new) if old == new => None,
+                            (None, Some(new)) => Some(("added", Some(new))),
+                            (Some(_), Some(new)) => Some(("modified", Some(new))),
+                            (Some(_), None) => Some(("deleted", None)),
+                            (None, None) => unreachable!(),
+                        };
+                        if let Some((diff_type, value)) = diff {
+                            events.push(serde_json::json!({
+                                "event": "storageDiff",
+                                "key": "0x01",
+                                "value": value,
+                                "type": diff_type,
+                            }));
+                        }
+                        events.push(serde_json::json!({ "event": "storageDiffDone" }));
+                        events.into_iter().map(Json).collect::<Vec<_>>()
+                    }
+                }
+            })
+            .build();
+
+        let backend: ArchiveBackend<SubstrateConfig> =
+            ArchiveBackend::new(RpcClient::new(mock_client));
+        let changes: Vec<_> = backend
+            .storage_value_changes(vec![1], H256::repeat_byte(0), H256::repeat_byte(32))
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+
+        let change = |n, value| StorageValueChange {
+            block_hash: H256::repeat_byte(n),
+            value,
+        };
+        assert_eq!(
+            changes,
+            vec![
+                change(0, Some(vec![0x0a])),
+                change(2, None),
+                change(31, Some(vec![0x0b])),
+            ]
+        );
+
+        // Only spans containing a change were split, rather than diffing every block.
+        assert_eq!(diff_calls.load(Ordering::SeqCst), 19);
+    }
+}
@@ -15,6 +15,8 @@ pub struct ArchiveStorageStream<T: Config> {
     at: HashFor<T>,
     methods: ChainHeadRpcMethods<RpcConfigFor<T>>,
     query_queue: VecDeque<ArchiveStorageQuery<Vec<u8>>>,
+    // If set, the queries are made against this default child trie.
+    child_key: Option<Vec<u8>>,
     state: Option<StreamState<T>>,
 }
 
@@ -41,11 +43,13 @@ impl<T: Config> ArchiveStorageStream<T> {
         at: HashFor<T>,
         methods: ChainHeadRpcMethods<RpcConfigFor<T>>,
         query_queue: VecDeque<ArchiveStorageQuery<Vec<u8>>>,
+        child_key: Option<Vec<u8>>,
     ) -> Self {
         Self {
             at,
             methods,
             query_queue,
+            child_key,
             state: None,
         }
     }
@@ -69,6 +73,7 @@ impl<T: Config> Stream for ArchiveStorageStream<T> {
 
                     let at = this.at;
                     let methods = this.methods.clone();
+                    let child_key = this.child_key.clone();
                     let current_query = query.clone();
                     let sub_fut = async move {
                         let query = std::iter::once(ArchiveStorageQuery {
@@ -77,7 +82,9 @@ impl<T: Config> Stream for ArchiveStorageStream<T> {
                             pagination_start_key: query.pagination_start_key.as_deref(),
                         });
 
-                        methods.archive_v1_storage(at, query, None).await
+                        methods
+                            .archive_v1_storage(at, query, child_key.as_deref())
+                            .await
                     };
 
                     this.state = Some(StreamState::GetSubscription {
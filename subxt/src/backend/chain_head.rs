@@ -15,12 +15,15 @@ mod storage_items;
 
 use self::follow_stream_driver::FollowStreamFinalizedHeads;
 use crate::backend::{
-    Backend, BlockRef, BlockRefT, StorageResponse, StreamOf, StreamOfResults, TransactionStatus,
-    utils::retry,
+    Backend, BlockJustification, BlockRef, BlockRefT, RuntimeVersionChange,
+    StorageFetchDescendantsOptions, StorageHashResponse, StorageResponse, StorageValueChange,
+    StreamOf, StreamOfResults, TransactionStatus, utils::retry,
 };
 use crate::config::{Config, Hash, HashFor, RpcConfigFor};
 use crate::error::{BackendError, RpcError};
+use crate::storage::decode_runtime_version;
 use async_trait::async_trait;
+use codec::Decode;
 use follow_stream_driver::{FollowStreamDriver, FollowStreamDriverHandle};
 use futures::future::Either;
 use futures::{Stream, StreamExt};
@@ -30,9 +33,12 @@ use storage_items::StorageItems;
 use subxt_rpcs::RpcClient;
 use subxt_rpcs::methods::ChainHeadRpcMethods;
 use subxt_rpcs::methods::chain_head::{
-    FollowEvent, MethodResponse, StorageQuery, StorageQueryType, StorageResultType,
+    FollowEvent, MethodResponse, RuntimeEvent, StorageQuery, StorageQueryType, StorageResultType,
 };
 
+/// The number of operations which the chainHead spec guarantees can be in progress at once.
+const CHAIN_HEAD_MAX_OPERATIONS: usize = 16;
+
 /// Configure and build an [`ChainHeadBackend`].
 pub struct ChainHeadBackendBuilder<T> {
     max_block_life: usize,
@@ -252,22 +258,12 @@ impl<T: Config> ChainHeadBackend<T> {
 
         Ok(StreamOf(Box::pin(headers)))
     }
-}
-
-impl<H: Hash + 'static> BlockRefT for follow_stream_unpin::BlockRef<H> {}
-impl<H: Hash + 'static> From<follow_stream_unpin::BlockRef<H>> for BlockRef<H> {
-    fn from(b: follow_stream_unpin::BlockRef<H>) -> Self {
-        BlockRef::new(b.hash(), b)
-    }
-}
 
-impl<T: Config> super::sealed::Sealed for ChainHeadBackend<T> {}
-
-#[async_trait]
-impl<T: Config> Backend<T> for ChainHeadBackend<T> {
-    async fn storage_fetch_values(
+    // Fetch the values at the given keys, optionally from some default child trie.
+    async fn fetch_values(
         &self,
         keys: Vec<Vec<u8>>,
+        child_key: Option<&[u8]>,
         at: HashFor<T>,
     ) -> Result<StreamOfResults<StorageResponse>, BackendError> {
         retry(|| async {
@@ -276,9 +272,14 @@ impl<T: Config> Backend<T> for ChainHeadBackend<T> {
                 query_type: StorageQueryType::Value,
             });
 
-            let storage_items =
-                StorageItems::from_methods(queries, at, &self.follow_handle, self.methods.clone())
-                    .await?;
+            let storage_items = StorageItems::from_methods(
+                queries,
+                child_key,
+                at,
+                &self.follow_handle,
+                self.methods.clone(),
+            )
+            .await?;
 
             let stream = storage_items.filter_map(async |val| {
                 let val = match val {
@@ -300,6 +301,68 @@ impl<T: Config> Backend<T> for ChainHeadBackend<T> {
         .await
     }
 
+    // Fetch the values underneath the given key, optionally from some default child trie.
+    async fn fetch_descendant_values(
+        &self,
+        key: Vec<u8>,
+        child_key: Option<&[u8]>,
+        at: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageResponse>, BackendError> {
+        retry(|| async {
+            let query = StorageQuery {
+                key: &*key,
+                query_type: StorageQueryType::DescendantsValues,
+            };
+
+            let storage_items = StorageItems::from_methods(
+                std::iter::once(query),
+                child_key,
+                at,
+                &self.follow_handle,
+                self.methods.clone(),
+            )
+            .await?;
+
+            let storage_result_stream = storage_items.filter_map(async |val| {
+                let val = match val {
+                    Ok(val) => val,
+                    Err(e) => return Some(Err(e)),
+                };
+
+                let StorageResultType::Value(result) = val.result else {
+                    return None;
+                };
+                Some(Ok(StorageResponse {
+                    key: val.key.0,
+                    value: result.0,
+                }))
+            });
+
+            Ok(StreamOf(Box::pin(storage_result_stream)))
+        })
+        .await
+    }
+}
+
+impl<H: Hash + 'static> BlockRefT for follow_stream_unpin::BlockRef<H> {}
+impl<H: Hash + 'static> From<follow_stream_unpin::BlockRef<H>> for BlockRef<H> {
+    fn from(b: follow_stream_unpin::BlockRef<H>) -> Self {
+        BlockRef::new(b.hash(), b)
+    }
+}
+
+impl<T: Config> super::sealed::Sealed for ChainHeadBackend<T> {}
+
+#[async_trait]
+impl<T: Config> Backend<T> for ChainHeadBackend<T> {
+    async fn storage_fetch_values(
+        &self,
+        keys: Vec<Vec<u8>>,
+        at: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageResponse>, BackendError> {
+        self.fetch_values(keys, None, at).await
+    }
+
     async fn storage_fetch_descendant_keys(
         &self,
         key: Vec<u8>,
@@ -314,6 +377,7 @@ impl<T: Config> Backend<T> for ChainHeadBackend<T> {
 
             let storage_items = StorageItems::from_methods(
                 std::iter::once(query),
+                None,
                 at,
                 &self.follow_handle,
                 self.methods.clone(),
@@ -326,45 +390,136 @@ impl<T: Config> Backend<T> for ChainHeadBackend<T> {
         .await
     }
 
-    async fn storage_fetch_descendant_values(
+    async fn storage_fetch_descendant_values_with_options(
         &self,
         key: Vec<u8>,
+        options: StorageFetchDescendantsOptions,
         at: HashFor<T>,
     ) -> Result<StreamOfResults<StorageResponse>, BackendError> {
+        // chainHead_v1_storage decides on its own page size, so that option is ignored, but
+        // it has no way to begin from a given key, and values aren't guaranteed to be ordered.
+        if options.start_after.is_some() {
+            return Err(BackendError::other(
+                "The chainHead backend does not support resuming storage iteration from a given key",
+            ));
+        }
+        self.fetch_descendant_values(key, None, at).await
+    }
+
+    async fn storage_fetch_hashes(
+        &self,
+        keys: Vec<Vec<u8>>,
+        at: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageHashResponse<HashFor<T>>>, BackendError> {
         retry(|| async {
-            let query = StorageQuery {
-                key: &*key,
-                query_type: StorageQueryType::DescendantsValues,
-            };
+            let queries = keys.iter().map(|key| StorageQuery {
+                key: &**key,
+                query_type: StorageQueryType::Hash,
+            });
 
             let storage_items = StorageItems::from_methods(
-                std::iter::once(query),
+                queries,
+                None,
                 at,
                 &self.follow_handle,
                 self.methods.clone(),
             )
             .await?;
 
-            let storage_result_stream = storage_items.filter_map(async |val| {
+            let stream = storage_items.filter_map(async |val| {
                 let val = match val {
                     Ok(val) => val,
                     Err(e) => return Some(Err(e)),
                 };
 
-                let StorageResultType::Value(result) = val.result else {
+                let StorageResultType::Hash(hash) = val.result else {
                     return None;
                 };
-                Some(Ok(StorageResponse {
+                let hash = match HashFor::<T>::decode(&mut &*hash.0) {
+                    Ok(hash) => hash,
+                    Err(e) => {
+                        return Some(Err(BackendError::other(format!(
+                            "Cannot decode the storage value hash: {e}"
+                        ))));
+                    }
+                };
+                Some(Ok(StorageHashResponse {
                     key: val.key.0,
-                    value: result.0,
+                    hash,
                 }))
             });
 
-            Ok(StreamOf(Box::pin(storage_result_stream)))
+            Ok(StreamOf(Box::pin(stream)))
+        })
+        .await
+    }
+
+    async fn storage_closest_descendant_merkle_value(
+        &self,
+        key: Vec<u8>,
+        at: HashFor<T>,
+    ) -> Result<Option<Vec<u8>>, BackendError> {
+        retry(|| async {
+            let query = StorageQuery {
+                key: &*key,
+                query_type: StorageQueryType::ClosestDescendantMerkleValue,
+            };
+
+            let mut storage_items = StorageItems::from_methods(
+                std::iter::once(query),
+                None,
+                at,
+                &self.follow_handle,
+                self.methods.clone(),
+            )
+            .await?;
+
+            while let Some(val) = storage_items.next().await {
+                if let StorageResultType::ClosestDescendantMerkleValue(merkle_value) = val?.result {
+                    return Ok(Some(merkle_value.0));
+                }
+            }
+            Ok(None)
         })
         .await
     }
 
+    async fn storage_value_changes(
+        &self,
+        _key: Vec<u8>,
+        _from: HashFor<T>,
+        _to: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageValueChange<HashFor<T>>>, BackendError> {
+        Err(BackendError::other(
+            "The ChainHead V1 RPCs do not support fetching storage changes over a range of blocks.",
+        ))
+    }
+
+    async fn storage_fetch_child_values(
+        &self,
+        child_key: Vec<u8>,
+        keys: Vec<Vec<u8>>,
+        at: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageResponse>, BackendError> {
+        self.fetch_values(keys, Some(&child_key), at).await
+    }
+
+    async fn storage_fetch_child_descendant_values(
+        &self,
+        child_key: Vec<u8>,
+        key: Vec<u8>,
+        options: StorageFetchDescendantsOptions,
+        at: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageResponse>, BackendError> {
+        if options.start_after.is_some() {
+            return Err(BackendError::other(
+                "The chainHead backend does not support resuming storage iteration from a given key",
+            ));
+        }
+        self.fetch_descendant_values(key, Some(&child_key), at)
+            .await
+    }
+
     async fn genesis_hash(&self) -> Result<HashFor<T>, BackendError> {
         retry(|| async {
             let genesis_hash = self.methods.chainspec_v1_genesis_hash().await?;
@@ -420,6 +575,15 @@ impl<T: Config> Backend<T> for ChainHeadBackend<T> {
         .await
     }
 
+    async fn block_justifications(
+        &self,
+        _at: HashFor<T>,
+    ) -> Result<Option<Vec<BlockJustification>>, BackendError> {
+        Err(BackendError::other(
+            "The ChainHead V1 RPCs do not support obtaining block justifications.",
+        ))
+    }
+
     async fn latest_finalized_block_ref(&self) -> Result<BlockRef<HashFor<T>>, BackendError> {
         let next_ref: Option<BlockRef<HashFor<T>>> = self
             .follow_handle
@@ -486,6 +650,82 @@ impl<T: Config> Backend<T> for ChainHeadBackend<T> {
         .await
     }
 
+    async fn stream_finalized_runtime_versions(
+        &self,
+        _hasher: T::Hasher,
+    ) -> Result<StreamOfResults<RuntimeVersionChange<HashFor<T>>>, BackendError> {
+        // New runtimes are reported in `NewBlock` events, so we hold onto them until
+        // the corresponding block is finalized or pruned.
+        let mut new_runtimes = HashMap::new();
+
+        let versions = self.follow_handle.subscribe().events().flat_map(move |ev| {
+            let changes = match ev {
+                FollowEvent::Initialized(init) => {
+                    new_runtimes.clear();
+                    match (
+                        init.finalized_block_hashes.last(),
+                        init.finalized_block_runtime,
+                    ) {
+                        (Some(block_ref), Some(runtime)) => vec![(block_ref.clone(), runtime)],
+                        _ => vec![],
+                    }
+                }
+                FollowEvent::NewBlock(ev) => {
+                    if let Some(runtime) = ev.new_runtime {
+                        new_runtimes.insert(ev.block_hash.hash(), runtime);
+                    }
+                    vec![]
+                }
+                FollowEvent::Finalized(ev) => {
+                    for block_ref in &ev.pruned_block_hashes {
+                        new_runtimes.remove(&block_ref.hash());
+                    }
+                    ev.finalized_block_hashes
+                        .into_iter()
+                        .filter_map(|block_ref| {
+                            let runtime = new_runtimes.remove(&block_ref.hash())?;
+                            Some((block_ref, runtime))
+                        })
+                        .collect()
+                }
+                _ => vec![],
+            };
+
+            futures::stream::iter(changes)
+        });
+
+        // The runtime details handed back in follow events lack some of the fields (like
+        // `authoringVersion`) that `state_getRuntimeVersion` hands back, so we ask each new
+        // runtime for its version to hand back the same thing as the other backends.
+        let backend = self.clone();
+        let versions = versions.then(move |(block_ref, runtime)| {
+            let backend = backend.clone();
+            async move {
+                if let RuntimeEvent::Invalid(ev) = runtime {
+                    return Err(BackendError::other(format!(
+                        "Cannot obtain the runtime at block {}: {}",
+                        block_ref.hash(),
+                        ev.error
+                    )));
+                }
+
+                let version_bytes = backend.call("Core_version", None, block_ref.hash()).await?;
+                let version = decode_runtime_version(&version_bytes).map_err(|e| {
+                    BackendError::other(format!(
+                        "Cannot decode the runtime version at block {}: {e}",
+                        block_ref.hash()
+                    ))
+                })?;
+                Ok(RuntimeVersionChange {
+                    block_ref: block_ref.into(),
+                    version,
+                })
+            }
+        });
+
+        Ok(StreamOf(Box::pin(versions)))
+    }
+
     async fn submit_transaction(
         &self,
         extrinsic: &[u8],
@@ -542,6 +782,12 @@ impl<T: Config> Backend<T> for ChainHeadBackend<T> {
         })
         .await
     }
+
+    fn max_concurrent_calls(&self) -> Option<usize> {
+        // The chainHead spec guarantees that at least this many operations can
+        // be in progress at once per follow subscription; any more may be rejected.
+        Some(CHAIN_HEAD_MAX_OPERATIONS)
+    }
 }
 
 /// A helper to obtain a subscription ID.
@@ -769,3 +1015,129 @@ async fn submit_transaction_tracking_follow_events<T: Config>(
 
     Ok(StreamOf(Box::pin(tx_stream)))
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::SubstrateConfig;
+    use crate::config::substrate::H256;
+    use codec::Encode;
+    use subxt_rpcs::client::MockRpcClient;
+    use subxt_rpcs::client::mock_rpc_client::Json;
+    use tokio::sync::mpsc;
+
+    // The `Core_version` output at block `n` (whose hash is `[n; 32]`).
+    fn core_version_at(n: u8) -> Vec<u8> {
+        (
+            "polkadot".to_string(),
+            "parity-polkadot".to_string(),
+            2u32,
+            100 + n as u32,
+            0u32,
+            vec![([1u8; 8], 3u32)],
+            26u32,
+            1u8,
+        )
+            .encode()
+    }
+
+    fn runtime_at(n: u8) -> serde_json::Value {
+        serde_json::json!({
+            "type": "valid",
+            "spec": {
+                "specName": "polkadot",
+                "implName": "parity-polkadot",
+                "specVersion": 100 + n as u32,
+                "implVersion": 0,
+                "transactionVersion": 26,
+                "apis": [["0x0101010101010101", 3]],
+            },
+        })
+    }
+
+    #[tokio::test]
+    async fn stream_finalized_runtime_versions_reports_full_versions() {
+        let (follow_tx, follow_rx) = mpsc::unbounded_channel();
+        let mock_client = MockRpcClient::builder()
+            .subscription_handler_once("chainHead_v1_follow", move |_params, _unsub| async move {
+                (follow_rx, "sub_id")
+            })
+            .method_handler("chainHead_v1_call", {
+                let follow_tx = follow_tx.clone();
+                move |params| {
+                    let follow_tx = follow_tx.clone();
+                    async move {
+                        let (_sub_id, at, method, _params): (String, H256, String, String) =
+                            serde_json::from_str(params.unwrap().get()).unwrap();
+                        assert_eq!(method, "Core_version");
+
+                        let operation_id = format!("call_{}", at[0]);
+                        follow_tx
+                            .send(Json(serde_json::json!({
+                                "event": "operationCallDone",
+                                "operationId": operation_id,
+                                "output": format!("0x{}", hex::encode(core_version_at(at[0]))),
+                            })))
+                            .unwrap();
+                        Json(serde_json::json!({
+                            "result": "started",
+                            "operationId": operation_id,
+                        }))
+                    }
+                }
+            })
+            .method_handler("chainHead_v1_unpin", |_params| async { Json(()) })
+            .build();
+
+        let (backend, mut driver) =
+            ChainHeadBackend::<SubstrateConfig>::builder().build(RpcClient::new(mock_client));
+        tokio::spawn(async move { while driver.next().await.is_some() {} });
+
+        let mut versions = backend
+            .stream_finalized_runtime_versions(Default::default())
+            .await
+            .unwrap();
+
+        let events = [
+            serde_json::json!({
+                "event": "initialized",
+                "finalizedBlockHashes": [H256::repeat_byte(0)],
+                "finalizedBlockRuntime": runtime_at(0),
+            }),
+            // Block 2 has a new runtime but is pruned, so it is never reported.
+            serde_json::json!({
+                "event": "newBlock",
+                "blockHash": H256::repeat_byte(1),
+                "parentBlockHash": H256::repeat_byte(0),
+                "newRuntime": runtime_at(1),
+            }),
+            serde_json::json!({
+                "event": "newBlock",
+                "blockHash": H256::repeat_byte(2),
+                "parentBlockHash": H256::repeat_byte(0),
+                "newRuntime": runtime_at(2),
+            }),
+            serde_json::json!({
+                "event": "finalized",
+                "finalizedBlockHashes": [H256::repeat_byte(1)],
+                "prunedBlockHashes": [H256::repeat_byte(2)],
+            }),
+        ];
+        for event in events {
+            follow_tx.send(Json(event)).unwrap();
+        }
+
+        for n in [0, 1] {
+            let change = versions.next().await.unwrap().unwrap();
+            assert_eq!(change.block_ref.hash(), H256::repeat_byte(n));
+            assert_eq!(change.version.spec_version, 100 + n as u32);
+            assert_eq!(change.version.transaction_version, 26);
+            assert_eq!(change.version.other["authoringVersion"], 2);
+            assert_eq!(change.version.other["systemVersion"], 1);
+            assert_eq!(
+                change.version.other["apis"],
+                serde_json::json!([["0x0101010101010101", 3]])
+            );
+        }
+    }
+}
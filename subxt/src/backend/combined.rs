@@ -7,7 +7,8 @@
 
 use crate::backend::chain_head::ChainHeadBackendDriver;
 use crate::backend::{
-    Backend, BlockRef, StorageResponse, StreamOfResults, TransactionStatus,
+    Backend, BlockJustification, BlockRef, RuntimeVersionChange, StorageFetchDescendantsOptions,
+    StorageHashResponse, StorageResponse, StreamOfResults, TransactionStatus,
     archive::ArchiveBackend, chain_head::ChainHeadBackend, legacy::LegacyBackend,
 };
 use crate::config::{Config, HashFor};
@@ -292,14 +293,96 @@ impl<T: Config> Backend<T> for CombinedBackend<T> {
         .await
     }
 
-    async fn storage_fetch_descendant_values(
+    async fn storage_fetch_descendant_values_with_options(
         &self,
         key: Vec<u8>,
+        options: StorageFetchDescendantsOptions,
         at: HashFor<T>,
     ) -> Result<StreamOfResults<StorageResponse>, BackendError> {
         try_backends(
             &[self.archive(), self.chainhead(), self.legacy()],
-            async |b: &dyn Backend<T>| b.storage_fetch_descendant_values(key.clone(), at).await,
+            async |b: &dyn Backend<T>| {
+                b.storage_fetch_descendant_values_with_options(key.clone(), options.clone(), at)
+                    .await
+            },
+        )
+        .await
+    }
+
+    async fn storage_fetch_hashes(
+        &self,
+        keys: Vec<Vec<u8>>,
+        at: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageHashResponse<HashFor<T>>>, BackendError> {
+        try_backends(
+            &[self.archive(), self.chainhead(), self.legacy()],
+            async |b: &dyn Backend<T>| b.storage_fetch_hashes(keys.clone(), at).await,
+        )
+        .await
+    }
+
+    async fn storage_closest_descendant_merkle_value(
+        &self,
+        key: Vec<u8>,
+        at: HashFor<T>,
+    ) -> Result<Option<Vec<u8>>, BackendError> {
+        try_backends(
+            &[self.archive(), self.chainhead(), self.legacy()],
+            async |b: &dyn Backend<T>| {
+                b.storage_closest_descendant_merkle_value(key.clone(), at)
+                    .await
+            },
+        )
+        .await
+    }
+
+    async fn storage_value_changes(
+        &self,
+        key: Vec<u8>,
+        from: HashFor<T>,
+        to: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageValueChange<HashFor<T>>>, BackendError> {
+        try_backends(
+            &[self.archive(), self.chainhead(), self.legacy()],
+            async |b: &dyn Backend<T>| b.storage_value_changes(key.clone(), from, to).await,
+        )
+        .await
+    }
+
+    async fn storage_fetch_child_values(
+        &self,
+        child_key: Vec<u8>,
+        keys: Vec<Vec<u8>>,
+        at: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageResponse>, BackendError> {
+        try_backends(
+            &[self.archive(), self.chainhead(), self.legacy()],
+            async |b: &dyn Backend<T>| {
+                b.storage_fetch_child_values(child_key.clone(), keys.clone(), at)
+                    .await
+            },
+        )
+        .await
+    }
+
+    async fn storage_fetch_child_descendant_values(
+        &self,
+        child_key: Vec<u8>,
+        key: Vec<u8>,
+        options: StorageFetchDescendantsOptions,
+        at: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageResponse>, BackendError> {
+        try_backends(
+            &[self.archive(), self.chainhead(), self.legacy()],
+            async |b: &dyn Backend<T>| {
+                b.storage_fetch_child_descendant_values(
+                    child_key.clone(),
+                    key.clone(),
+                    options.clone(),
+                    at,
+                )
+                .await
+            },
         )
         .await
     }
@@ -346,6 +429,20 @@ impl<T: Config> Backend<T> for CombinedBackend<T> {
         .await
     }
 
+    async fn block_justifications(
+        &self,
+        at: HashFor<T>,
+    ) -> Result<Option<Vec<BlockJustification>>, BackendError> {
+        try_backends(
+            &[
+                // Only the legacy backend supports this.
+                self.legacy(),
+            ],
+            async |b: &dyn Backend<T>| b.block_justifications(at).await,
+        )
+        .await
+    }
+
     async fn latest_finalized_block_ref(&self) -> Result<BlockRef<HashFor<T>>, BackendError> {
         try_backends(
             &[
@@ -404,6 +501,21 @@ impl<T: Config> Backend<T> for CombinedBackend<T> {
         .await
     }
 
+    async fn stream_finalized_runtime_versions(
+        &self,
+        hasher: T::Hasher,
+    ) -> Result<StreamOfResults<RuntimeVersionChange<HashFor<T>>>, BackendError> {
+        try_backends(
+            &[
+                // Ignore archive backend; it doesn't support this.
+                self.chainhead(),
+                self.legacy(),
+            ],
+            async |b: &dyn Backend<T>| b.stream_finalized_runtime_versions(hasher.clone()).await,
+        )
+        .await
+    }
+
     async fn submit_transaction(
         &self,
         extrinsic: &[u8],
@@ -435,6 +547,15 @@ impl<T: Config> Backend<T> for CombinedBackend<T> {
         )
         .await
     }
+
+    fn max_concurrent_calls(&self) -> Option<usize> {
+        // Calls may be made via any of the backends, so use the lowest limit.
+        [self.archive(), self.chainhead(), self.legacy()]
+            .into_iter()
+            .flatten()
+            .filter_map(|b| b.max_concurrent_calls())
+            .min()
+    }
 }
 
 /// Call one backend after the other in the list until we get a successful result back.
@@ -9,18 +9,25 @@ mod descendant_streams;
 
 use crate::backend::utils::{retry, retry_stream};
 use crate::backend::{
-    Backend, BlockRef, StorageResponse, StreamOf, StreamOfResults, TransactionStatus,
+    Backend, BlockJustification, BlockRef, RuntimeVersionChange, StorageFetchDescendantsOptions,
+    StorageHashResponse, StorageResponse, StorageValueChange, StreamOf, StreamOfResults,
+    TransactionStatus,
 };
 use crate::config::{Config, HashFor, Hasher, Header, RpcConfigFor};
 use crate::error::BackendError;
+use crate::metadata::WellKnownStorageKey;
 use async_trait::async_trait;
 use codec::Encode;
 use descendant_streams::{StorageFetchDescendantKeysStream, StorageFetchDescendantValuesStream};
 use futures::TryStreamExt;
 use futures::{Future, Stream, StreamExt, future, future::Either, stream};
+use std::sync::Arc;
 use subxt_rpcs::RpcClient;
+use subxt_rpcs::client::RpcSubscription;
 use subxt_rpcs::methods::legacy::NumberOrHex;
-use subxt_rpcs::methods::legacy::{LegacyRpcMethods, TransactionStatus as RpcTransactionStatus};
+use subxt_rpcs::methods::legacy::{
+    LegacyRpcMethods, RuntimeVersion, TransactionStatus as RpcTransactionStatus,
+};
 
 /// Configure and build an [`LegacyBackend`].
 pub struct LegacyBackendBuilder<T> {
@@ -45,7 +52,10 @@ impl<T: Config> LegacyBackendBuilder<T> {
 
     /// Iterating over storage entries using the [`LegacyBackend`] requires
     /// fetching entries in batches. This configures the number of entries that
-    /// we'll try to obtain in each batch (default: 64).
+    /// we'll try to obtain in each batch (default: 64). It is also the number of
+    /// keys whose values we'll ask for at once when fetching many values, and the
+    /// number of blocks whose changes we'll ask for at once when fetching the history
+    /// of a storage value.
     pub fn storage_page_size(mut self, storage_page_size: u32) -> Self {
         self.storage_page_size = storage_page_size;
         self
@@ -84,6 +94,33 @@ impl<T: Config> LegacyBackend<T> {
     }
 }
 
+impl<T: Config> LegacyBackend<T> {
+    // Fetch the number of the block with the given hash.
+    async fn block_number(&self, at: HashFor<T>) -> Result<u64, BackendError> {
+        let header = self.block_header(at).await?.ok_or_else(|| {
+            BackendError::other(format!("Cannot find the block header for block {at:?}"))
+        })?;
+        Ok(header.number())
+    }
+
+    // Fetch the hash of the block with the given number.
+    async fn block_hash(&self, number: u64) -> Result<HashFor<T>, BackendError> {
+        let block_ref = self.block_number_to_hash(number).await?.ok_or_else(|| {
+            BackendError::other(format!("Cannot find the block hash for block {number}"))
+        })?;
+        Ok(block_ref.hash())
+    }
+
+    // Fetch the runtime version at the block with the given hash.
+    async fn runtime_version_at(&self, at: HashFor<T>) -> Result<RuntimeVersion, BackendError> {
+        retry(|| async {
+            let version = self.methods.state_get_runtime_version(Some(at)).await?;
+            Ok(version)
+        })
+        .await
+    }
+}
+
 impl<T: Config> super::sealed::Sealed for LegacyBackend<T> {}
 
 #[async_trait]
@@ -108,19 +145,56 @@ impl<T: Config> Backend<T> for LegacyBackend<T> {
             })
         }
 
-        let keys = keys.clone();
+        fn get_entries<T: Config>(
+            keys: Vec<Vec<u8>>,
+            at: HashFor<T>,
+            methods: LegacyRpcMethods<RpcConfigFor<T>>,
+        ) -> impl Future<Output = Result<Vec<StorageResponse>, BackendError>> {
+            retry(move || {
+                let methods = methods.clone();
+                let keys = keys.clone();
+                async move {
+                    let res = methods
+                        .state_query_storage_at(keys.iter().map(|k| &**k), Some(at))
+                        .await?;
+                    let values = res
+                        .into_iter()
+                        .flat_map(|change_set| change_set.changes)
+                        .filter_map(|(key, value)| {
+                            Some(StorageResponse {
+                                key: key.0,
+                                value: value?.0,
+                            })
+                        })
+                        .collect();
+                    Ok(values)
+                }
+            })
+        }
+
         let methods = self.methods.clone();
 
-        // For each key, return it + a future to get the result.
-        let iter = keys
-            .into_iter()
-            .map(move |key| get_entry(key, at, methods.clone()));
+        // A single value is fetched using `state_getStorage`.
+        if keys.len() == 1 {
+            let key = keys.into_iter().next().expect("one key exists; qed");
+            let s = stream::once(get_entry(key, at, methods))
+                // Filter any Options out (ie if we didn't find a value at the key we return nothing).
+                .filter_map(|r| future::ready(r.transpose()));
+            return Ok(StreamOf(Box::pin(s)));
+        }
 
-        let s = stream::iter(iter)
-            // Resolve the future
-            .then(|fut| fut)
-            // Filter any Options out (ie if we didn't find a value at some key we return nothing for it).
-            .filter_map(|r| future::ready(r.transpose()));
+        // Otherwise, fetch values for each batch of keys in a single `state_queryStorageAt` call.
+        let batches: Vec<Vec<Vec<u8>>> = keys
+            .chunks(self.storage_page_size.max(1) as usize)
+            .map(|batch| batch.to_vec())
+            .collect();
+
+        let s = stream::iter(batches)
+            .then(move |batch| get_entries(batch, at, methods.clone()))
+            .flat_map(|res| match res {
+                Ok(values) => Either::Left(stream::iter(values.into_iter().map(Ok))),
+                Err(e) => Either::Right(stream::once(future::ready(Err(e)))),
+            });
 
         Ok(StreamOf(Box::pin(s)))
     }
@@ -153,16 +227,184 @@ impl<T: Config> Backend<T> for LegacyBackend<T> {
         Ok(StreamOf(Box::pin(keys)))
     }
 
-    async fn storage_fetch_descendant_values(
+    async fn storage_fetch_descendant_values_with_options(
         &self,
         key: Vec<u8>,
+        options: StorageFetchDescendantsOptions,
         at: HashFor<T>,
     ) -> Result<StreamOfResults<StorageResponse>, BackendError> {
         let values_stream = StorageFetchDescendantValuesStream::new(
             self.methods.clone(),
             key,
             at,
-            self.storage_page_size,
+            options.page_size.unwrap_or(self.storage_page_size),
+            options.start_after,
+            None,
+        );
+
+        Ok(StreamOf(Box::pin(values_stream)))
+    }
+
+    async fn storage_fetch_hashes(
+        &self,
+        keys: Vec<Vec<u8>>,
+        at: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageHashResponse<HashFor<T>>>, BackendError> {
+        let methods = self.methods.clone();
+
+        let iter = keys.into_iter().map(move |key| {
+            let methods = methods.clone();
+            retry(move || {
+                let methods = methods.clone();
+                let key = key.clone();
+                async move {
+                    let res = methods.state_get_storage_hash(&key, Some(at)).await?;
+                    Ok(res.map(move |hash| StorageHashResponse { key, hash }))
+                }
+            })
+        });
+
+        let s = stream::iter(iter)
+            .then(|fut| fut)
+            .filter_map(|r| future::ready(r.transpose()));
+
+        Ok(StreamOf(Box::pin(s)))
+    }
+
+    async fn storage_closest_descendant_merkle_value(
+        &self,
+        _key: Vec<u8>,
+        _at: HashFor<T>,
+    ) -> Result<Option<Vec<u8>>, BackendError> {
+        Err(BackendError::other(
+            "The legacy backend does not support fetching closest descendant Merkle values",
+        ))
+    }
+
+    async fn storage_value_changes(
+        &self,
+        key: Vec<u8>,
+        from: HashFor<T>,
+        to: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageValueChange<HashFor<T>>>, BackendError> {
+        let to_number = self.block_number(to).await?;
+        let from_number = self.block_number(from).await?;
+        // Pages overlap by one block, so each must span at least two blocks to make progress.
+        let page_size = u64::from(self.storage_page_size).max(2);
+        let this = self.clone();
+
+        // Ask for the changes in one page of blocks at a time, so that we don't hold the changes
+        // for the entire range in memory at once.
+        let pages = stream::try_unfold(
+            Some((from_number, from, None)),
+            move |page: Option<(u64, HashFor<T>, Option<Option<Vec<u8>>>)>| {
+                let this = this.clone();
+                let key = key.clone();
+                async move {
+                    let Some((start_number, start_hash, mut last_value)) = page else {
+                        return Ok(None);
+                    };
+
+                    let end_number = start_number.saturating_add(page_size - 1).min(to_number);
+                    let end_hash = if end_number == to_number {
+                        to
+                    } else {
+                        this.block_hash(end_number).await?
+                    };
+
+                    let change_sets = retry(|| async {
+                        let change_sets = this
+                            .methods
+                            .state_query_storage(std::iter::once(&*key), start_hash, Some(end_hash))
+                            .await?;
+                        Ok(change_sets)
+                    })
+                    .await?;
+
+                    // Each change set contains our key only if its value changed in that block,
+                    // or, for the first change set in a page, to tell us the value at the start
+                    // of the page (which we'll have seen already unless this is the first page).
+                    let mut changes: Vec<Result<_, BackendError>> = Vec::new();
+                    for change_set in change_sets {
+                        let Some((_, value)) =
+                            change_set.changes.into_iter().find(|(k, _)| k.0 == key)
+                        else {
+                            continue;
+                        };
+                        let value = value.map(|v| v.0);
+                        if last_value.as_ref() != Some(&value) {
+                            last_value = Some(value.clone());
+                            changes.push(Ok(StorageValueChange {
+                                block_hash: change_set.block,
+                                value,
+                            }));
+                        }
+                    }
+
+                    // The next page begins at the last block of this one.
+                    let next_page =
+                        (end_number < to_number).then_some((end_number, end_hash, last_value));
+                    Ok(Some((stream::iter(changes), next_page)))
+                }
+            },
+        );
+
+        Ok(StreamOf(Box::pin(pages.try_flatten())))
+    }
+
+    async fn storage_fetch_child_values(
+        &self,
+        child_key: Vec<u8>,
+        keys: Vec<Vec<u8>>,
+        at: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageResponse>, BackendError> {
+        fn get_entry<T: Config>(
+            child_storage_key: Arc<[u8]>,
+            key: Vec<u8>,
+            at: HashFor<T>,
+            methods: LegacyRpcMethods<RpcConfigFor<T>>,
+        ) -> impl Future<Output = Result<Option<StorageResponse>, BackendError>> {
+            retry(move || {
+                let methods = methods.clone();
+                let child_storage_key = child_storage_key.clone();
+                let key = key.clone();
+                async move {
+                    let res = methods
+                        .childstate_get_storage(&child_storage_key, &key, Some(at))
+                        .await?;
+                    Ok(res.map(move |value| StorageResponse { key, value }))
+                }
+            })
+        }
+
+        let child_storage_key: Arc<[u8]> = default_child_storage_key(child_key).into();
+        let methods = self.methods.clone();
+
+        let iter = keys
+            .into_iter()
+            .map(move |key| get_entry(child_storage_key.clone(), key, at, methods.clone()));
+
+        let s = stream::iter(iter)
+            .then(|fut| fut)
+            .filter_map(|r| future::ready(r.transpose()));
+
+        Ok(StreamOf(Box::pin(s)))
+    }
+
+    async fn storage_fetch_child_descendant_values(
+        &self,
+        child_key: Vec<u8>,
+        key: Vec<u8>,
+        options: StorageFetchDescendantsOptions,
+        at: HashFor<T>,
+    ) -> Result<StreamOfResults<StorageResponse>, BackendError> {
+        let values_stream = StorageFetchDescendantValuesStream::new(
+            self.methods.clone(),
+            key,
+            at,
+            options.page_size.unwrap_or(self.storage_page_size),
+            options.start_after,
+            Some(default_child_storage_key(child_key)),
         );
 
         Ok(StreamOf(Box::pin(values_stream)))
@@ -212,6 +454,19 @@ impl<T: Config> Backend<T> for LegacyBackend<T> {
         .await
     }
 
+    async fn block_justifications(
+        &self,
+        at: HashFor<T>,
+    ) -> Result<Option<Vec<BlockJustification>>, BackendError> {
+        retry(|| async {
+            let Some(details) = self.methods.chain_get_block(Some(at)).await? else {
+                return Ok(None);
+            };
+            Ok(Some(details.justifications.unwrap_or_default()))
+        })
+        .await
+    }
+
     async fn latest_finalized_block_ref(&self) -> Result<BlockRef<HashFor<T>>, BackendError> {
         retry(|| async {
             let hash = self.methods.chain_get_finalized_head().await?;
@@ -310,6 +565,85 @@ impl<T: Config> Backend<T> for LegacyBackend<T> {
         Ok(retry_sub)
     }
 
+    async fn stream_finalized_runtime_versions(
+        &self,
+        hasher: T::Hasher,
+    ) -> Result<StreamOfResults<RuntimeVersionChange<HashFor<T>>>, BackendError> {
+        let headers = self.stream_finalized_block_headers(hasher).await?;
+
+        // This subscription tells us about runtime versions in new best blocks but not which
+        // blocks they are in, so we use it to know when to look for the finalized block with
+        // a new version. If it isn't available, we ask for the version at every finalized block.
+        let best_versions = self.methods.state_subscribe_runtime_version().await.ok();
+
+        let state = RuntimeVersionsState {
+            backend: self.clone(),
+            headers,
+            best_versions,
+            best_spec_version: None,
+            current: None,
+        };
+
+        let versions = stream::try_unfold(state, |mut state| async move {
+            let Some(current) = &state.current else {
+                // Start by handing back the version at the latest finalized block.
+                let block_ref = state.backend.latest_finalized_block_ref().await?;
+                let version = state.backend.runtime_version_at(block_ref.hash()).await?;
+                state.current = Some(version.clone());
+                return Ok(Some((RuntimeVersionChange { block_ref, version }, state)));
+            };
+            let mut current_spec_version = current.spec_version;
+
+            loop {
+                let next = match &mut state.best_versions {
+                    Some(best_versions) => {
+                        match future::select(best_versions.next(), state.headers.next()).await {
+                            Either::Left((version, _)) => Either::Left(version),
+                            Either::Right((header, _)) => Either::Right(header),
+                        }
+                    }
+                    None => Either::Right(state.headers.next().await),
+                };
+
+                let header = match next {
+                    Either::Left(Some(Ok(version))) => {
+                        state.best_spec_version = Some(version.spec_version);
+                        continue;
+                    }
+                    Either::Left(_) => {
+                        // The subscription has stopped, so ask at every finalized block from now on.
+                        state.best_versions = None;
+                        continue;
+                    }
+                    Either::Right(header) => header,
+                };
+
+                let Some(header) = header else {
+                    return Ok(None);
+                };
+                let (_, block_ref) = header?;
+
+                // Nothing to do if the best blocks are known to be on the current version.
+                if state.best_versions.is_some()
+                    && state.best_spec_version == Some(current_spec_version)
+                {
+                    continue;
+                }
+
+                let version = state.backend.runtime_version_at(block_ref.hash()).await?;
+                if state.current.as_ref() == Some(&version) {
+                    continue;
+                }
+
+                current_spec_version = version.spec_version;
+                state.current = Some(version.clone());
+                return Ok(Some((RuntimeVersionChange { block_ref, version }, state)));
+            }
+        });
+
+        Ok(StreamOf(Box::pin(versions)))
+    }
+
     async fn submit_transaction(
         &self,
         extrinsic: &[u8],
@@ -387,6 +721,23 @@ impl<T: Config> Backend<T> for LegacyBackend<T> {
     }
 }
 
+// The state needed to stream finalized runtime versions.
+struct RuntimeVersionsState<T: Config> {
+    backend: LegacyBackend<T>,
+    headers: StreamOfResults<(T::Header, BlockRef<HashFor<T>>)>,
+    best_versions: Option<RpcSubscription<RuntimeVersion>>,
+    best_spec_version: Option<u32>,
+    current: Option<RuntimeVersion>,
+}
+
+// The legacy `childstate_*` methods expect the child trie key to be prefixed.
+fn default_child_storage_key(child_key: Vec<u8>) -> Vec<u8> {
+    WellKnownStorageKey::DefaultChildStorage {
+        child_key: &child_key,
+    }
+    .to_key()
+}
+
 /// Note: This is exposed for testing but is not considered stable and may change
 /// without notice in a patch release.
 #[doc(hidden)]
@@ -434,3 +785,395 @@ where
         Either::Right(previous_headers.chain(stream::once(async { Ok(header) })))
     })
 }
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use crate::config::substrate::{H256, SubstrateHeader};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use subxt_rpcs::client::MockRpcClient;
+    use subxt_rpcs::client::mock_rpc_client::Json;
+
+    /// The hash that [`chain_with_value_history`] gives to the block with the given number.
+    pub fn block_hash(number: u8) -> H256 {
+        H256::repeat_byte(number)
+    }
+
+    /// Mock a chain in which block `n` has the hash [`block_hash(n)`], and the value at `key`
+    /// in block `n` is `values[n]`. The number of `state_queryStorage` calls made is counted.
+    pub fn chain_with_value_history(
+        key: Vec<u8>,
+        values: Vec<Option<Vec<u8>>>,
+        calls: Arc<AtomicUsize>,
+    ) -> MockRpcClient {
+        let key = format!("0x{}", hex::encode(key));
+        let to_hex = |value: &Option<Vec<u8>>| {
+            value
+                .as_ref()
+                .map(|value| format!("0x{}", hex::encode(value)))
+        };
+
+        MockRpcClient::builder()
+            .method_handler("chain_getBlockHash", |params| async move {
+                let (number,): (u8,) = serde_json::from_str(params.unwrap().get()).unwrap();
+                Json(block_hash(number))
+            })
+            .method_handler("chain_getHeader", |params| async move {
+                let (hash,): (H256,) = serde_json::from_str(params.unwrap().get()).unwrap();
+                Json(SubstrateHeader {
+                    parent_hash: block_hash(hash[0].wrapping_sub(1)),
+                    number: hash[0].into(),
+                    state_root: H256::zero(),
+                    extrinsics_root: H256::zero(),
+                    digest: Default::default(),
+                })
+            })
+            .method_handler("state_queryStorage", move |params| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                let key = key.clone();
+                let values = values.clone();
+                async move {
+                    let (keys, from, to): (Vec<String>, H256, H256) =
+                        serde_json::from_str(params.unwrap().get()).unwrap();
+                    assert_eq!(keys, vec![key.clone()]);
+
+                    // The first change set has the value at `from`, and the rest are only
+                    // given for blocks in which the value changes.
+                    let (from, to) = (from[0] as usize, to[0] as usize);
+                    let change_sets: Vec<_> = (from..=to)
+                        .filter(|&n| n == from || values[n] != values[n - 1])
+                        .map(|n| {
+                            serde_json::json!({
+                                "block": block_hash(n as u8),
+                                "changes": [[key, to_hex(&values[n])]],
+                            })
+                        })
+                        .collect();
+                    Json(change_sets)
+                }
+            })
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::SubstrateConfig;
+    use crate::config::substrate::H256;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use subxt_rpcs::client::MockRpcClient;
+    use subxt_rpcs::client::mock_rpc_client::{Json, MockRpcClientBuilder};
+
+    // Handle `state_queryStorageAt` by returning a value for every key except `0x00`,
+    // counting the number of calls made.
+    fn with_query_storage_at(
+        builder: MockRpcClientBuilder,
+        calls: Arc<AtomicUsize>,
+    ) -> MockRpcClientBuilder {
+        builder.method_handler("state_queryStorageAt", move |params| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                let (keys, _at): (Vec<String>, Option<H256>) =
+                    serde_json::from_str(params.unwrap().get()).unwrap();
+                let changes: Vec<_> = keys
+                    .into_iter()
+                    .map(|key| {
+                        let value = (key != "0x00").then(|| format!("{key}ff"));
+                        (key, value)
+                    })
+                    .collect();
+                Json(serde_json::json!([{ "block": H256::zero(), "changes": changes }]))
+            }
+        })
+    }
+
+    fn response(key: &[u8]) -> StorageResponse {
+        let mut value = key.to_vec();
+        value.push(0xff);
+        StorageResponse {
+            key: key.to_vec(),
+            value,
+        }
+    }
+
+    #[tokio::test]
+    async fn storage_fetch_values_batches_keys() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mock_client = with_query_storage_at(MockRpcClient::builder(), calls.clone()).build();
+
+        let backend: LegacyBackend<SubstrateConfig> = LegacyBackend::builder()
+            .storage_page_size(2)
+            .build(RpcClient::new(mock_client));
+
+        let values: Vec<_> = backend
+            .storage_fetch_values(vec![vec![0], vec![1], vec![2]], H256::zero())
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+
+        // Two batches of keys, so two requests were made.
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(values, vec![response(&[1]), response(&[2])]);
+    }
+
+    #[tokio::test]
+    async fn storage_fetch_descendant_values_honours_options() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mock_client =
+            MockRpcClient::builder().method_handler("state_getKeysPaged", |params| async move {
+                let (prefix, count, start_key, _at): (String, u32, Option<String>, Option<H256>) =
+                    serde_json::from_str(params.unwrap().get()).unwrap();
+                // The page size given in the options is used.
+                assert_eq!(count, 1);
+                let keys: Vec<_> = ["0x0101", "0x0102", "0x0103", "0x0201"]
+                    .into_iter()
+                    .filter(|key| key.starts_with(&prefix))
+                    .filter(|key| start_key.as_deref().is_none_or(|start| *key > start))
+                    .take(count as usize)
+                    .collect();
+                Json(keys)
+            });
+        let mock_client = with_query_storage_at(mock_client, calls).build();
+
+        let backend: LegacyBackend<SubstrateConfig> =
+            LegacyBackend::builder().build(RpcClient::new(mock_client));
+
+        let options = StorageFetchDescendantsOptions {
+            start_after: Some(vec![1, 1]),
+            page_size: Some(1),
+        };
+        let values: Vec<_> = backend
+            .storage_fetch_descendant_values_with_options(vec![1], options, H256::zero())
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+
+        // Only values after the `start_after` key are returned.
+        assert_eq!(values, vec![response(&[1, 2]), response(&[1, 3])]);
+    }
+
+    #[tokio::test]
+    async fn child_values_are_fetched_from_the_default_child_trie() {
+        let child_storage_key = format!("0x{}", hex::encode(b":child_storage:default:foo"));
+
+        let mock_client = MockRpcClient::builder()
+            .method_handler("childstate_getStorage", {
+                let child_storage_key = child_storage_key.clone();
+                move |params| {
+                    let child_storage_key = child_storage_key.clone();
+                    async move {
+                        let (child_key, key, _at): (String, String, Option<H256>) =
+                            serde_json::from_str(params.unwrap().get()).unwrap();
+                        assert_eq!(child_key, child_storage_key);
+                        Json((key == "0x01").then_some("0x2a"))
+                    }
+                }
+            })
+            .method_handler("childstate_getKeysPaged", move |params| {
+                let child_storage_key = child_storage_key.clone();
+                async move {
+                    let (child_key, prefix, count, start_key, _at): (
+                        String,
+                        String,
+                        u32,
+                        Option<String>,
+                        Option<H256>,
+                    ) = serde_json::from_str(params.unwrap().get()).unwrap();
+                    assert_eq!(child_key, child_storage_key);
+                    let keys: Vec<_> = ["0x0101", "0x0102", "0x0201"]
+                        .into_iter()
+                        .filter(|key| key.starts_with(&prefix))
+                        .filter(|key| start_key.as_deref().is_none_or(|start| *key > start))
+                        .take(count as usize)
+                        .collect();
+                    Json(keys)
+                }
+            })
+            .method_handler("childstate_getStorageEntries", |params| async move {
+                let (_child_key, keys, _at): (String, Vec<String>, Option<H256>) =
+                    serde_json::from_str(params.unwrap().get()).unwrap();
+                let values: Vec<_> = keys.into_iter().map(|key| format!("{key}ff")).collect();
+                Json(values)
+            })
+            .build();
+
+        let backend: LegacyBackend<SubstrateConfig> =
+            LegacyBackend::builder().build(RpcClient::new(mock_client));
+
+        let values: Vec<_> = backend
+            .storage_fetch_child_values(b"foo".to_vec(), vec![vec![1], vec![2]], H256::zero())
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(
+            values,
+            vec![StorageResponse {
+                key: vec![1],
+                value: vec![0x2a]
+            }]
+        );
+
+        let values: Vec<_> = backend
+            .storage_fetch_child_descendant_values(
+                b"foo".to_vec(),
+                vec![1],
+                Default::default(),
+                H256::zero(),
+            )
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(values, vec![response(&[1, 1]), response(&[1, 2])]);
+    }
+
+    #[tokio::test]
+    async fn storage_value_changes_are_fetched_in_pages() {
+        let values = vec![
+            Some(vec![1]),
+            Some(vec![1]),
+            Some(vec![2]),
+            Some(vec![2]),
+            None,
+            Some(vec![3]),
+        ];
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mock_client =
+            test_utils::chain_with_value_history(vec![1, 2, 3], values, calls.clone());
+
+        let backend: LegacyBackend<SubstrateConfig> = LegacyBackend::builder()
+            .storage_page_size(2)
+            .build(RpcClient::new(mock_client));
+
+        let changes: Vec<_> = backend
+            .storage_value_changes(
+                vec![1, 2, 3],
+                test_utils::block_hash(0),
+                test_utils::block_hash(5),
+            )
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+
+        // Pages of two blocks which overlap by one block: 0-1, 1-2, 2-3, 3-4 and 4-5.
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+        let change = |n, value| StorageValueChange {
+            block_hash: test_utils::block_hash(n),
+            value,
+        };
+        assert_eq!(
+            changes,
+            vec![
+                change(0, Some(vec![1])),
+                change(2, Some(vec![2])),
+                change(4, None),
+                change(5, Some(vec![3])),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn runtime_versions_are_only_fetched_after_a_new_best_version_is_seen() {
+        use crate::client::OfflineClient;
+        use crate::config::substrate::SubstrateHeader;
+        use std::collections::HashMap;
+
+        fn header(number: u64) -> SubstrateHeader<H256> {
+            SubstrateHeader {
+                parent_hash: H256::zero(),
+                number,
+                state_root: H256::zero(),
+                extrinsics_root: H256::zero(),
+                digest: Default::default(),
+            }
+        }
+
+        let hasher = OfflineClient::new_with_config(crate::client::test_utils::config())
+            .at_block(0u64)
+            .unwrap()
+            .hasher()
+            .clone();
+        let block_hash = |number| hasher.hash(&header(number).encode());
+
+        // Blocks 0 and 1 use spec version 1, and blocks 2 and 3 use spec version 2.
+        let numbers: HashMap<H256, u64> = (0..4).map(|n| (block_hash(n), n)).collect();
+        fn runtime_version(spec_version: u32) -> serde_json::Value {
+            serde_json::json!({ "specVersion": spec_version, "transactionVersion": 1 })
+        }
+        let spec_version = |number| if number < 2 { 1 } else { 2 };
+
+        // The runtime version subscription has already seen spec version 2 in a best block.
+        let (best_versions_tx, best_versions_rx) = tokio::sync::mpsc::unbounded_channel();
+        best_versions_tx.send(Json(runtime_version(1))).unwrap();
+        best_versions_tx.send(Json(runtime_version(2))).unwrap();
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mock_client = MockRpcClient::builder()
+            .method_handler("chain_getFinalizedHead", {
+                let hash = block_hash(0);
+                move |_params| async move { Json(hash) }
+            })
+            .method_handler("chain_getHeader", {
+                let numbers = numbers.clone();
+                move |params| {
+                    let numbers = numbers.clone();
+                    async move {
+                        let (hash,): (H256,) = serde_json::from_str(params.unwrap().get()).unwrap();
+                        Json(header(numbers[&hash]))
+                    }
+                }
+            })
+            .method_handler("state_getRuntimeVersion", {
+                let calls = calls.clone();
+                move |params| {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    let numbers = numbers.clone();
+                    async move {
+                        let (hash,): (H256,) = serde_json::from_str(params.unwrap().get()).unwrap();
+                        Json(runtime_version(spec_version(numbers[&hash])))
+                    }
+                }
+            })
+            .subscription_handler("chain_subscribeFinalizedHeads", |_params, _unsub| async {
+                vec![Json(header(1)), Json(header(2)), Json(header(3))]
+            })
+            .subscription_handler_once(
+                "state_subscribeRuntimeVersion",
+                move |_params, _unsub| async move { best_versions_rx },
+            )
+            .build();
+
+        let backend: LegacyBackend<SubstrateConfig> =
+            LegacyBackend::builder().build(RpcClient::new(mock_client));
+
+        let changes: Vec<_> = backend
+            .stream_finalized_runtime_versions(hasher.clone())
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+
+        // The version at the latest finalized block is handed back first, followed by the
+        // first finalized block with a new version.
+        let changes: Vec<_> = changes
+            .into_iter()
+            .map(|change| (change.block_ref.hash(), change.version.spec_version))
+            .collect();
+        assert_eq!(changes, vec![(block_hash(0), 1), (block_hash(2), 2)]);
+
+        // The version was requested at block 0, and then at blocks 1 and 2 until the new
+        // version was found. Block 3 is known to be on the latest version already.
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+}
@@ -15,6 +15,8 @@ pub struct StorageFetchDescendantKeysStream<T: Config> {
     methods: LegacyRpcMethods<RpcConfigFor<T>>,
     key: Vec<u8>,
     at: HashFor<T>,
+    // The prefixed key of the child trie to look in, if any.
+    child_storage_key: Option<Vec<u8>>,
     // How many entries to ask for each time.
     storage_page_size: u32,
     // What key do we start paginating from? None = from the beginning.
@@ -38,6 +40,7 @@ impl<T: Config> StorageFetchDescendantKeysStream<T> {
             methods,
             key,
             at,
+            child_storage_key: None,
             storage_page_size,
             pagination_start_key: None,
             keys_fut: None,
@@ -105,15 +108,31 @@ impl<T: Config> Stream for StorageFetchDescendantKeysStream<T> {
             let at = this.at;
             let storage_page_size = this.storage_page_size;
             let pagination_start_key = this.pagination_start_key.clone();
+            let child_storage_key = this.child_storage_key.clone();
             let keys_fut = async move {
-                let keys = methods
-                    .state_get_keys_paged(
-                        &key,
-                        storage_page_size,
-                        pagination_start_key.as_deref(),
-                        Some(at),
-                    )
-                    .await?;
+                let keys = match child_storage_key {
+                    Some(child_storage_key) => {
+                        methods
+                            .childstate_get_keys_paged(
+                                &child_storage_key,
+                                &key,
+                                storage_page_size,
+                                pagination_start_key.as_deref(),
+                                Some(at),
+                            )
+                            .await?
+                    }
+                    None => {
+                        methods
+                            .state_get_keys_paged(
+                                &key,
+                                storage_page_size,
+                                pagination_start_key.as_deref(),
+                                Some(at),
+                            )
+                            .await?
+                    }
+                };
                 Ok(keys)
             };
             this.keys_fut = Some(Box::pin(keys_fut));
@@ -143,20 +162,24 @@ pub struct StorageFetchDescendantValuesStream<T: Config> {
 }
 
 impl<T: Config> StorageFetchDescendantValuesStream<T> {
-    /// Fetch descendant values.
+    /// Fetch descendant values, starting after `pagination_start_key` if one is given.
+    /// If `child_storage_key` is given, values are fetched from that (prefixed) child trie.
     pub fn new(
         methods: LegacyRpcMethods<RpcConfigFor<T>>,
         key: Vec<u8>,
         at: HashFor<T>,
         storage_page_size: u32,
+        pagination_start_key: Option<Vec<u8>>,
+        child_storage_key: Option<Vec<u8>>,
     ) -> Self {
         StorageFetchDescendantValuesStream {
             keys_stream: StorageFetchDescendantKeysStream {
                 methods,
                 key,
                 at,
+                child_storage_key,
                 storage_page_size,
-                pagination_start_key: None,
+                pagination_start_key,
                 keys_fut: None,
                 done: false,
             },
@@ -216,7 +239,28 @@ impl<T: Config> Stream for StorageFetchDescendantValuesStream<T> {
                 let methods = this.keys_stream.methods.clone();
                 let at = this.keys_stream.at;
                 let keys = this.keys.clone();
+                let child_storage_key = this.keys_stream.child_storage_key.clone();
                 let results_fut = async move {
+                    if let Some(child_storage_key) = child_storage_key {
+                        let values = retry(|| async {
+                            let res = methods
+                                .childstate_get_storage_entries(
+                                    &child_storage_key,
+                                    keys.iter().map(|k| &**k),
+                                    Some(at),
+                                )
+                                .await?;
+                            Ok(res)
+                        })
+                        .await?;
+                        let values: VecDeque<_> = keys
+                            .into_iter()
+                            .zip(values)
+                            .filter_map(|(k, v)| Some((k, v?)))
+                            .collect();
+                        return Ok(Some(values));
+                    }
+
                     let keys = keys.iter().map(|k| &**k);
                     let values = retry(|| async {
                         let res = methods
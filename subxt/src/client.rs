@@ -14,6 +14,7 @@
 
 mod offline_client;
 mod online_client;
+mod snapshot_client;
 
 use crate::backend::BlockRef;
 use crate::config::{Config, HashFor};
@@ -22,18 +23,25 @@ use crate::custom_values::CustomValuesClient;
 use crate::error::BlockError;
 use crate::events::EventsClient;
 use crate::extrinsics::ExtrinsicsClient;
+use crate::grandpa::GrandpaClient;
 use crate::runtime_apis::RuntimeApisClient;
-use crate::storage::StorageClient;
+use crate::storage::{StorageClient, StorageSnapshot};
 use crate::transactions::TransactionsClient;
 use crate::view_functions::ViewFunctionsClient;
 use core::marker::PhantomData;
 use std::borrow::Cow;
+use std::sync::Arc;
 use subxt_metadata::{ArcMetadata, Metadata};
 
 pub use offline_client::{OfflineClient, OfflineClientAtBlockImpl, OfflineClientAtBlockT};
 pub use online_client::{
     Block, BlockNumberOrRef, Blocks, OnlineClient, OnlineClientAtBlockImpl, OnlineClientAtBlockT,
+    RuntimeUpgrade, RuntimeUpgrades,
 };
+pub use snapshot_client::{SnapshotClientAtBlockImpl, StorageClientAtBlockT};
+
+#[cfg(test)]
+pub(crate) use snapshot_client::test_utils;
 
 /// This represents a client at a specific block number, and is created by calling either
 /// [`OnlineClient::at_block`] or [`OfflineClient::at_block`].
@@ -108,6 +116,11 @@ where
         ViewFunctionsClient::new(&self.client)
     }
 
+    /// Work with GRANDPA finality justifications at this block.
+    pub fn grandpa(&self) -> GrandpaClient<'_, T, Client> {
+        GrandpaClient::new(&self.client)
+    }
+
     /// Obtain a clone of the metadata. Prefer [`Self::metadata_ref()`]
     /// unless you need to take ownership of the metadata.
     pub fn metadata(&self) -> ArcMetadata {
@@ -190,8 +203,26 @@ where
     }
 }
 
+impl<T: Config> ClientAtBlock<T, OfflineClientAtBlockImpl<T>> {
+    /// Provide a [`StorageSnapshot`] which storage values will be fetched from. This allows the
+    /// usual storage APIs (like [`crate::storage::StorageEntry::fetch()`] and
+    /// [`crate::storage::StorageEntry::iter()`]) to be used without a connection to a node.
+    ///
+    /// The snapshot is expected to contain the storage at this block, so that the metadata used to
+    /// decode values matches the values in it.
+    pub fn with_storage_snapshot(
+        self,
+        snapshot: impl Into<Arc<StorageSnapshot>>,
+    ) -> SnapshotClientAtBlock<T> {
+        ClientAtBlock::new(SnapshotClientAtBlockImpl::new(self.client, snapshot.into()))
+    }
+}
+
 /// An offline client at a specific block.
 pub type OfflineClientAtBlock<T> = ClientAtBlock<T, OfflineClientAtBlockImpl<T>>;
 
+/// An offline client at a specific block, which can fetch storage values from a [`StorageSnapshot`].
+pub type SnapshotClientAtBlock<T> = ClientAtBlock<T, SnapshotClientAtBlockImpl<T>>;
+
 /// An online client at a specific block.
 pub type OnlineClientAtBlock<T> = ClientAtBlock<T, OnlineClientAtBlockImpl<T>>;
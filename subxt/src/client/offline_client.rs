@@ -29,10 +29,22 @@ impl<T: Config> OfflineClient<T> {
             .spec_and_transaction_version_for_block_number(block_number)
             .ok_or(OfflineClientAtBlockError::SpecVersionNotFound { block_number })?;
 
-        let metadata = self
-            .config
-            .metadata_for_spec_version(spec_version)
-            .ok_or(OfflineClientAtBlockError::MetadataNotFound { spec_version })?;
+        let metadata = match self.config.metadata_for_spec_version(spec_version) {
+            Some(metadata) => metadata,
+            // Fall back to any encoded metadata that the config has. We can't convert
+            // pre-V14 metadata here, since it would need to be paired with legacy types.
+            None => {
+                let metadata = self
+                    .config
+                    .encoded_metadata_for_spec_version(spec_version)
+                    .and_then(|bytes| Metadata::decode_from(&bytes).ok())
+                    .ok_or(OfflineClientAtBlockError::MetadataNotFound { spec_version })?
+                    .arc();
+                self.config
+                    .set_metadata_for_spec_version(spec_version, metadata.clone());
+                metadata
+            }
+        };
 
         let genesis_hash = self.config.genesis_hash();
 
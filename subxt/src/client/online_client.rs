@@ -1,13 +1,20 @@
 mod block_number_or_ref;
 mod blocks;
+mod runtime_upgrades;
+mod spec_versions;
 
 use super::ClientAtBlock;
 use super::OfflineClientAtBlockT;
 use crate::backend::{Backend, BlockRef};
+use crate::config::substrate::SpecVersionForRange;
 use crate::config::{Config, HashFor, Hasher, Header};
 use crate::error::OnlineClientError;
-use crate::error::{BlocksError, OnlineClientAtBlockError};
+use crate::error::{
+    BlocksError, OnlineClientAtBlockError, RuntimeUpgradesError, SpecVersionDiscoveryError,
+    StorageHistoryError,
+};
 use crate::metadata::{ArcMetadata, Metadata};
+use crate::storage::{Address, StorageHistory};
 use crate::transactions::TransactionsClient;
 use codec::{Compact, Decode, Encode};
 use core::marker::PhantomData;
@@ -18,6 +25,7 @@ use std::sync::Arc;
 
 pub use block_number_or_ref::BlockNumberOrRef;
 pub use blocks::{Block, Blocks};
+pub use runtime_upgrades::{RuntimeUpgrade, RuntimeUpgrades};
 
 /// A client which requires a connection to a chain, and allows interacting with it.
 #[derive(Clone, Debug)]
@@ -266,6 +274,68 @@ impl<T: Config> OnlineClient<T> {
         Ok(Blocks::from_headers_stream(self.clone(), stream))
     }
 
+    /// Subscribe to runtime upgrades. Each time a finalized block has a different spec version
+    /// to the one before it, this yields the old and new runtime versions, the new metadata and a
+    /// [`crate::metadata::MetadataDiff`] describing what changed between the old and new metadata.
+    ///
+    /// **Note:** Runtime versions are obtained via [`crate::backend::Backend::stream_finalized_runtime_versions()`],
+    /// which is told about new runtimes by the node where possible. If the node cannot tell us about new
+    /// runtimes, the runtime version is requested for every finalized block instead.
+    pub async fn subscribe_runtime_upgrades(
+        &self,
+    ) -> Result<RuntimeUpgrades<T>, RuntimeUpgradesError> {
+        RuntimeUpgrades::new(self.clone()).await
+    }
+
+    /// Find the spec and transaction versions used by each range of blocks in the given block range.
+    /// Rather than looking at every block, this binary searches for the blocks at which the versions
+    /// change, and so needs only a handful of runtime API calls per runtime upgrade.
+    ///
+    /// The result can be serialized, and handed to a configuration via for instance
+    /// [`crate::config::SubstrateConfigBuilder::set_spec_version_for_block_ranges()`], so that the
+    /// versions do not need to be looked up when instantiating a client at each block. The last range
+    /// returned ends at the end of the given block range. The ranges are also handed to
+    /// [`Config::cache_spec_version_for_block_ranges()`], so that the configuration can cache them.
+    ///
+    /// **Note:** This assumes that versions only ever increase, and so if two blocks have the same
+    /// versions then so does every block in between them. The node must be able to provide the state
+    /// for every block in the range (ie it should be an archive node).
+    pub async fn discover_spec_versions(
+        &self,
+        block_range: std::ops::Range<u64>,
+    ) -> Result<Vec<SpecVersionForRange>, SpecVersionDiscoveryError> {
+        let ranges = spec_versions::discover_spec_versions(self, block_range).await?;
+        self.inner
+            .config
+            .cache_spec_version_for_block_ranges(&ranges);
+        Ok(ranges)
+    }
+
+    /// Obtain the value of some storage entry at the start of a range of blocks, and then
+    /// at each subsequent block (up to and including `to`) in which it changes. Values are
+    /// decoded using the metadata at the block they were found in, so that they continue to
+    /// decode correctly across runtime upgrades.
+    ///
+    /// **Note:** This can be expensive over large ranges. The legacy backend makes one
+    /// `state_queryStorage` call per page of blocks (see
+    /// [`crate::backend::LegacyBackendBuilder::storage_page_size()`]), while the archive backend
+    /// diffs the storage across the whole range and then bisects only those spans of blocks in
+    /// which the value changed. The archive backend won't notice a change which is undone again
+    /// within a span that it doesn't need to bisect.
+    pub async fn storage_history<Addr>(
+        &self,
+        address: Addr,
+        key_parts: Addr::KeyParts,
+        from: impl Into<BlockNumberOrRef<T>>,
+        to: impl Into<BlockNumberOrRef<T>>,
+    ) -> Result<StorageHistory<T, Addr::Value>, StorageHistoryError>
+    where
+        Addr: Address + Clone + Send + Sync + 'static,
+        Addr::Value: Send + 'static,
+    {
+        StorageHistory::new(self.clone(), address, key_parts, from.into(), to.into()).await
+    }
+
     /// Instantiate a client to work at the current finalized block _at the time of instantiation_.
     /// This does not track new blocks.
     pub async fn at_current_block(
@@ -357,41 +427,12 @@ impl<T: Config> OnlineClient<T> {
                         reason: e,
                     })?;
 
-                // This is the "modern" version information. We get this back for
-                // all Polkadot RC blocks and try this first.
-                #[derive(codec::Decode, Debug)]
-                struct SpecVersionHeader {
-                    _spec_name: String,
-                    _impl_name: String,
-                    _authoring_version: u32,
-                    spec_version: u32,
-                    _impl_version: u32,
-                    _apis: Vec<([u8; 8], u32)>,
-                    transaction_version: u32,
-                }
-
-                // For old Kusama RC blocks (and possibly others), the transaction
-                // version field hasn't been added yet and so we default it to 0.
-                #[derive(codec::Decode, Debug)]
-                struct OldSpecVersionHeader {
-                    _spec_name: String,
-                    _impl_name: String,
-                    _authoring_version: u32,
-                    spec_version: u32,
-                    _impl_version: u32,
-                    _apis: Vec<([u8; 8], u32)>,
-                }
-
-                SpecVersionHeader::decode(&mut &spec_version_bytes[..])
-                    .map(|version| (version.spec_version, version.transaction_version))
-                    .or_else(|_| {
-                        OldSpecVersionHeader::decode(&mut &spec_version_bytes[..])
-                            .map(|version| (version.spec_version, 0))
-                    })
-                    .map_err(|e| OnlineClientAtBlockError::CannotDecodeSpecVersion {
+                decode_spec_and_transaction_version(&spec_version_bytes).map_err(|e| {
+                    OnlineClientAtBlockError::CannotDecodeSpecVersion {
                         block_hash: block_hash.into(),
                         reason: e,
-                    })?
+                    }
+                })?
             }
         };
 
@@ -399,103 +440,120 @@ impl<T: Config> OnlineClient<T> {
         let metadata = match self.inner.config.metadata_for_spec_version(spec_version) {
             Some(metadata) => metadata,
             None => {
-                let metadata: Metadata =
-                    match get_metadata(&*self.inner.backend, block_hash).await? {
-                        m @ RuntimeMetadata::V0(_)
-                        | m @ RuntimeMetadata::V1(_)
-                        | m @ RuntimeMetadata::V2(_)
-                        | m @ RuntimeMetadata::V3(_)
-                        | m @ RuntimeMetadata::V4(_)
-                        | m @ RuntimeMetadata::V5(_)
-                        | m @ RuntimeMetadata::V6(_)
-                        | m @ RuntimeMetadata::V7(_) => {
-                            return Err(OnlineClientAtBlockError::UnsupportedMetadataVersion {
+                // Allow our config to provide encoded metadata (eg from disk) before we go to the chain for it.
+                let cached_metadata = self
+                    .inner
+                    .config
+                    .encoded_metadata_for_spec_version(spec_version)
+                    .and_then(|bytes| RuntimeMetadataPrefixed::decode(&mut &*bytes).ok());
+                let runtime_metadata = match cached_metadata {
+                    Some(RuntimeMetadataPrefixed(_, metadata)) => metadata,
+                    None => {
+                        let metadata = get_metadata(&*self.inner.backend, block_hash).await?;
+                        let encoded_metadata = (frame_metadata::META_RESERVED, &metadata).encode();
+                        self.inner
+                            .config
+                            .set_encoded_metadata_for_spec_version(spec_version, &encoded_metadata);
+                        metadata
+                    }
+                };
+
+                let metadata: Metadata = match runtime_metadata {
+                    m @ RuntimeMetadata::V0(_)
+                    | m @ RuntimeMetadata::V1(_)
+                    | m @ RuntimeMetadata::V2(_)
+                    | m @ RuntimeMetadata::V3(_)
+                    | m @ RuntimeMetadata::V4(_)
+                    | m @ RuntimeMetadata::V5(_)
+                    | m @ RuntimeMetadata::V6(_)
+                    | m @ RuntimeMetadata::V7(_) => {
+                        return Err(OnlineClientAtBlockError::UnsupportedMetadataVersion {
+                            block_hash: block_hash.into(),
+                            version: m.version(),
+                        });
+                    }
+                    RuntimeMetadata::V8(m) => {
+                        let types = get_legacy_types(self, &m, spec_version)?;
+                        Metadata::from_v8(&m, &types).map_err(|e| {
+                            OnlineClientAtBlockError::CannotConvertLegacyMetadata {
                                 block_hash: block_hash.into(),
-                                version: m.version(),
-                            });
-                        }
-                        RuntimeMetadata::V8(m) => {
-                            let types = get_legacy_types(self, &m, spec_version)?;
-                            Metadata::from_v8(&m, &types).map_err(|e| {
-                                OnlineClientAtBlockError::CannotConvertLegacyMetadata {
-                                    block_hash: block_hash.into(),
-                                    metadata_version: 8,
-                                    reason: e,
-                                }
-                            })?
-                        }
-                        RuntimeMetadata::V9(m) => {
-                            let types = get_legacy_types(self, &m, spec_version)?;
-                            Metadata::from_v9(&m, &types).map_err(|e| {
-                                OnlineClientAtBlockError::CannotConvertLegacyMetadata {
-                                    block_hash: block_hash.into(),
-                                    metadata_version: 9,
-                                    reason: e,
-                                }
-                            })?
-                        }
-                        RuntimeMetadata::V10(m) => {
-                            let types = get_legacy_types(self, &m, spec_version)?;
-                            Metadata::from_v10(&m, &types).map_err(|e| {
-                                OnlineClientAtBlockError::CannotConvertLegacyMetadata {
-                                    block_hash: block_hash.into(),
-                                    metadata_version: 10,
-                                    reason: e,
-                                }
-                            })?
-                        }
-                        RuntimeMetadata::V11(m) => {
-                            let types = get_legacy_types(self, &m, spec_version)?;
-                            Metadata::from_v11(&m, &types).map_err(|e| {
-                                OnlineClientAtBlockError::CannotConvertLegacyMetadata {
-                                    block_hash: block_hash.into(),
-                                    metadata_version: 11,
-                                    reason: e,
-                                }
-                            })?
-                        }
-                        RuntimeMetadata::V12(m) => {
-                            let types = get_legacy_types(self, &m, spec_version)?;
-                            Metadata::from_v12(&m, &types).map_err(|e| {
-                                OnlineClientAtBlockError::CannotConvertLegacyMetadata {
-                                    block_hash: block_hash.into(),
-                                    metadata_version: 12,
-                                    reason: e,
-                                }
-                            })?
-                        }
-                        RuntimeMetadata::V13(m) => {
-                            let types = get_legacy_types(self, &m, spec_version)?;
-                            Metadata::from_v13(&m, &types).map_err(|e| {
-                                OnlineClientAtBlockError::CannotConvertLegacyMetadata {
-                                    block_hash: block_hash.into(),
-                                    metadata_version: 13,
-                                    reason: e,
-                                }
-                            })?
-                        }
-                        RuntimeMetadata::V14(m) => Metadata::from_v14(m).map_err(|e| {
-                            OnlineClientAtBlockError::CannotConvertModernMetadata {
+                                metadata_version: 8,
+                                reason: e,
+                            }
+                        })?
+                    }
+                    RuntimeMetadata::V9(m) => {
+                        let types = get_legacy_types(self, &m, spec_version)?;
+                        Metadata::from_v9(&m, &types).map_err(|e| {
+                            OnlineClientAtBlockError::CannotConvertLegacyMetadata {
                                 block_hash: block_hash.into(),
-                                metadata_version: 14,
+                                metadata_version: 9,
                                 reason: e,
                             }
-                        })?,
-                        RuntimeMetadata::V15(m) => Metadata::from_v15(m).map_err(|e| {
-                            OnlineClientAtBlockError::CannotConvertModernMetadata {
+                        })?
+                    }
+                    RuntimeMetadata::V10(m) => {
+                        let types = get_legacy_types(self, &m, spec_version)?;
+                        Metadata::from_v10(&m, &types).map_err(|e| {
+                            OnlineClientAtBlockError::CannotConvertLegacyMetadata {
                                 block_hash: block_hash.into(),
-                                metadata_version: 15,
+                                metadata_version: 10,
                                 reason: e,
                             }
-                        })?,
-                        RuntimeMetadata::V16(m) => Metadata::from_v16(m).map_err(|e| {
-                            OnlineClientAtBlockError::CannotConvertModernMetadata {
+                        })?
+                    }
+                    RuntimeMetadata::V11(m) => {
+                        let types = get_legacy_types(self, &m, spec_version)?;
+                        Metadata::from_v11(&m, &types).map_err(|e| {
+                            OnlineClientAtBlockError::CannotConvertLegacyMetadata {
                                 block_hash: block_hash.into(),
-                                metadata_version: 16,
+                                metadata_version: 11,
                                 reason: e,
                             }
-                        })?,
-                    };
+                        })?
+                    }
+                    RuntimeMetadata::V12(m) => {
+                        let types = get_legacy_types(self, &m, spec_version)?;
+                        Metadata::from_v12(&m, &types).map_err(|e| {
+                            OnlineClientAtBlockError::CannotConvertLegacyMetadata {
+                                block_hash: block_hash.into(),
+                                metadata_version: 12,
+                                reason: e,
+                            }
+                        })?
+                    }
+                    RuntimeMetadata::V13(m) => {
+                        let types = get_legacy_types(self, &m, spec_version)?;
+                        Metadata::from_v13(&m, &types).map_err(|e| {
+                            OnlineClientAtBlockError::CannotConvertLegacyMetadata {
+                                block_hash: block_hash.into(),
+                                metadata_version: 13,
+                                reason: e,
+                            }
+                        })?
+                    }
+                    RuntimeMetadata::V14(m) => Metadata::from_v14(m).map_err(|e| {
+                        OnlineClientAtBlockError::CannotConvertModernMetadata {
+                            block_hash: block_hash.into(),
+                            metadata_version: 14,
+                            reason: e,
+                        }
+                    })?,
+                    RuntimeMetadata::V15(m) => Metadata::from_v15(m).map_err(|e| {
+                        OnlineClientAtBlockError::CannotConvertModernMetadata {
+                            block_hash: block_hash.into(),
+                            metadata_version: 15,
+                            reason: e,
+                        }
+                    })?,
+                    RuntimeMetadata::V16(m) => Metadata::from_v16(m).map_err(|e| {
+                        OnlineClientAtBlockError::CannotConvertModernMetadata {
+                            block_hash: block_hash.into(),
+                            metadata_version: 16,
+                            reason: e,
+                        }
+                    })?,
+                };
                 let metadata = Arc::new(metadata);
                 self.inner
                     .config
@@ -601,6 +659,14 @@ fn get_legacy_types<'a, T: Config, Md: ToTypeRegistry>(
     Ok(types)
 }
 
+/// Decode the spec and transaction versions from the bytes returned by the `Core_version` Runtime API.
+fn decode_spec_and_transaction_version(
+    spec_version_bytes: &[u8],
+) -> Result<(u32, u32), codec::Error> {
+    crate::storage::decode_runtime_version(spec_version_bytes)
+        .map(|version| (version.spec_version, version.transaction_version))
+}
+
 async fn get_metadata<T: Config>(
     backend: &dyn Backend<T>,
     block_hash: HashFor<T>,
@@ -1,8 +1,9 @@
 use crate::backend::{BlockRef, StreamOfResults};
 use crate::client::{ClientAtBlock, OnlineClient, OnlineClientAtBlockImpl};
 use crate::config::{Config, HashFor, Header};
-use crate::error::{BlocksError, OnlineClientAtBlockError};
+use crate::error::{BlockAuthorError, BlocksError, OnlineClientAtBlockError};
 use futures::{Stream, StreamExt};
+use scale_decode::DecodeAsType;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
@@ -80,4 +81,110 @@ impl<T: Config> Block<T> {
     ) -> Result<ClientAtBlock<T, OnlineClientAtBlockImpl<T>>, OnlineClientAtBlockError> {
         self.client.at_block(self.block_ref.clone()).await
     }
+
+    /// Find the account ID of the author of this block.
+    ///
+    /// This looks for a BABE or Aura pre-runtime digest in the block header to work out which
+    /// authority produced the block, and then maps this to an account using the
+    /// `Session.Validators` storage entry at this block, falling back to `Babe.Authorities` or
+    /// `Aura.Authorities` if there is no `Session` pallet.
+    ///
+    /// `None` is returned if the header contains no BABE or Aura pre-runtime digest, or if the
+    /// authority cannot be mapped to an account.
+    pub async fn author(&self) -> Result<Option<T::AccountId>, BlockAuthorError> {
+        let Some(digest) = self.block_header.digest() else {
+            return Ok(None);
+        };
+
+        let babe_pre_digest = digest
+            .babe_pre_digest()
+            .transpose()
+            .map_err(BlockAuthorError::CannotDecodeDigest)?;
+        let aura_pre_digest = digest
+            .aura_pre_digest()
+            .transpose()
+            .map_err(BlockAuthorError::CannotDecodeDigest)?;
+
+        if babe_pre_digest.is_none() && aura_pre_digest.is_none() {
+            return Ok(None);
+        }
+
+        let client_at_block = self
+            .at()
+            .await
+            .map_err(BlockAuthorError::CannotInstantiateClientAtBlock)?;
+
+        let validators: Option<Vec<T::AccountId>> =
+            fetch_plain_value(&client_at_block, "Session", "Validators").await?;
+
+        if let Some(babe_pre_digest) = babe_pre_digest {
+            // BABE tells us the index of the authority directly.
+            let index = babe_pre_digest.authority_index() as usize;
+            let author = match validators {
+                Some(validators) => validators.into_iter().nth(index),
+                None => {
+                    let authorities: Option<Vec<(T::AccountId, u64)>> =
+                        fetch_plain_value(&client_at_block, "Babe", "Authorities").await?;
+                    authorities
+                        .and_then(|a| a.into_iter().nth(index))
+                        .map(|(account_id, _weight)| account_id)
+                }
+            };
+            return Ok(author);
+        }
+
+        if let Some(aura_pre_digest) = aura_pre_digest {
+            // Aura authors are assigned round-robin, so we work out the author from the slot.
+            let authorities = match validators {
+                Some(validators) => Some(validators),
+                None => fetch_plain_value(&client_at_block, "Aura", "Authorities").await?,
+            };
+            let author = authorities.and_then(|authorities| {
+                if authorities.is_empty() {
+                    return None;
+                }
+                let index = (aura_pre_digest.slot % authorities.len() as u64) as usize;
+                authorities.into_iter().nth(index)
+            });
+            return Ok(author);
+        }
+
+        Ok(None)
+    }
+}
+
+/// Fetch and decode some plain storage value, returning `None` if it doesn't exist in the metadata.
+async fn fetch_plain_value<T: Config, V: DecodeAsType>(
+    client_at_block: &ClientAtBlock<T, OnlineClientAtBlockImpl<T>>,
+    pallet_name: &'static str,
+    entry_name: &'static str,
+) -> Result<Option<V>, BlockAuthorError> {
+    let entry_exists = client_at_block
+        .metadata_ref()
+        .pallet_by_name(pallet_name)
+        .and_then(|p| p.storage())
+        .and_then(|s| s.entry_by_name(entry_name))
+        .is_some();
+    if !entry_exists {
+        return Ok(None);
+    }
+
+    let address = crate::storage::dynamic::<(), V>(pallet_name, entry_name);
+    let value = client_at_block
+        .storage()
+        .fetch(address, ())
+        .await
+        .map_err(|error| BlockAuthorError::CannotFetchAuthorities {
+            pallet_name,
+            entry_name,
+            error,
+        })?
+        .decode()
+        .map_err(|error| BlockAuthorError::CannotDecodeAuthorities {
+            pallet_name,
+            entry_name,
+            error,
+        })?;
+
+    Ok(Some(value))
 }
@@ -0,0 +1,151 @@
+use crate::backend::{RuntimeVersionChange, StreamOfResults};
+use crate::client::OnlineClient;
+use crate::config::{Config, HashFor};
+use crate::error::RuntimeUpgradesError;
+use crate::metadata::{ArcMetadata, MetadataDiff};
+use crate::storage::RuntimeVersion;
+use futures::{Stream, StreamExt};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A stream of runtime upgrades. This is created by calling
+/// [`OnlineClient::subscribe_runtime_upgrades()`].
+pub struct RuntimeUpgrades<T: Config> {
+    stream: Pin<Box<dyn Stream<Item = Result<RuntimeUpgrade<T>, RuntimeUpgradesError>> + Send>>,
+}
+
+struct RuntimeUpgradesState<T: Config> {
+    client: OnlineClient<T>,
+    versions: StreamOfResults<RuntimeVersionChange<HashFor<T>>>,
+    // The current runtime version and metadata, once the first version has been seen.
+    current: Option<(RuntimeVersion, ArcMetadata)>,
+}
+
+impl<T: Config> RuntimeUpgrades<T> {
+    pub(crate) async fn new(client: OnlineClient<T>) -> Result<Self, RuntimeUpgradesError> {
+        // We need a hasher to know how to hash things. Thus, we need metadata to instantiate
+        // the hasher, so let's use the current block.
+        let current_block = client
+            .at_current_block()
+            .await
+            .map_err(RuntimeUpgradesError::CannotGetCurrentBlock)?;
+        let hasher = current_block.client.hasher.clone();
+
+        let versions = client
+            .inner
+            .backend
+            .stream_finalized_runtime_versions(hasher)
+            .await
+            .map_err(RuntimeUpgradesError::CannotGetRuntimeVersionStream)?;
+
+        let state = RuntimeUpgradesState {
+            client,
+            versions,
+            current: None,
+        };
+
+        let stream = futures::stream::try_unfold(state, |mut state| async move {
+            while let Some(change) = state.versions.next().await {
+                let RuntimeVersionChange { block_ref, version } =
+                    change.map_err(RuntimeUpgradesError::CannotGetRuntimeVersion)?;
+
+                // The same version can be handed back more than once, so ignore it if so.
+                let is_same_spec_version = state
+                    .current
+                    .as_ref()
+                    .is_some_and(|(current, _)| current.spec_version == version.spec_version);
+                if is_same_spec_version {
+                    continue;
+                }
+
+                let at_block = state
+                    .client
+                    .at_block(block_ref)
+                    .await
+                    .map_err(RuntimeUpgradesError::CannotInstantiateClientAtBlock)?;
+                let metadata = at_block.metadata();
+
+                // The first version handed back is the one we're starting from.
+                let Some((old_version, old_metadata)) =
+                    state.current.replace((version.clone(), metadata.clone()))
+                else {
+                    continue;
+                };
+
+                let upgrade = RuntimeUpgrade {
+                    block_number: at_block.block_number(),
+                    block_hash: at_block.block_hash(),
+                    old_version,
+                    new_version: version,
+                    diff: old_metadata.diff(&metadata),
+                    metadata,
+                };
+                return Ok(Some((upgrade, state)));
+            }
+            Ok(None)
+        });
+
+        Ok(RuntimeUpgrades {
+            stream: Box::pin(stream),
+        })
+    }
+
+    /// Return the next runtime upgrade when one is finalized.
+    pub async fn next(&mut self) -> Option<Result<RuntimeUpgrade<T>, RuntimeUpgradesError>> {
+        StreamExt::next(self).await
+    }
+}
+
+impl<T: Config> std::marker::Unpin for RuntimeUpgrades<T> {}
+impl<T: Config> Stream for RuntimeUpgrades<T> {
+    type Item = Result<RuntimeUpgrade<T>, RuntimeUpgradesError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream.poll_next_unpin(cx)
+    }
+}
+
+/// A runtime upgrade, found by noticing that the spec version of some finalized block
+/// differs from that of the previous finalized block.
+#[derive(Debug, Clone)]
+pub struct RuntimeUpgrade<T: Config> {
+    block_number: u64,
+    block_hash: HashFor<T>,
+    old_version: RuntimeVersion,
+    new_version: RuntimeVersion,
+    metadata: ArcMetadata,
+    diff: MetadataDiff,
+}
+
+impl<T: Config> RuntimeUpgrade<T> {
+    /// The number of the first block whose state contains the new runtime.
+    pub fn block_number(&self) -> u64 {
+        self.block_number
+    }
+
+    /// The hash of the first block whose state contains the new runtime.
+    pub fn block_hash(&self) -> HashFor<T> {
+        self.block_hash
+    }
+
+    /// The version of the runtime prior to the upgrade.
+    pub fn old_version(&self) -> &RuntimeVersion {
+        &self.old_version
+    }
+
+    /// The version of the runtime after the upgrade.
+    pub fn new_version(&self) -> &RuntimeVersion {
+        &self.new_version
+    }
+
+    /// The metadata of the new runtime.
+    pub fn metadata(&self) -> ArcMetadata {
+        self.metadata.clone()
+    }
+
+    /// The pallets, calls, storage entries, constants, events and runtime APIs which
+    /// were added, removed or changed between the old and new runtime.
+    pub fn diff(&self) -> &MetadataDiff {
+        &self.diff
+    }
+}
@@ -0,0 +1,213 @@
+use super::decode_spec_and_transaction_version;
+use crate::client::OnlineClient;
+use crate::config::Config;
+use crate::config::substrate::SpecVersionForRange;
+use crate::error::SpecVersionDiscoveryError;
+use futures::StreamExt;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// The number of blocks whose versions we'll ask for at once, if the backend has no limit.
+const DEFAULT_MAX_CONCURRENT_CALLS: usize = 16;
+
+/// Binary search the given range of blocks to find each block at which the spec or
+/// transaction version changes, and return the range of blocks for each version.
+pub(crate) async fn discover_spec_versions<T: Config>(
+    client: &OnlineClient<T>,
+    block_range: Range<u64>,
+) -> Result<Vec<SpecVersionForRange>, SpecVersionDiscoveryError> {
+    if block_range.is_empty() {
+        return Err(SpecVersionDiscoveryError::EmptyBlockRange {
+            start: block_range.start,
+            end: block_range.end,
+        });
+    }
+
+    let max_concurrent_calls = client
+        .inner
+        .backend
+        .max_concurrent_calls()
+        .unwrap_or(DEFAULT_MAX_CONCURRENT_CALLS)
+        .max(1);
+
+    find_version_ranges(block_range, max_concurrent_calls, |block_number| {
+        versions_at(client, block_number)
+    })
+    .await
+}
+
+// Find the ranges of blocks which share the same versions, given some way to look up the
+// spec and transaction versions at a block number. The block range must not be empty.
+async fn find_version_ranges<F, Fut, E>(
+    block_range: Range<u64>,
+    max_concurrent_calls: usize,
+    versions_at: F,
+) -> Result<Vec<SpecVersionForRange>, E>
+where
+    F: Fn(u64) -> Fut,
+    Fut: Future<Output = Result<(u32, u32), E>>,
+{
+    // The versions at each block number we've looked at so far.
+    let mut versions = BTreeMap::new();
+    let mut to_check = vec![block_range.start, block_range.end - 1];
+    to_check.dedup();
+
+    while !to_check.is_empty() {
+        let mut found = futures::stream::iter(to_check)
+            .map(|block_number| {
+                let version = versions_at(block_number);
+                async move { Ok::<_, E>((block_number, version.await?)) }
+            })
+            .buffer_unordered(max_concurrent_calls);
+
+        while let Some(res) = found.next().await {
+            let (block_number, version) = res?;
+            versions.insert(block_number, version);
+        }
+
+        to_check = blocks_to_check(&versions);
+    }
+
+    Ok(to_ranges(&versions, block_range.end))
+}
+
+// Return the spec and transaction versions at some block number.
+async fn versions_at<T: Config>(
+    client: &OnlineClient<T>,
+    block_number: u64,
+) -> Result<(u32, u32), SpecVersionDiscoveryError> {
+    let backend = &client.inner.backend;
+    let block_hash = backend
+        .block_number_to_hash(block_number)
+        .await
+        .map_err(|e| SpecVersionDiscoveryError::CannotGetBlockHash {
+            block_number,
+            reason: e,
+        })?
+        .ok_or(SpecVersionDiscoveryError::BlockNotFound { block_number })?
+        .hash();
+
+    let version_bytes = backend
+        .call("Core_version", None, block_hash)
+        .await
+        .map_err(|e| SpecVersionDiscoveryError::CannotGetSpecVersion {
+            block_number,
+            reason: e,
+        })?;
+
+    decode_spec_and_transaction_version(&version_bytes).map_err(|e| {
+        SpecVersionDiscoveryError::CannotDecodeSpecVersion {
+            block_number,
+            reason: e,
+        }
+    })
+}
+
+// Versions only ever increase, so if two blocks have the same versions then so does every
+// block in between them. Otherwise, we need to look at the block halfway between them,
+// unless they are adjacent (in which case we've found a block at which the versions change).
+fn blocks_to_check(versions: &BTreeMap<u64, (u32, u32)>) -> Vec<u64> {
+    versions
+        .iter()
+        .zip(versions.iter().skip(1))
+        .filter(|((a, a_version), (b, b_version))| a_version != b_version && *b - *a > 1)
+        .map(|((a, _), (b, _))| a + (b - a) / 2)
+        .collect()
+}
+
+// Turn the versions found at each block into ranges of blocks that share the same versions.
+fn to_ranges(versions: &BTreeMap<u64, (u32, u32)>, end: u64) -> Vec<SpecVersionForRange> {
+    let mut ranges: Vec<SpecVersionForRange> = Vec::new();
+    for (&block_number, &(spec_version, transaction_version)) in versions {
+        if let Some(last) = ranges.last_mut() {
+            if last.spec_version == spec_version && last.transaction_version == transaction_version
+            {
+                continue;
+            }
+            last.block_range.end = block_number;
+        }
+        ranges.push(SpecVersionForRange {
+            block_range: block_number..end,
+            spec_version,
+            transaction_version,
+        });
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // Find the version ranges using `versions_at`, returning them along with the number of
+    // times that `versions_at` was called.
+    async fn discover(
+        block_range: Range<u64>,
+        versions_at: impl Fn(u64) -> (u32, u32),
+    ) -> (Vec<SpecVersionForRange>, usize) {
+        let calls = AtomicUsize::new(0);
+        let ranges = find_version_ranges(block_range, 4, |n| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            std::future::ready(Ok::<_, ()>(versions_at(n)))
+        })
+        .await
+        .unwrap();
+        (ranges, calls.into_inner())
+    }
+
+    fn range(
+        block_range: Range<u64>,
+        spec_version: u32,
+        transaction_version: u32,
+    ) -> SpecVersionForRange {
+        SpecVersionForRange {
+            block_range,
+            spec_version,
+            transaction_version,
+        }
+    }
+
+    #[tokio::test]
+    async fn finds_each_version_change() {
+        let versions_at = |n: u64| match n {
+            0..1_000 => (1, 1),
+            1_000..1_001 => (2, 1),
+            1_001..50_000 => (2, 2),
+            _ => (5, 2),
+        };
+
+        let (ranges, calls) = discover(0..100_000, versions_at).await;
+        assert_eq!(
+            ranges,
+            vec![
+                range(0..1_000, 1, 1),
+                range(1_000..1_001, 2, 1),
+                range(1_001..50_000, 2, 2),
+                range(50_000..100_000, 5, 2),
+            ]
+        );
+        // We should need far fewer calls than there are blocks.
+        assert!(calls < 100);
+    }
+
+    #[tokio::test]
+    async fn handles_a_single_version() {
+        let (ranges, calls) = discover(10..20, |_| (3, 4)).await;
+        assert_eq!(ranges, vec![range(10..20, 3, 4)]);
+        assert_eq!(calls, 2);
+
+        let (ranges, calls) = discover(10..11, |_| (3, 4)).await;
+        assert_eq!(ranges, vec![range(10..11, 3, 4)]);
+        assert_eq!(calls, 1);
+    }
+
+    #[tokio::test]
+    async fn lookup_errors_are_returned() {
+        let res = find_version_ranges(0..100, 4, |n| {
+            std::future::ready(if n == 99 { Err(n) } else { Ok((1, 1)) })
+        })
+        .await;
+        assert_eq!(res, Err(99));
+    }
+}
@@ -0,0 +1,277 @@
+use super::{OfflineClientAtBlockImpl, OfflineClientAtBlockT, OnlineClientAtBlockT};
+use crate::backend::{
+    StorageFetchDescendantsOptions, StorageHashResponse, StorageResponse, StreamOf, StreamOfResults,
+};
+use crate::config::{Config, HashFor, Hasher};
+use crate::error::BackendError;
+use crate::metadata::{ArcMetadata, Metadata};
+use crate::storage::StorageSnapshot;
+use std::future::Future;
+use std::sync::Arc;
+
+/// This represents a client at a specific block which is able to fetch storage values,
+/// either from a node or from some [`StorageSnapshot`].
+#[doc(hidden)]
+pub trait StorageClientAtBlockT<T: Config>: OfflineClientAtBlockT<T> {
+    /// Fetch the values at each of the given keys. Keys with no value are skipped.
+    fn storage_fetch_values(
+        &self,
+        keys: Vec<Vec<u8>>,
+    ) -> impl Future<Output = Result<StreamOfResults<StorageResponse>, BackendError>>;
+    /// Fetch the values underneath the given key.
+    fn storage_fetch_descendant_values(
+        &self,
+        key: Vec<u8>,
+        options: StorageFetchDescendantsOptions,
+    ) -> impl Future<Output = Result<StreamOfResults<StorageResponse>, BackendError>>;
+    /// Fetch the hashes of the values at each of the given keys. Keys with no value are skipped.
+    fn storage_fetch_hashes(
+        &self,
+        keys: Vec<Vec<u8>>,
+    ) -> impl Future<Output = Result<StreamOfResults<StorageHashResponse<HashFor<T>>>, BackendError>>;
+    /// Fetch the Merkle value of the closest descendant of the given key.
+    fn storage_closest_descendant_merkle_value(
+        &self,
+        key: Vec<u8>,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, BackendError>>;
+    /// Fetch the values at each of the given keys in the given default child trie.
+    fn storage_fetch_child_values(
+        &self,
+        child_key: Vec<u8>,
+        keys: Vec<Vec<u8>>,
+    ) -> impl Future<Output = Result<StreamOfResults<StorageResponse>, BackendError>>;
+    /// Fetch the values underneath the given key in the given default child trie.
+    fn storage_fetch_child_descendant_values(
+        &self,
+        child_key: Vec<u8>,
+        key: Vec<u8>,
+        options: StorageFetchDescendantsOptions,
+    ) -> impl Future<Output = Result<StreamOfResults<StorageResponse>, BackendError>>;
+}
+
+// Online clients fetch storage from the node at the current block.
+impl<T: Config, Client: OnlineClientAtBlockT<T>> StorageClientAtBlockT<T> for Client {
+    fn storage_fetch_values(
+        &self,
+        keys: Vec<Vec<u8>>,
+    ) -> impl Future<Output = Result<StreamOfResults<StorageResponse>, BackendError>> {
+        self.backend()
+            .storage_fetch_values(keys, self.block_ref().hash())
+    }
+
+    fn storage_fetch_descendant_values(
+        &self,
+        key: Vec<u8>,
+        options: StorageFetchDescendantsOptions,
+    ) -> impl Future<Output = Result<StreamOfResults<StorageResponse>, BackendError>> {
+        self.backend().storage_fetch_descendant_values_with_options(
+            key,
+            options,
+            self.block_ref().hash(),
+        )
+    }
+
+    fn storage_fetch_hashes(
+        &self,
+        keys: Vec<Vec<u8>>,
+    ) -> impl Future<Output = Result<StreamOfResults<StorageHashResponse<HashFor<T>>>, BackendError>>
+    {
+        self.backend()
+            .storage_fetch_hashes(keys, self.block_ref().hash())
+    }
+
+    fn storage_closest_descendant_merkle_value(
+        &self,
+        key: Vec<u8>,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, BackendError>> {
+        self.backend()
+            .storage_closest_descendant_merkle_value(key, self.block_ref().hash())
+    }
+
+    fn storage_fetch_child_values(
+        &self,
+        child_key: Vec<u8>,
+        keys: Vec<Vec<u8>>,
+    ) -> impl Future<Output = Result<StreamOfResults<StorageResponse>, BackendError>> {
+        self.backend()
+            .storage_fetch_child_values(child_key, keys, self.block_ref().hash())
+    }
+
+    fn storage_fetch_child_descendant_values(
+        &self,
+        child_key: Vec<u8>,
+        key: Vec<u8>,
+        options: StorageFetchDescendantsOptions,
+    ) -> impl Future<Output = Result<StreamOfResults<StorageResponse>, BackendError>> {
+        self.backend().storage_fetch_child_descendant_values(
+            child_key,
+            key,
+            options,
+            self.block_ref().hash(),
+        )
+    }
+}
+
+/// An implementation of the [`OfflineClientAtBlockT`] and [`StorageClientAtBlockT`] traits,
+/// which serves storage requests from a [`StorageSnapshot`]. This is constructed by calling
+/// [`crate::client::OfflineClientAtBlock::with_storage_snapshot()`]. You won't tend to need this
+/// type and instead should prefer to refer to [`crate::client::SnapshotClientAtBlock`].
+#[derive(Debug, Clone)]
+pub struct SnapshotClientAtBlockImpl<T: Config> {
+    client: OfflineClientAtBlockImpl<T>,
+    snapshot: Arc<StorageSnapshot>,
+}
+
+impl<T: Config> SnapshotClientAtBlockImpl<T> {
+    pub(crate) fn new(client: OfflineClientAtBlockImpl<T>, snapshot: Arc<StorageSnapshot>) -> Self {
+        SnapshotClientAtBlockImpl { client, snapshot }
+    }
+
+    /// The storage snapshot that's being used.
+    pub fn snapshot(&self) -> &StorageSnapshot {
+        &self.snapshot
+    }
+}
+
+impl<T: Config> StorageClientAtBlockT<T> for SnapshotClientAtBlockImpl<T> {
+    async fn storage_fetch_values(
+        &self,
+        keys: Vec<Vec<u8>>,
+    ) -> Result<StreamOfResults<StorageResponse>, BackendError> {
+        let values = self.snapshot.values_at(&keys);
+        let stream = futures::stream::iter(values.into_iter().map(Ok));
+        Ok(StreamOf::new(Box::pin(stream)))
+    }
+
+    async fn storage_fetch_descendant_values(
+        &self,
+        key: Vec<u8>,
+        options: StorageFetchDescendantsOptions,
+    ) -> Result<StreamOfResults<StorageResponse>, BackendError> {
+        // We have everything in memory already, so there's no need for pages.
+        let values: Vec<_> = self
+            .snapshot
+            .iter_prefix_after(&key, options.start_after.as_deref())
+            .map(|(key, value)| {
+                Ok(StorageResponse {
+                    key: key.to_vec(),
+                    value: value.to_vec(),
+                })
+            })
+            .collect();
+        let stream = futures::stream::iter(values);
+        Ok(StreamOf::new(Box::pin(stream)))
+    }
+
+    async fn storage_fetch_hashes(
+        &self,
+        keys: Vec<Vec<u8>>,
+    ) -> Result<StreamOfResults<StorageHashResponse<HashFor<T>>>, BackendError> {
+        let hasher = self.hasher();
+        let hashes: Vec<_> = self
+            .snapshot
+            .values_at(&keys)
+            .into_iter()
+            .map(|res| {
+                Ok(StorageHashResponse {
+                    hash: hasher.hash(&res.value),
+                    key: res.key,
+                })
+            })
+            .collect();
+        let stream = futures::stream::iter(hashes);
+        Ok(StreamOf::new(Box::pin(stream)))
+    }
+
+    async fn storage_closest_descendant_merkle_value(
+        &self,
+        _key: Vec<u8>,
+    ) -> Result<Option<Vec<u8>>, BackendError> {
+        Err(BackendError::other(
+            "Storage snapshots cannot compute closest descendant Merkle values",
+        ))
+    }
+
+    async fn storage_fetch_child_values(
+        &self,
+        _child_key: Vec<u8>,
+        _keys: Vec<Vec<u8>>,
+    ) -> Result<StreamOfResults<StorageResponse>, BackendError> {
+        Err(BackendError::other(
+            "Storage snapshots do not contain child tries",
+        ))
+    }
+
+    async fn storage_fetch_child_descendant_values(
+        &self,
+        _child_key: Vec<u8>,
+        _key: Vec<u8>,
+        _options: StorageFetchDescendantsOptions,
+    ) -> Result<StreamOfResults<StorageResponse>, BackendError> {
+        Err(BackendError::other(
+            "Storage snapshots do not contain child tries",
+        ))
+    }
+}
+
+impl<T: Config> OfflineClientAtBlockT<T> for SnapshotClientAtBlockImpl<T> {
+    fn metadata_ref(&self) -> &Metadata {
+        self.client.metadata_ref()
+    }
+    fn metadata(&self) -> ArcMetadata {
+        self.client.metadata()
+    }
+    fn block_number(&self) -> u64 {
+        self.client.block_number()
+    }
+    fn genesis_hash(&self) -> Option<HashFor<T>> {
+        self.client.genesis_hash()
+    }
+    fn spec_version(&self) -> u32 {
+        self.client.spec_version()
+    }
+    fn transaction_version(&self) -> u32 {
+        self.client.transaction_version()
+    }
+    fn hasher(&self) -> &T::Hasher {
+        self.client.hasher()
+    }
+}
+
+/// Snapshot client related test utilities used outside this module.
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use super::*;
+    use crate::client::{OfflineClient, SnapshotClientAtBlock};
+    use crate::config::SubstrateConfig;
+    use crate::config::substrate::SpecVersionForRange;
+
+    /// The spec version that [`snapshot_client`] reports.
+    pub const SPEC_VERSION: u32 = 1;
+
+    /// Configuration for a chain whose blocks all use the `polkadot_metadata_small.scale`
+    /// metadata and have the spec version [`SPEC_VERSION`].
+    pub fn config() -> SubstrateConfig {
+        let bytes = std::fs::read("../artifacts/polkadot_metadata_small.scale")
+            .expect("metadata artifact should exist");
+        let metadata = Metadata::decode_from(&bytes).expect("metadata should decode");
+
+        SubstrateConfig::builder()
+            .set_spec_version_for_block_ranges([SpecVersionForRange {
+                block_range: 0..u64::MAX,
+                spec_version: SPEC_VERSION,
+                transaction_version: 1,
+            }])
+            .set_metadata_for_spec_versions([(SPEC_VERSION, metadata.arc())])
+            .build()
+    }
+
+    /// Build a client at block 0 of a chain using the [`config()`] above, which serves storage
+    /// from the given snapshot.
+    pub fn snapshot_client(snapshot: StorageSnapshot) -> SnapshotClientAtBlock<SubstrateConfig> {
+        OfflineClient::new_with_config(config())
+            .at_block(0u64)
+            .expect("block 0 should be available")
+            .with_storage_snapshot(snapshot)
+    }
+}
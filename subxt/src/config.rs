@@ -46,6 +46,7 @@
 //! the problem might be.
 
 mod default_transaction_extensions;
+mod metadata_disk_cache;
 mod transaction_extension_traits;
 
 pub mod polkadot;
@@ -60,11 +61,13 @@ use scale_encode::EncodeAsType;
 use scale_info_legacy::TypeRegistrySet;
 use serde::{Serialize, de::DeserializeOwned};
 use std::{fmt::Display, marker::PhantomData};
+use substrate::SpecVersionForRange;
 use subxt_rpcs::RpcConfig;
 
 pub use default_transaction_extensions::{
     DefaultExtrinsicParamsBuilder, DefaultTransactionExtensions,
 };
+pub use metadata_disk_cache::MetadataDiskCache;
 pub use polkadot::{PolkadotConfig, PolkadotExtrinsicParams, PolkadotExtrinsicParamsBuilder};
 pub use substrate::{SubstrateConfig, SubstrateExtrinsicParams, SubstrateExtrinsicParamsBuilder};
 pub use transaction_extension_traits::{ClientState, TransactionExtension, TransactionExtensions};
@@ -131,6 +134,28 @@ pub trait Config: Clone + Debug + Sized + Send + Sync + 'static {
     /// do nothing if it prefers.
     fn set_metadata_for_spec_version(&self, _spec_version: u32, _metadata: ArcMetadata) {}
 
+    /// Return SCALE encoded metadata (ie a [`frame_metadata::RuntimeMetadataPrefixed`]) for a given spec
+    /// version, if available. Unlike [`Config::metadata_for_spec_version`], this can be used to cache metadata
+    /// somewhere that the [`Metadata`] type cannot be stored directly, for instance on disk.
+    ///
+    /// The [`crate::client::OnlineClient`] will check this if [`Config::metadata_for_spec_version`] returns
+    /// `None`, before looking the metadata up on chain and calling [`Config::set_encoded_metadata_for_spec_version`].
+    fn encoded_metadata_for_spec_version(&self, _spec_version: u32) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Set some SCALE encoded metadata for a given spec version. The [`crate::client::OnlineClient`] will call
+    /// this if it has to retrieve metadata from the chain, to give this the opportunity to cache it. The
+    /// configuration can do nothing if it prefers.
+    fn set_encoded_metadata_for_spec_version(&self, _spec_version: u32, _encoded_metadata: &[u8]) {}
+
+    /// Cache the spec and transaction versions for some ranges of blocks. The [`crate::client::OnlineClient`]
+    /// will call this after finding them via [`crate::client::OnlineClient::discover_spec_versions`], to give
+    /// this the opportunity to store them somewhere for next time, for instance on disk. This does not change
+    /// the versions that [`Config::spec_and_transaction_version_for_block_number`] hands back. The configuration
+    /// can do nothing if it prefers.
+    fn cache_spec_version_for_block_ranges(&self, _ranges: &[SpecVersionForRange]) {}
+
     /// Return legacy types (ie types to use with Runtimes that return pre-V14 metadata) for a given spec version.
     /// If this returns `None`, [`subxt`](crate) will return an error if type definitions are needed to access some older
     /// block.
@@ -218,4 +243,10 @@ pub trait Hasher: Debug + Clone + Send + Sync + 'static {
 pub trait Header: Sized + Encode + Decode + Debug + Sync + Send + DeserializeOwned + Clone {
     /// Return the block number of this header.
     fn number(&self) -> u64;
+
+    /// Return the digest of this header, if it is in the standard Substrate format. This is
+    /// used to find things like the author of a block. By default, `None` is returned.
+    fn digest(&self) -> Option<&substrate::Digest> {
+        None
+    }
 }
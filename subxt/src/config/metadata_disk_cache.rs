@@ -0,0 +1,438 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+//! A cache which persists metadata and spec version information to disk, so
+//! that it doesn't need to be downloaded again each time a process starts.
+
+use super::substrate::SpecVersionForRange;
+use crate::error::MetadataDiskCacheError;
+use codec::{Decode, Encode};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Every cache file begins with these bytes, followed by a blake2_256 checksum of the
+/// remaining bytes, so that we can spot truncated or otherwise corrupted files. Next is
+/// a little endian `u64` sequence number recording the order in which files were written.
+const MAGIC: &[u8; 8] = b"subxtmc2";
+const CHECKSUM_LEN: usize = 32;
+const SEQUENCE_LEN: usize = 8;
+const HEADER_LEN: usize = MAGIC.len() + CHECKSUM_LEN + SEQUENCE_LEN;
+
+/// The default maximum size of a single metadata entry (64 MiB).
+const DEFAULT_MAX_ENTRY_SIZE: u64 = 64 * 1024 * 1024;
+/// The default maximum size of all of the metadata stored for one chain (1 GiB).
+const DEFAULT_MAX_TOTAL_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// A cache which stores SCALE encoded metadata for each spec version, and the block ranges that
+/// each spec version applies to, on disk. Each chain is stored in a separate directory keyed by
+/// its genesis hash (see [`MetadataDiskCache::for_chain()`]), so the same root directory can be
+/// shared between chains.
+///
+/// Every file written is checksummed, and corrupted files are ignored. The amount of metadata
+/// stored for a chain is limited; when the limit is exceeded, the least recently written
+/// metadata is removed.
+///
+/// Hand this to [`crate::config::SubstrateConfigBuilder::set_metadata_disk_cache()`] (or the
+/// equivalent on [`crate::config::PolkadotConfigBuilder`]) to use it, in which case the genesis
+/// hash given to the builder is used to pick the directory for the chain. Alternately, implement
+/// [`crate::config::Config::encoded_metadata_for_spec_version()`] and
+/// [`crate::config::Config::set_encoded_metadata_for_spec_version()`] on a custom configuration
+/// to call into it.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use subxt::config::{MetadataDiskCache, SubstrateConfig};
+/// use subxt::config::substrate::H256;
+///
+/// let genesis_hash: H256 = "0x91b171bb158e2d3848fa23a9f1c25182fb8e20313b2c1eb49219da7a70ce90c3"
+///     .parse()
+///     .unwrap();
+///
+/// let config = SubstrateConfig::builder()
+///     .set_genesis_hash(genesis_hash)
+///     .set_metadata_disk_cache(MetadataDiskCache::new("./metadata-cache"))
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct MetadataDiskCache {
+    root_dir: PathBuf,
+    dir: PathBuf,
+    max_entry_size: u64,
+    max_total_size: u64,
+}
+
+impl MetadataDiskCache {
+    /// Create a new cache which stores files in `root_dir`, which will be created if it doesn't
+    /// exist. Use [`MetadataDiskCache::for_chain()`] to store the files for some chain in a
+    /// subdirectory of this; configuration builders do this automatically.
+    pub fn new(root_dir: impl Into<PathBuf>) -> Self {
+        let root_dir = root_dir.into();
+        MetadataDiskCache {
+            dir: root_dir.clone(),
+            root_dir,
+            max_entry_size: DEFAULT_MAX_ENTRY_SIZE,
+            max_total_size: DEFAULT_MAX_TOTAL_SIZE,
+        }
+    }
+
+    /// Store files for the chain with the given genesis hash in a subdirectory of the root
+    /// directory, so that the root directory can be shared between chains.
+    pub fn for_chain(mut self, genesis_hash: impl AsRef<[u8]>) -> Self {
+        self.dir = self
+            .root_dir
+            .join(format!("0x{}", hex::encode(genesis_hash.as_ref())));
+        self
+    }
+
+    /// Set the maximum size, in bytes, of a single metadata entry. Larger entries will not be
+    /// written, and larger files will not be read. Defaults to 64 MiB.
+    pub fn max_entry_size(mut self, max_entry_size: u64) -> Self {
+        self.max_entry_size = max_entry_size;
+        self
+    }
+
+    /// Set the maximum size, in bytes, of all of the metadata stored for this chain. Defaults to 1 GiB.
+    pub fn max_total_size(mut self, max_total_size: u64) -> Self {
+        self.max_total_size = max_total_size;
+        self
+    }
+
+    /// The directory that files for this chain are stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Return the SCALE encoded metadata stored for the given spec version, if any.
+    pub fn encoded_metadata(
+        &self,
+        spec_version: u32,
+    ) -> Result<Option<Vec<u8>>, MetadataDiskCacheError> {
+        read_file(&self.metadata_path(spec_version), self.max_entry_size)
+    }
+
+    /// Store some SCALE encoded metadata for the given spec version, removing the least
+    /// recently written metadata for this chain if needed to stay within the size limit.
+    pub fn set_encoded_metadata(
+        &self,
+        spec_version: u32,
+        encoded_metadata: &[u8],
+    ) -> Result<(), MetadataDiskCacheError> {
+        let size = (encoded_metadata.len() + HEADER_LEN) as u64;
+        let max_size = self.max_entry_size.min(self.max_total_size);
+        if size > max_size {
+            return Err(MetadataDiskCacheError::EntryTooLarge { size, max_size });
+        }
+
+        let path = self.metadata_path(spec_version);
+        let sequence = self.make_room_for(size, &path)?;
+        write_file(&path, sequence, encoded_metadata)
+    }
+
+    /// Return the spec and transaction versions stored for ranges of blocks.
+    pub fn spec_version_ranges(&self) -> Result<Vec<SpecVersionForRange>, MetadataDiskCacheError> {
+        let path = self.spec_version_ranges_path();
+        let Some(bytes) = read_file(&path, self.max_entry_size)? else {
+            return Ok(Vec::new());
+        };
+
+        let ranges = <Vec<(u64, u64, u32, u32)>>::decode(&mut &*bytes).map_err(|e| {
+            MetadataDiskCacheError::CorruptedFile {
+                path,
+                reason: e.to_string(),
+            }
+        })?;
+
+        Ok(ranges
+            .into_iter()
+            .map(
+                |(start, end, spec_version, transaction_version)| SpecVersionForRange {
+                    block_range: start..end,
+                    spec_version,
+                    transaction_version,
+                },
+            )
+            .collect())
+    }
+
+    /// Store the spec and transaction versions for ranges of blocks, replacing any
+    /// ranges that were previously stored.
+    pub fn set_spec_version_ranges<'a>(
+        &self,
+        ranges: impl IntoIterator<Item = &'a SpecVersionForRange>,
+    ) -> Result<(), MetadataDiskCacheError> {
+        let ranges: Vec<(u64, u64, u32, u32)> = ranges
+            .into_iter()
+            .map(|r| {
+                (
+                    r.block_range.start,
+                    r.block_range.end,
+                    r.spec_version,
+                    r.transaction_version,
+                )
+            })
+            .collect();
+        write_file(&self.spec_version_ranges_path(), 0, &ranges.encode())
+    }
+
+    /// Store the spec and transaction versions for ranges of blocks alongside any ranges
+    /// previously stored. Previously stored ranges which overlap the given ones are replaced.
+    pub fn add_spec_version_ranges<'a>(
+        &self,
+        ranges: impl IntoIterator<Item = &'a SpecVersionForRange>,
+    ) -> Result<(), MetadataDiskCacheError> {
+        let new_ranges: Vec<_> = ranges.into_iter().cloned().collect();
+        // If the stored ranges can't be read then they are replaced entirely.
+        let mut all_ranges: Vec<_> = self
+            .spec_version_ranges()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|old| {
+                !new_ranges.iter().any(|new| {
+                    old.block_range.start < new.block_range.end
+                        && new.block_range.start < old.block_range.end
+                })
+            })
+            .chain(new_ranges.iter().cloned())
+            .collect();
+        all_ranges.sort_by_key(|r| r.block_range.start);
+        self.set_spec_version_ranges(&all_ranges)
+    }
+
+    fn metadata_dir(&self) -> PathBuf {
+        self.dir.join("metadata")
+    }
+
+    fn metadata_path(&self, spec_version: u32) -> PathBuf {
+        self.metadata_dir().join(format!("{spec_version}.scale"))
+    }
+
+    fn spec_version_ranges_path(&self) -> PathBuf {
+        self.dir.join("spec_versions.scale")
+    }
+
+    // Remove the least recently written metadata files until there is room to
+    // write a new file of the given size (replacing any file at `replacing`), and
+    // return the sequence number that the new file should be written with.
+    fn make_room_for(&self, size: u64, replacing: &Path) -> Result<u64, MetadataDiskCacheError> {
+        let dir = self.metadata_dir();
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => {
+                return Err(MetadataDiskCacheError::CannotReadFile {
+                    path: dir,
+                    reason: e,
+                });
+            }
+        };
+
+        let mut files = Vec::new();
+        let mut next_sequence = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(file_metadata) = entry.metadata() else {
+                continue;
+            };
+            // Files whose sequence number can't be read are treated as the oldest.
+            let sequence = read_sequence(&path).unwrap_or(0);
+            next_sequence = next_sequence.max(sequence + 1);
+            if path != replacing {
+                files.push((sequence, file_metadata.len(), path));
+            }
+        }
+
+        let mut total_size: u64 = files.iter().map(|(_, len, _)| len).sum::<u64>() + size;
+        files.sort_by_key(|(sequence, _, _)| *sequence);
+        for (_, len, path) in files {
+            if total_size <= self.max_total_size {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total_size -= len;
+            }
+        }
+        Ok(next_sequence)
+    }
+}
+
+// Read a cache file, checking that it isn't too large and that its checksum is valid.
+// Returns `None` if the file does not exist.
+fn read_file(path: &Path, max_size: u64) -> Result<Option<Vec<u8>>, MetadataDiskCacheError> {
+    let size = match fs::metadata(path) {
+        Ok(file_metadata) => file_metadata.len(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(MetadataDiskCacheError::CannotReadFile {
+                path: path.to_owned(),
+                reason: e,
+            });
+        }
+    };
+    if size > max_size {
+        return Err(MetadataDiskCacheError::EntryTooLarge { size, max_size });
+    }
+
+    let bytes = fs::read(path).map_err(|e| MetadataDiskCacheError::CannotReadFile {
+        path: path.to_owned(),
+        reason: e,
+    })?;
+
+    let corrupted = |reason: &str| MetadataDiskCacheError::CorruptedFile {
+        path: path.to_owned(),
+        reason: reason.to_owned(),
+    };
+    if bytes.len() < HEADER_LEN || &bytes[..MAGIC.len()] != MAGIC {
+        return Err(corrupted("unexpected file header"));
+    }
+    let (checksum, rest) = bytes[MAGIC.len()..].split_at(CHECKSUM_LEN);
+    if checksum != sp_crypto_hashing::blake2_256(rest) {
+        return Err(corrupted("checksum mismatch"));
+    }
+
+    Ok(Some(rest[SEQUENCE_LEN..].to_vec()))
+}
+
+// Read the sequence number from the header of a cache file, without reading the rest of it.
+fn read_sequence(path: &Path) -> Option<u64> {
+    let mut header = [0u8; HEADER_LEN];
+    fs::File::open(path).ok()?.read_exact(&mut header).ok()?;
+    if &header[..MAGIC.len()] != MAGIC {
+        return None;
+    }
+    let sequence = header[MAGIC.len() + CHECKSUM_LEN..].try_into().ok()?;
+    Some(u64::from_le_bytes(sequence))
+}
+
+// Write a cache file, prefixed with a header containing a checksum and the given sequence
+// number. The file is written to a temporary location first and then moved into place, so
+// that readers never see a partially written file.
+fn write_file(path: &Path, sequence: u64, payload: &[u8]) -> Result<(), MetadataDiskCacheError> {
+    let dir = path
+        .parent()
+        .expect("cache files always live in a directory");
+    fs::create_dir_all(dir).map_err(|e| MetadataDiskCacheError::CannotCreateDirectory {
+        path: dir.to_owned(),
+        reason: e,
+    })?;
+
+    let mut rest = Vec::with_capacity(SEQUENCE_LEN + payload.len());
+    rest.extend_from_slice(&sequence.to_le_bytes());
+    rest.extend_from_slice(payload);
+
+    let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&sp_crypto_hashing::blake2_256(&rest));
+    bytes.extend_from_slice(&rest);
+
+    let cannot_write = |e| MetadataDiskCacheError::CannotWriteFile {
+        path: path.to_owned(),
+        reason: e,
+    };
+    let tmp_path = path.with_extension(format!("tmp-{}", std::process::id()));
+    fs::write(&tmp_path, &bytes).map_err(cannot_write)?;
+    fs::rename(&tmp_path, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp_path);
+        cannot_write(e)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn temp_cache(name: &str) -> MetadataDiskCache {
+        let root = std::env::temp_dir().join(format!(
+            "subxt-metadata-disk-cache-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        MetadataDiskCache::new(root).for_chain([1u8; 32])
+    }
+
+    fn range(block_range: std::ops::Range<u64>, spec_version: u32) -> SpecVersionForRange {
+        SpecVersionForRange {
+            block_range,
+            spec_version,
+            transaction_version: 1,
+        }
+    }
+
+    #[test]
+    fn metadata_round_trips() {
+        let cache = temp_cache("round-trip");
+        assert_eq!(cache.encoded_metadata(1).unwrap(), None);
+
+        cache.set_encoded_metadata(1, &[1, 2, 3]).unwrap();
+        assert_eq!(cache.encoded_metadata(1).unwrap(), Some(vec![1, 2, 3]));
+        assert_eq!(cache.encoded_metadata(2).unwrap(), None);
+
+        let ranges = vec![range(0..100, 1), range(100..200, 2)];
+        cache.set_spec_version_ranges(&ranges).unwrap();
+        assert_eq!(cache.spec_version_ranges().unwrap(), ranges);
+
+        let _ = fs::remove_dir_all(cache.dir());
+    }
+
+    #[test]
+    fn corrupted_files_are_detected() {
+        let cache = temp_cache("corrupted");
+        cache.set_encoded_metadata(1, &[1, 2, 3]).unwrap();
+
+        let path = cache.metadata_path(1);
+        let mut bytes = fs::read(&path).unwrap();
+        *bytes.last_mut().unwrap() = 4;
+        fs::write(&path, bytes).unwrap();
+
+        assert!(matches!(
+            cache.encoded_metadata(1),
+            Err(MetadataDiskCacheError::CorruptedFile { .. })
+        ));
+
+        let _ = fs::remove_dir_all(cache.dir());
+    }
+
+    #[test]
+    fn size_limits_are_respected() {
+        let cache = temp_cache("size-limits")
+            .max_entry_size(HEADER_LEN as u64 + 10)
+            .max_total_size(2 * (HEADER_LEN as u64 + 10));
+
+        assert!(matches!(
+            cache.set_encoded_metadata(1, &[0; 11]),
+            Err(MetadataDiskCacheError::EntryTooLarge { .. })
+        ));
+
+        // Only two entries fit, so writing a third removes the least recently written.
+        cache.set_encoded_metadata(1, &[0; 10]).unwrap();
+        cache.set_encoded_metadata(2, &[0; 10]).unwrap();
+        cache.set_encoded_metadata(1, &[1; 10]).unwrap();
+        cache.set_encoded_metadata(3, &[0; 10]).unwrap();
+        assert_eq!(cache.encoded_metadata(1).unwrap(), Some(vec![1; 10]));
+        assert_eq!(cache.encoded_metadata(2).unwrap(), None);
+        assert!(cache.encoded_metadata(3).unwrap().is_some());
+
+        let _ = fs::remove_dir_all(cache.dir());
+    }
+
+    #[test]
+    fn spec_version_ranges_are_merged() {
+        let cache = temp_cache("merge-ranges");
+        cache
+            .set_spec_version_ranges(&[range(0..100, 1), range(100..200, 2)])
+            .unwrap();
+
+        // The second range is replaced, and the new ones are added in order.
+        cache
+            .add_spec_version_ranges(&[range(150..300, 3), range(300..400, 4)])
+            .unwrap();
+        assert_eq!(
+            cache.spec_version_ranges().unwrap(),
+            vec![range(0..100, 1), range(150..300, 3), range(300..400, 4)]
+        );
+
+        let _ = fs::remove_dir_all(cache.dir());
+    }
+}
@@ -4,7 +4,9 @@
 
 //! Polkadot specific configuration
 
-use super::{Config, DefaultExtrinsicParamsBuilder, DefaultTransactionExtensions};
+use super::{
+    Config, DefaultExtrinsicParamsBuilder, DefaultTransactionExtensions, MetadataDiskCache,
+};
 
 use crate::config::substrate::{SubstrateConfig, SubstrateConfigBuilder};
 use crate::metadata::ArcMetadata;
@@ -53,6 +55,20 @@ impl PolkadotConfigBuilder {
         self
     }
 
+    /// Set the genesis hash for this chain.
+    pub fn set_genesis_hash(mut self, genesis_hash: H256) -> Self {
+        self.inner = self.inner.set_genesis_hash(genesis_hash);
+        self
+    }
+
+    /// Persist metadata and spec version information to disk using the given [`MetadataDiskCache`].
+    /// A genesis hash must also be set via [`PolkadotConfigBuilder::set_genesis_hash()`]. See
+    /// [`SubstrateConfigBuilder::set_metadata_disk_cache()`] for more.
+    pub fn set_metadata_disk_cache(mut self, cache: MetadataDiskCache) -> Self {
+        self.inner = self.inner.set_metadata_disk_cache(cache);
+        self
+    }
+
     /// Use the current "known" spec version information for the Polkadot Relay Chain. For historic blocks in
     /// the known block range, this will avoid needing to check the spec version at each block.
     ///
@@ -148,6 +164,19 @@ impl Config for PolkadotConfig {
     fn set_metadata_for_spec_version(&self, spec_version: u32, metadata: ArcMetadata) {
         self.0.set_metadata_for_spec_version(spec_version, metadata)
     }
+
+    fn encoded_metadata_for_spec_version(&self, spec_version: u32) -> Option<Vec<u8>> {
+        self.0.encoded_metadata_for_spec_version(spec_version)
+    }
+
+    fn set_encoded_metadata_for_spec_version(&self, spec_version: u32, encoded_metadata: &[u8]) {
+        self.0
+            .set_encoded_metadata_for_spec_version(spec_version, encoded_metadata)
+    }
+
+    fn cache_spec_version_for_block_ranges(&self, ranges: &[SpecVersionForRange]) {
+        self.0.cache_spec_version_for_block_ranges(ranges)
+    }
 }
 
 /// A struct representing the signed extra and additional parameters required
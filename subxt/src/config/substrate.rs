@@ -4,8 +4,11 @@
 
 //! Substrate specific configuration
 
+mod consensus_digests;
+
 use super::{
     Config, DefaultExtrinsicParamsBuilder, DefaultTransactionExtensions, HashFor, Hasher, Header,
+    MetadataDiskCache,
 };
 use crate::config::Hash;
 use crate::metadata::{ArcMetadata, Metadata};
@@ -19,12 +22,21 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::RwLock;
 
+pub use consensus_digests::{
+    AURA_ENGINE_ID, AuraConsensusLog, AuraPreDigest, BABE_ENGINE_ID, BEEFY_ENGINE_ID,
+    BabeAllowedSlots, BabeConsensusLog, BabeNextConfigDescriptor, BabeNextEpochDescriptor,
+    BabePreDigest, BabePrimaryPreDigest, BabeSecondaryPlainPreDigest, BabeSecondaryVRFPreDigest,
+    BeefyConsensusLog, BeefyValidatorSet, ConsensusDigest, GRANDPA_ENGINE_ID, GrandpaConsensusLog,
+    GrandpaScheduledChange, VrfSignature,
+};
+
 /// Construct a [`SubstrateConfig`] using this.
 pub struct SubstrateConfigBuilder {
     legacy_types: Option<ChainTypeRegistry>,
-    spec_and_transaction_version_for_block_number: RangeMap<u64, (u32, u32)>,
+    spec_version_for_block_ranges: Option<Vec<SpecVersionForRange>>,
     genesis_hash: Option<H256>,
     metadata_for_spec_version: RwLock<HashMap<u32, ArcMetadata>>,
+    metadata_disk_cache: Option<MetadataDiskCache>,
     use_old_v9_hashers_before_spec_version: u32,
 }
 
@@ -40,8 +52,9 @@ impl SubstrateConfigBuilder {
         SubstrateConfigBuilder {
             legacy_types: None,
             genesis_hash: None,
-            spec_and_transaction_version_for_block_number: RangeMap::empty(),
+            spec_version_for_block_ranges: None,
             metadata_for_spec_version: RwLock::new(HashMap::new()),
+            metadata_disk_cache: None,
             use_old_v9_hashers_before_spec_version: 0,
         }
     }
@@ -78,15 +91,25 @@ impl SubstrateConfigBuilder {
         mut self,
         ranges: impl IntoIterator<Item = SpecVersionForRange>,
     ) -> Self {
-        let mut m = RangeMap::builder();
-        for version_for_range in ranges.into_iter() {
-            let start = version_for_range.block_range.start;
-            let end = version_for_range.block_range.end;
-            let spec_version = version_for_range.spec_version;
-            let transaction_version = version_for_range.transaction_version;
-            m = m.add_range(start, end, (spec_version, transaction_version));
-        }
-        self.spec_and_transaction_version_for_block_number = m.build();
+        self.spec_version_for_block_ranges = Some(ranges.into_iter().collect());
+        self
+    }
+
+    /// Persist metadata and spec version information to disk using the given [`MetadataDiskCache`],
+    /// so that it does not need to be downloaded again each time the process starts.
+    ///
+    /// Metadata retrieved from the chain is written to the cache, and read back from it when it is
+    /// not already held in memory. If spec versions for block ranges are given via
+    /// [`SubstrateConfigBuilder::set_spec_version_for_block_ranges()`], they are written to the cache,
+    /// and otherwise any ranges previously written to the cache are used. Ranges found using
+    /// [`crate::client::OnlineClient::discover_spec_versions()`] are also written to the cache, and
+    /// will be used the next time a configuration is built with this cache.
+    ///
+    /// Files are stored in a directory for the chain whose genesis hash is given via
+    /// [`SubstrateConfigBuilder::set_genesis_hash()`]. If no genesis hash is given, the cache
+    /// is not used.
+    pub fn set_metadata_disk_cache(mut self, cache: MetadataDiskCache) -> Self {
+        self.metadata_disk_cache = Some(cache);
         self
     }
 
@@ -99,13 +122,59 @@ impl SubstrateConfigBuilder {
 
     /// Construct the [`SubstrateConfig`] from this builder.
     pub fn build(self) -> SubstrateConfig {
+        // The cache keeps the files for each chain apart using the genesis hash.
+        let metadata_disk_cache = match (self.metadata_disk_cache, self.genesis_hash) {
+            (Some(cache), Some(genesis_hash)) => Some(cache.for_chain(genesis_hash)),
+            (Some(_), None) => {
+                tracing::warn!(target: "subxt", "Not using the metadata disk cache because no genesis hash was set");
+                None
+            }
+            (None, _) => None,
+        };
+
+        let mut spec_and_transaction_version_for_block_number = RangeMap::builder();
+        match (&self.spec_version_for_block_ranges, &metadata_disk_cache) {
+            (Some(ranges), cache) => {
+                // Ranges were given explicitly, so use them (and cache them if we can).
+                for range in ranges {
+                    spec_and_transaction_version_for_block_number =
+                        spec_and_transaction_version_for_block_number.add_range(
+                            range.block_range.start,
+                            range.block_range.end,
+                            (range.spec_version, range.transaction_version),
+                        );
+                }
+                if let Some(cache) = cache {
+                    if let Err(e) = cache.set_spec_version_ranges(ranges) {
+                        tracing::warn!(target: "subxt", "Cannot write spec versions to metadata disk cache: {e}");
+                    }
+                }
+            }
+            (None, Some(cache)) => {
+                // Use any ranges previously cached, ignoring any that are invalid.
+                let ranges = cache.spec_version_ranges().unwrap_or_else(|e| {
+                    tracing::warn!(target: "subxt", "Cannot read spec versions from metadata disk cache: {e}");
+                    Vec::new()
+                });
+                for range in ranges {
+                    let _ = spec_and_transaction_version_for_block_number.try_add_range(
+                        range.block_range.start,
+                        range.block_range.end,
+                        (range.spec_version, range.transaction_version),
+                    );
+                }
+            }
+            (None, None) => {}
+        }
+
         SubstrateConfig {
             inner: Arc::new(SubstrateConfigInner {
                 legacy_types: self.legacy_types,
-                spec_and_transaction_version_for_block_number: self
-                    .spec_and_transaction_version_for_block_number,
+                spec_and_transaction_version_for_block_number:
+                    spec_and_transaction_version_for_block_number.build(),
                 genesis_hash: self.genesis_hash,
                 metadata_for_spec_version: self.metadata_for_spec_version,
+                metadata_disk_cache,
             }),
         }
     }
@@ -114,6 +183,7 @@ impl SubstrateConfigBuilder {
 /// Define a spec version for a range of blocks. The new spec version is expected
 /// to begin at the first block in the range and end just prior to the last block
 /// in the range.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SpecVersionForRange {
     /// The block range that this spec version applies to. Inclusive of the start
     /// and exclusive of the enc.
@@ -137,6 +207,7 @@ struct SubstrateConfigInner {
     spec_and_transaction_version_for_block_number: RangeMap<u64, (u32, u32)>,
     genesis_hash: Option<H256>,
     metadata_for_spec_version: RwLock<HashMap<u32, ArcMetadata>>,
+    metadata_disk_cache: Option<MetadataDiskCache>,
 }
 
 impl Default for SubstrateConfig {
@@ -206,6 +277,34 @@ impl Config for SubstrateConfig {
             .unwrap()
             .insert(spec_version, metadata);
     }
+
+    fn encoded_metadata_for_spec_version(&self, spec_version: u32) -> Option<Vec<u8>> {
+        let cache = self.inner.metadata_disk_cache.as_ref()?;
+        cache.encoded_metadata(spec_version).unwrap_or_else(|e| {
+            tracing::warn!(target: "subxt", "Cannot read metadata from metadata disk cache: {e}");
+            None
+        })
+    }
+
+    fn set_encoded_metadata_for_spec_version(&self, spec_version: u32, encoded_metadata: &[u8]) {
+        let Some(cache) = &self.inner.metadata_disk_cache else {
+            return;
+        };
+        if let Err(e) = cache.set_encoded_metadata(spec_version, encoded_metadata) {
+            tracing::warn!(target: "subxt", "Cannot write metadata to metadata disk cache: {e}");
+        }
+    }
+
+    // Only the disk cache is written to here; the ranges held in memory are fixed when the
+    // configuration is built.
+    fn cache_spec_version_for_block_ranges(&self, ranges: &[SpecVersionForRange]) {
+        let Some(cache) = &self.inner.metadata_disk_cache else {
+            return;
+        };
+        if let Err(e) = cache.add_spec_version_ranges(ranges) {
+            tracing::warn!(target: "subxt", "Cannot write spec versions to metadata disk cache: {e}");
+        }
+    }
 }
 
 /// A struct representing the signed extra and additional parameters required
@@ -313,6 +412,10 @@ where
     fn number(&self) -> u64 {
         self.number
     }
+
+    fn digest(&self) -> Option<&Digest> {
+        Some(&self.digest)
+    }
 }
 
 /// Generic header digest. From `sp_runtime::generic::digest`.
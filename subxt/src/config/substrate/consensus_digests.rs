@@ -0,0 +1,405 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+//! Typed representations of the well-known consensus related digest items which are found in
+//! Substrate based block headers. These are adapted from the corresponding `sp_consensus_*`
+//! crates so that we don't need to depend on them.
+
+use super::{ConsensusEngineId, Digest, DigestItem, H256};
+use codec::{Decode, DecodeAll, Encode};
+
+/// The engine ID for BABE. From `sp_consensus_babe::BABE_ENGINE_ID`.
+pub const BABE_ENGINE_ID: ConsensusEngineId = *b"BABE";
+/// The engine ID for Aura. From `sp_consensus_aura::AURA_ENGINE_ID`.
+pub const AURA_ENGINE_ID: ConsensusEngineId = *b"aura";
+/// The engine ID for GRANDPA. From `sp_consensus_grandpa::GRANDPA_ENGINE_ID`.
+pub const GRANDPA_ENGINE_ID: ConsensusEngineId = *b"FRNK";
+/// The engine ID for BEEFY. From `sp_consensus_beefy::BEEFY_ENGINE_ID`.
+pub const BEEFY_ENGINE_ID: ConsensusEngineId = *b"BEEF";
+
+/// A consensus related digest item which we know how to decode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsensusDigest {
+    /// A BABE pre-runtime digest, describing who authored the block and in which slot.
+    BabePreRuntime(BabePreDigest),
+    /// A message from the runtime to the BABE consensus engine.
+    BabeConsensus(BabeConsensusLog),
+    /// An Aura pre-runtime digest, describing the slot that the block was authored in.
+    AuraPreRuntime(AuraPreDigest),
+    /// A message from the runtime to the Aura consensus engine.
+    AuraConsensus(AuraConsensusLog),
+    /// A message from the runtime to the GRANDPA finality gadget, for instance
+    /// to signal an authority set change.
+    GrandpaConsensus(GrandpaConsensusLog),
+    /// A message from the runtime to the BEEFY finality gadget.
+    BeefyConsensus(BeefyConsensusLog),
+    /// An indication that the runtime code or heap pages changed in this block.
+    RuntimeEnvironmentUpdated,
+}
+
+impl DigestItem {
+    /// Attempt to decode this digest item into one of the well-known [`ConsensusDigest`] items.
+    ///
+    /// Returns `None` if this item is not one that we recognise, and otherwise the result of
+    /// decoding it.
+    pub fn decode_consensus(&self) -> Option<Result<ConsensusDigest, codec::Error>> {
+        let res = match self {
+            DigestItem::PreRuntime(BABE_ENGINE_ID, data) => {
+                decode_all(data).map(ConsensusDigest::BabePreRuntime)
+            }
+            DigestItem::PreRuntime(AURA_ENGINE_ID, data) => {
+                decode_all(data).map(ConsensusDigest::AuraPreRuntime)
+            }
+            DigestItem::Consensus(BABE_ENGINE_ID, data) => {
+                decode_all(data).map(ConsensusDigest::BabeConsensus)
+            }
+            DigestItem::Consensus(AURA_ENGINE_ID, data) => {
+                decode_all(data).map(ConsensusDigest::AuraConsensus)
+            }
+            DigestItem::Consensus(GRANDPA_ENGINE_ID, data) => {
+                decode_all(data).map(ConsensusDigest::GrandpaConsensus)
+            }
+            DigestItem::Consensus(BEEFY_ENGINE_ID, data) => {
+                decode_all(data).map(ConsensusDigest::BeefyConsensus)
+            }
+            DigestItem::RuntimeEnvironmentUpdated => Ok(ConsensusDigest::RuntimeEnvironmentUpdated),
+            _ => return None,
+        };
+        Some(res)
+    }
+}
+
+impl Digest {
+    /// Iterate over the digest items that we know how to decode, decoding each one.
+    /// Items which are not recognised are skipped.
+    pub fn consensus_items(&self) -> impl Iterator<Item = Result<ConsensusDigest, codec::Error>> {
+        self.logs.iter().filter_map(|item| item.decode_consensus())
+    }
+
+    /// Find and decode the BABE pre-runtime digest, returning `None` if one doesn't exist.
+    pub fn babe_pre_digest(&self) -> Option<Result<BabePreDigest, codec::Error>> {
+        self.logs.iter().find_map(|item| match item {
+            DigestItem::PreRuntime(BABE_ENGINE_ID, data) => Some(decode_all(data)),
+            _ => None,
+        })
+    }
+
+    /// Find and decode the Aura pre-runtime digest, returning `None` if one doesn't exist.
+    pub fn aura_pre_digest(&self) -> Option<Result<AuraPreDigest, codec::Error>> {
+        self.logs.iter().find_map(|item| match item {
+            DigestItem::PreRuntime(AURA_ENGINE_ID, data) => Some(decode_all(data)),
+            _ => None,
+        })
+    }
+
+    /// Find and decode any GRANDPA consensus logs, which signal changes to the GRANDPA
+    /// authority set amongst other things.
+    pub fn grandpa_logs(&self) -> impl Iterator<Item = Result<GrandpaConsensusLog, codec::Error>> {
+        self.logs.iter().filter_map(|item| match item {
+            DigestItem::Consensus(GRANDPA_ENGINE_ID, data) => Some(decode_all(data)),
+            _ => None,
+        })
+    }
+
+    /// Find and decode any BEEFY consensus logs.
+    pub fn beefy_logs(&self) -> impl Iterator<Item = Result<BeefyConsensusLog, codec::Error>> {
+        self.logs.iter().filter_map(|item| match item {
+            DigestItem::Consensus(BEEFY_ENGINE_ID, data) => Some(decode_all(data)),
+            _ => None,
+        })
+    }
+
+    /// Does this digest contain a [`DigestItem::RuntimeEnvironmentUpdated`] item, signalling that
+    /// the runtime code or heap pages were changed in this block?
+    pub fn has_runtime_environment_updated(&self) -> bool {
+        self.logs
+            .iter()
+            .any(|item| matches!(item, DigestItem::RuntimeEnvironmentUpdated))
+    }
+}
+
+fn decode_all<T: Decode>(mut data: &[u8]) -> Result<T, codec::Error> {
+    T::decode_all(&mut data)
+}
+
+/// A BABE pre-runtime digest. From `sp_consensus_babe::digests::PreDigest`.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub enum BabePreDigest {
+    /// A primary VRF-based slot assignment.
+    #[codec(index = 1)]
+    Primary(BabePrimaryPreDigest),
+    /// A secondary deterministic slot assignment.
+    #[codec(index = 2)]
+    SecondaryPlain(BabeSecondaryPlainPreDigest),
+    /// A secondary deterministic slot assignment with VRF outputs.
+    #[codec(index = 3)]
+    SecondaryVRF(BabeSecondaryVRFPreDigest),
+}
+
+impl BabePreDigest {
+    /// The index of the authority which authored the block.
+    pub fn authority_index(&self) -> u32 {
+        match self {
+            BabePreDigest::Primary(d) => d.authority_index,
+            BabePreDigest::SecondaryPlain(d) => d.authority_index,
+            BabePreDigest::SecondaryVRF(d) => d.authority_index,
+        }
+    }
+
+    /// The slot that the block was authored in.
+    pub fn slot(&self) -> u64 {
+        match self {
+            BabePreDigest::Primary(d) => d.slot,
+            BabePreDigest::SecondaryPlain(d) => d.slot,
+            BabePreDigest::SecondaryVRF(d) => d.slot,
+        }
+    }
+
+    /// Was the block authored in a primary slot?
+    pub fn is_primary(&self) -> bool {
+        matches!(self, BabePreDigest::Primary(_))
+    }
+}
+
+/// A BABE primary slot assignment. From `sp_consensus_babe::digests::PrimaryPreDigest`.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub struct BabePrimaryPreDigest {
+    /// The index of the authority which authored the block.
+    pub authority_index: u32,
+    /// The slot number.
+    pub slot: u64,
+    /// The VRF signature.
+    pub vrf_signature: VrfSignature,
+}
+
+/// A BABE secondary slot assignment. From `sp_consensus_babe::digests::SecondaryPlainPreDigest`.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub struct BabeSecondaryPlainPreDigest {
+    /// The index of the authority which authored the block.
+    pub authority_index: u32,
+    /// The slot number.
+    pub slot: u64,
+}
+
+/// A BABE secondary slot assignment with VRF outputs. From
+/// `sp_consensus_babe::digests::SecondaryVRFPreDigest`.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub struct BabeSecondaryVRFPreDigest {
+    /// The index of the authority which authored the block.
+    pub authority_index: u32,
+    /// The slot number.
+    pub slot: u64,
+    /// The VRF signature.
+    pub vrf_signature: VrfSignature,
+}
+
+/// An sr25519 VRF signature. From `sp_core::sr25519::vrf::VrfSignature`.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub struct VrfSignature {
+    /// The VRF pre-output.
+    pub pre_output: [u8; 32],
+    /// The VRF proof.
+    pub proof: [u8; 64],
+}
+
+/// A message from the runtime to the BABE consensus engine. From `sp_consensus_babe::ConsensusLog`.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub enum BabeConsensusLog {
+    /// The epoch has changed. This provides information about the _next_ epoch.
+    #[codec(index = 1)]
+    NextEpochData(BabeNextEpochDescriptor),
+    /// Disable the authority with the given index.
+    #[codec(index = 2)]
+    OnDisabled(u32),
+    /// The epoch has changed, and the epoch after the current one will enact
+    /// different epoch configurations.
+    #[codec(index = 3)]
+    NextConfigData(BabeNextConfigDescriptor),
+}
+
+/// Information about the next BABE epoch. From `sp_consensus_babe::digests::NextEpochDescriptor`.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub struct BabeNextEpochDescriptor {
+    /// The authorities (sr25519 public keys) and their weights.
+    pub authorities: Vec<([u8; 32], u64)>,
+    /// The value of randomness to use for the slot-assignment.
+    pub randomness: [u8; 32],
+}
+
+/// Information about the next BABE epoch config, if changed. From
+/// `sp_consensus_babe::digests::NextConfigDescriptor`.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub enum BabeNextConfigDescriptor {
+    /// Version 1.
+    #[codec(index = 1)]
+    V1 {
+        /// Value of `c` in `BabeEpochConfiguration`.
+        c: (u64, u64),
+        /// Value of `allowed_slots` in `BabeEpochConfiguration`.
+        allowed_slots: BabeAllowedSlots,
+    },
+}
+
+/// The types of slot that are allowed to be authored. From `sp_consensus_babe::AllowedSlots`.
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BabeAllowedSlots {
+    /// Only allow primary slots.
+    PrimarySlots,
+    /// Allow primary and secondary plain slots.
+    PrimaryAndSecondaryPlainSlots,
+    /// Allow primary and secondary VRF slots.
+    PrimaryAndSecondaryVRFSlots,
+}
+
+/// An Aura pre-runtime digest, which contains the slot that the block was authored in.
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuraPreDigest {
+    /// The slot number.
+    pub slot: u64,
+}
+
+/// A message from the runtime to the Aura consensus engine. From `sp_consensus_aura::ConsensusLog`.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub enum AuraConsensusLog {
+    /// The authorities (public keys) have changed.
+    #[codec(index = 1)]
+    AuthoritiesChange(Vec<[u8; 32]>),
+    /// Disable the authority with the given index.
+    #[codec(index = 2)]
+    OnDisabled(u32),
+}
+
+/// A message from the runtime to the GRANDPA finality gadget. From
+/// `sp_consensus_grandpa::ConsensusLog<u32>`.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub enum GrandpaConsensusLog {
+    /// Schedule an authority set change. This is applied once the block which
+    /// contains it plus the given delay is finalized.
+    #[codec(index = 1)]
+    ScheduledChange(GrandpaScheduledChange),
+    /// Force an authority set change. This is applied once the block which contains
+    /// it plus the given delay is imported, ignoring finality. The first value is the
+    /// median last finalized block number.
+    #[codec(index = 2)]
+    ForcedChange(u32, GrandpaScheduledChange),
+    /// Note that the authority with the given index is disabled.
+    #[codec(index = 3)]
+    OnDisabled(u64),
+    /// A signal to pause the current authority set after the given delay.
+    #[codec(index = 4)]
+    Pause(u32),
+    /// A signal to resume the current authority set after the given delay.
+    #[codec(index = 5)]
+    Resume(u32),
+}
+
+/// A scheduled change of GRANDPA authority set. From `sp_consensus_grandpa::ScheduledChange`.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub struct GrandpaScheduledChange {
+    /// The new authorities (ed25519 public keys) and their weights after the change.
+    pub next_authorities: Vec<([u8; 32], u64)>,
+    /// The number of blocks to delay.
+    pub delay: u32,
+}
+
+/// A message from the runtime to the BEEFY finality gadget. From `sp_consensus_beefy::ConsensusLog`.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub enum BeefyConsensusLog {
+    /// The authorities have changed.
+    #[codec(index = 1)]
+    AuthoritiesChange(BeefyValidatorSet),
+    /// Disable the authority with the given index.
+    #[codec(index = 2)]
+    OnDisabled(u32),
+    /// The MMR root hash.
+    #[codec(index = 3)]
+    MmrRoot(H256),
+}
+
+/// A BEEFY validator set. From `sp_consensus_beefy::ValidatorSet`.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub struct BeefyValidatorSet {
+    /// The validators (compressed ECDSA public keys) in the set.
+    pub validators: Vec<[u8; 33]>,
+    /// The identifier of this validator set.
+    pub id: u64,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn decodes_babe_pre_digest() {
+        let pre_digest = BabePreDigest::SecondaryPlain(BabeSecondaryPlainPreDigest {
+            authority_index: 7,
+            slot: 123_456,
+        });
+        let digest = Digest {
+            logs: vec![
+                DigestItem::Seal(BABE_ENGINE_ID, vec![1, 2, 3]),
+                DigestItem::PreRuntime(BABE_ENGINE_ID, pre_digest.encode()),
+            ],
+        };
+
+        let decoded = digest.babe_pre_digest().unwrap().unwrap();
+        assert_eq!(decoded, pre_digest);
+        assert_eq!(decoded.authority_index(), 7);
+        assert_eq!(decoded.slot(), 123_456);
+        assert!(!decoded.is_primary());
+        assert!(digest.aura_pre_digest().is_none());
+    }
+
+    #[test]
+    fn decodes_aura_pre_digest() {
+        let item = DigestItem::PreRuntime(AURA_ENGINE_ID, 42u64.encode());
+        assert_eq!(
+            item.decode_consensus().unwrap().unwrap(),
+            ConsensusDigest::AuraPreRuntime(AuraPreDigest { slot: 42 })
+        );
+    }
+
+    #[test]
+    fn decodes_aura_on_disabled() {
+        // The authority index is a u32; a log encoded as such must decode in full.
+        let item = DigestItem::Consensus(AURA_ENGINE_ID, (2u8, 3u32).encode());
+        assert_eq!(
+            item.decode_consensus().unwrap().unwrap(),
+            ConsensusDigest::AuraConsensus(AuraConsensusLog::OnDisabled(3))
+        );
+    }
+
+    #[test]
+    fn decodes_grandpa_forced_change() {
+        let log = GrandpaConsensusLog::ForcedChange(
+            10,
+            GrandpaScheduledChange {
+                next_authorities: vec![([1; 32], 1), ([2; 32], 1)],
+                delay: 5,
+            },
+        );
+        let digest = Digest {
+            logs: vec![
+                DigestItem::Consensus(GRANDPA_ENGINE_ID, log.encode()),
+                DigestItem::RuntimeEnvironmentUpdated,
+            ],
+        };
+
+        let logs: Vec<_> = digest.grandpa_logs().collect::<Result<_, _>>().unwrap();
+        assert_eq!(logs, vec![log]);
+        assert!(digest.has_runtime_environment_updated());
+        assert_eq!(digest.consensus_items().count(), 2);
+    }
+
+    #[test]
+    fn unknown_items_are_ignored_and_trailing_bytes_error() {
+        let unknown = DigestItem::PreRuntime(*b"fake", vec![1, 2, 3]);
+        assert!(unknown.decode_consensus().is_none());
+
+        let mut bytes = 42u64.encode();
+        bytes.push(0);
+        let bad = DigestItem::PreRuntime(AURA_ENGINE_ID, bytes);
+        assert!(bad.decode_consensus().unwrap().is_err());
+    }
+}
@@ -54,6 +54,8 @@ pub enum Error {
     #[error(transparent)]
     BlocksError(#[from] BlocksError),
     #[error(transparent)]
+    BlockAuthorError(#[from] BlockAuthorError),
+    #[error(transparent)]
     AccountNonceError(#[from] AccountNonceError),
     #[error(transparent)]
     RuntimeApiError(#[from] RuntimeApiError),
@@ -81,6 +83,22 @@ pub enum Error {
     StorageError(#[from] StorageError),
     #[error(transparent)]
     CombinedBackendError(#[from] CombinedBackendError),
+    #[error(transparent)]
+    GrandpaError(#[from] GrandpaError),
+    #[error(transparent)]
+    GrandpaVerificationError(#[from] GrandpaVerificationError),
+    #[error(transparent)]
+    StorageSnapshotError(#[from] StorageSnapshotError),
+    #[error(transparent)]
+    StorageHistoryError(#[from] StorageHistoryError),
+    #[error(transparent)]
+    RuntimeUpgradesError(#[from] RuntimeUpgradesError),
+    #[error(transparent)]
+    SpecVersionDiscoveryError(#[from] SpecVersionDiscoveryError),
+    #[error(transparent)]
+    RuntimeCodeError(#[from] RuntimeCodeError),
+    #[error(transparent)]
+    MetadataDiskCacheError(#[from] MetadataDiskCacheError),
     // Dev note: Subxt doesn't directly return Raw* errors. These exist so that when
     // users use common crates (like parity-scale-codec and subxt-rpcs), errors returned
     // there can be handled automatically using ? when the expected error is subxt::Error.
@@ -147,6 +165,7 @@ impl Error {
             // the actual error types to make it harder to miss adding any, and be exhaustive
             // here so new error variants are not missed as easily.
             Error::BlocksError(e) => e.backend_error(),
+            Error::BlockAuthorError(e) => e.backend_error(),
             Error::AccountNonceError(e) => e.backend_error(),
             Error::OnlineClientError(e) => e.backend_error(),
             Error::RuntimeApiError(e) => e.backend_error(),
@@ -170,6 +189,14 @@ impl Error {
             Error::ModuleErrorDecodeError(e) => e.backend_error(),
             Error::DispatchErrorDecodeError(e) => e.backend_error(),
             Error::CombinedBackendError(e) => e.backend_error(),
+            Error::GrandpaError(e) => e.backend_error(),
+            Error::GrandpaVerificationError(e) => e.backend_error(),
+            Error::StorageSnapshotError(e) => e.backend_error(),
+            Error::StorageHistoryError(e) => e.backend_error(),
+            Error::RuntimeUpgradesError(e) => e.backend_error(),
+            Error::SpecVersionDiscoveryError(e) => e.backend_error(),
+            Error::RuntimeCodeError(e) => e.backend_error(),
+            Error::MetadataDiskCacheError(e) => e.backend_error(),
             #[cfg(feature = "light-client")]
             Error::OtherLightClientError(_) => None,
             #[cfg(feature = "light-client")]
@@ -255,6 +282,101 @@ impl BlocksError {
     }
 }
 
+/// Errors finding the author of a block.
+#[allow(missing_docs)]
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum BlockAuthorError {
+    #[error("Cannot find block author: cannot decode the pre-runtime digest: {0}")]
+    CannotDecodeDigest(codec::Error),
+    #[error("Cannot find block author: cannot instantiate client at block: {0}")]
+    CannotInstantiateClientAtBlock(OnlineClientAtBlockError),
+    #[error("Cannot find block author: cannot fetch {pallet_name}.{entry_name}: {error}")]
+    CannotFetchAuthorities {
+        pallet_name: &'static str,
+        entry_name: &'static str,
+        error: StorageError,
+    },
+    #[error("Cannot find block author: cannot decode {pallet_name}.{entry_name}: {error}")]
+    CannotDecodeAuthorities {
+        pallet_name: &'static str,
+        entry_name: &'static str,
+        error: StorageValueError,
+    },
+}
+
+impl BlockAuthorError {
+    fn backend_error(&self) -> Option<&BackendError> {
+        match self {
+            BlockAuthorError::CannotInstantiateClientAtBlock(e) => e.backend_error(),
+            BlockAuthorError::CannotFetchAuthorities { error, .. } => error.backend_error(),
+            _ => None,
+        }
+    }
+}
+
+/// Errors fetching or decoding GRANDPA justifications.
+#[allow(missing_docs)]
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum GrandpaError {
+    #[error("Cannot fetch block justifications: {0}")]
+    CannotFetchJustifications(BackendError),
+    #[error("Cannot fetch block justifications: block {block_hash} not found")]
+    BlockNotFound { block_hash: Hex },
+    #[error("Cannot decode GRANDPA justification: {0}")]
+    CannotDecodeJustification(codec::Error),
+    #[error("Cannot decode GRANDPA finality proof: {0}")]
+    CannotDecodeFinalityProof(codec::Error),
+    #[error("Cannot fetch GRANDPA authority set: {0}")]
+    CannotFetchAuthoritySet(BackendError),
+    #[error("Cannot decode GRANDPA authority set: {0}")]
+    CannotDecodeAuthoritySet(codec::Error),
+}
+
+impl GrandpaError {
+    fn backend_error(&self) -> Option<&BackendError> {
+        match self {
+            GrandpaError::CannotFetchJustifications(e)
+            | GrandpaError::CannotFetchAuthoritySet(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Errors verifying a GRANDPA justification.
+#[allow(missing_docs)]
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum GrandpaVerificationError {
+    #[error("Cannot verify GRANDPA justification: the authority set has no voting weight")]
+    EmptyAuthoritySet,
+    #[error("Cannot verify GRANDPA justification: cannot decode ancestry header: {0}")]
+    CannotDecodeAncestryHeader(codec::Error),
+    #[error("Invalid GRANDPA justification: precommit signed by unknown authority {authority_id}")]
+    UnknownAuthority { authority_id: Hex },
+    #[error("Invalid GRANDPA justification: bad precommit signature from authority {authority_id}")]
+    InvalidSignature { authority_id: Hex },
+    #[error(
+        "Invalid GRANDPA justification: precommit target {precommit_target} is not a descendant of the commit target"
+    )]
+    InvalidAncestry { precommit_target: Hex },
+    #[error(
+        "Invalid GRANDPA justification: ancestry header {header_hash} is not needed to prove any precommit target"
+    )]
+    UnusedAncestry { header_hash: Hex },
+    #[error(
+        "Invalid GRANDPA justification: precommits have weight {signed_weight}, but at least {threshold} is required"
+    )]
+    InsufficientWeight { signed_weight: u64, threshold: u64 },
+}
+
+impl GrandpaVerificationError {
+    fn backend_error(&self) -> Option<&BackendError> {
+        None
+    }
+}
+
 /// Errors constructing an online client at a specific block number.
 #[allow(missing_docs)]
 #[derive(Debug, thiserror::Error)]
@@ -511,12 +633,26 @@ pub enum RuntimeApiError {
     CouldNotDecodeResponse(frame_decode::runtime_apis::RuntimeApiDecodeError<u32>),
     #[error("Cannot call the Runtime API: {0}")]
     CannotCallApi(BackendError),
+    #[error("Cannot execute the Runtime API locally: cannot fetch the runtime code: {0}")]
+    CannotFetchRuntimeCode(StorageError),
+    #[error("Cannot execute the Runtime API locally: invalid runtime code: {0}")]
+    InvalidRuntimeCode(RuntimeCodeError),
+    #[error("Cannot execute the Runtime API locally: cannot instantiate the runtime: {0}")]
+    CannotInstantiateLocalRuntime(String),
+    #[error("Cannot execute the Runtime API locally: cannot fetch storage: {0}")]
+    CannotFetchLocalStorage(BackendError),
+    #[error("Cannot execute the Runtime API locally: {0}")]
+    LocalExecutionFailed(String),
+    #[error("Cannot decode the metadata returned from the local runtime: {0}")]
+    CannotDecodeLocalMetadata(String),
 }
 
 impl RuntimeApiError {
     fn backend_error(&self) -> Option<&BackendError> {
         match self {
             RuntimeApiError::CannotCallApi(e) => Some(e),
+            RuntimeApiError::CannotFetchLocalStorage(e) => Some(e),
+            RuntimeApiError::CannotFetchRuntimeCode(e) => e.backend_error(),
             _ => None,
         }
     }
@@ -807,6 +943,14 @@ pub enum TransactionEventsError {
     },
     #[error("The transaction failed with the following dispatch error: {0}")]
     ExtrinsicFailed(#[from] DispatchError),
+    #[error(
+        "A call dispatched by the transaction failed (reported in {pallet_name}::{event_name}) with the following dispatch error: {error}"
+    )]
+    NestedDispatchFailed {
+        pallet_name: String,
+        event_name: String,
+        error: DispatchError,
+    },
 }
 
 impl TransactionEventsError {
@@ -923,6 +1067,8 @@ pub enum StorageError {
     StorageInfoError(frame_decode::storage::StorageInfoError<'static>),
     #[error("Cannot encode storage key: {0}")]
     StorageKeyEncodeError(frame_decode::storage::StorageKeyEncodeError),
+    #[error("Cannot encode storage value: {0}")]
+    CannotEncodeValue(scale_encode::Error),
     #[error("Cannot create a key to iterate over a plain entry")]
     CannotIterPlainEntry {
         pallet_name: String,
@@ -948,6 +1094,14 @@ pub enum StorageError {
     StreamFailure(BackendError),
     #[error("Cannot decode the storage version for a given entry: {0}")]
     CannotDecodeStorageVersion(codec::Error),
+    #[error(
+        "The storage cursor given does not point to a key within the entries being iterated over"
+    )]
+    CursorNotInIteration,
+    #[error("Cannot decode the child trie value: {0}")]
+    CannotDecodeChildTrieValue(codec::Error),
+    #[error("Cannot decode the value at the well known storage key '{key}': {reason}")]
+    CannotDecodeWellKnownValue { key: String, reason: codec::Error },
 }
 
 impl StorageError {
@@ -961,6 +1115,200 @@ impl StorageError {
     }
 }
 
+/// Something went wrong loading or saving a [`crate::storage::StorageSnapshot`].
+#[derive(Debug, DeriveError)]
+#[non_exhaustive]
+#[allow(missing_docs)]
+pub enum StorageSnapshotError {
+    #[error("Cannot deserialize the storage snapshot: {0}")]
+    CannotDeserialize(serde_json::Error),
+    #[error("The chain spec does not contain any raw genesis storage (expected `genesis.raw.top`)")]
+    NoRawGenesis,
+    #[error("Cannot read or write the storage snapshot file: {0}")]
+    Io(std::io::Error),
+}
+
+impl StorageSnapshotError {
+    fn backend_error(&self) -> Option<&BackendError> {
+        None
+    }
+}
+
+/// Something went wrong working with some [`crate::storage::RuntimeCode`].
+#[derive(Debug, DeriveError)]
+#[non_exhaustive]
+#[allow(missing_docs)]
+pub enum RuntimeCodeError {
+    #[error("Cannot decompress the runtime code: {0}")]
+    CannotDecompress(String),
+    #[error("Cannot decompress the runtime code: it is larger than the limit of {limit} bytes")]
+    TooLarge { limit: u64 },
+    #[error("The runtime code is not valid WASM: {0}")]
+    InvalidWasm(&'static str),
+    #[error("Cannot decode the runtime version in the runtime code: {0}")]
+    CannotDecodeRuntimeVersion(codec::Error),
+}
+
+impl RuntimeCodeError {
+    fn backend_error(&self) -> Option<&BackendError> {
+        None
+    }
+}
+
+/// Something went wrong obtaining the history of a storage value via
+/// [`crate::client::OnlineClient::storage_history()`].
+#[derive(Debug, DeriveError)]
+#[non_exhaustive]
+#[allow(missing_docs)]
+pub enum StorageHistoryError {
+    #[error("Cannot obtain storage history: cannot instantiate client at block: {0}")]
+    CannotInstantiateClientAtBlock(OnlineClientAtBlockError),
+    #[error("Cannot obtain storage history: cannot encode the storage key: {0}")]
+    CannotEncodeKey(StorageError),
+    #[error(
+        "Cannot obtain storage history: the start of the block range (block {from}) is after the end (block {to})"
+    )]
+    InvalidBlockRange { from: u64, to: u64 },
+    #[error("Cannot obtain storage history: cannot fetch storage changes: {0}")]
+    CannotFetchChanges(BackendError),
+    #[error("Encountered an error streaming storage changes: {0}")]
+    StreamFailure(BackendError),
+    #[error(
+        "The storage address is not compatible with the metadata at block {block_number}: {error}"
+    )]
+    IncompatibleAddress {
+        block_number: u64,
+        error: StorageError,
+    },
+    #[error("Cannot decode the storage value at block {block_number}: {error}")]
+    CannotDecodeValue {
+        block_number: u64,
+        error: StorageValueError,
+    },
+}
+
+impl StorageHistoryError {
+    fn backend_error(&self) -> Option<&BackendError> {
+        match self {
+            StorageHistoryError::CannotInstantiateClientAtBlock(e) => e.backend_error(),
+            StorageHistoryError::CannotEncodeKey(e) => e.backend_error(),
+            StorageHistoryError::CannotFetchChanges(e) | StorageHistoryError::StreamFailure(e) => {
+                Some(e)
+            }
+            StorageHistoryError::IncompatibleAddress { error, .. } => error.backend_error(),
+            StorageHistoryError::CannotDecodeValue { .. } => None,
+        }
+    }
+}
+
+/// Something went wrong subscribing to runtime upgrades via
+/// [`crate::client::OnlineClient::subscribe_runtime_upgrades()`].
+#[derive(Debug, DeriveError)]
+#[non_exhaustive]
+#[allow(missing_docs)]
+pub enum RuntimeUpgradesError {
+    #[error("Cannot subscribe to runtime upgrades: cannot get the current block: {0}")]
+    CannotGetCurrentBlock(OnlineClientAtBlockError),
+    #[error("Cannot subscribe to runtime upgrades: cannot get runtime version stream: {0}")]
+    CannotGetRuntimeVersionStream(BackendError),
+    #[error("Error streaming runtime upgrades: cannot get the next runtime version: {0}")]
+    CannotGetRuntimeVersion(BackendError),
+    #[error("Error streaming runtime upgrades: cannot instantiate client at block: {0}")]
+    CannotInstantiateClientAtBlock(OnlineClientAtBlockError),
+}
+
+impl RuntimeUpgradesError {
+    fn backend_error(&self) -> Option<&BackendError> {
+        match self {
+            RuntimeUpgradesError::CannotGetCurrentBlock(e)
+            | RuntimeUpgradesError::CannotInstantiateClientAtBlock(e) => e.backend_error(),
+            RuntimeUpgradesError::CannotGetRuntimeVersionStream(e)
+            | RuntimeUpgradesError::CannotGetRuntimeVersion(e) => Some(e),
+        }
+    }
+}
+
+/// Something went wrong discovering spec versions via
+/// [`crate::client::OnlineClient::discover_spec_versions()`].
+#[derive(Debug, DeriveError)]
+#[non_exhaustive]
+#[allow(missing_docs)]
+pub enum SpecVersionDiscoveryError {
+    #[error("Cannot discover spec versions: the block range {start}..{end} is empty")]
+    EmptyBlockRange { start: u64, end: u64 },
+    #[error("Cannot discover spec versions: cannot get the hash of block {block_number}: {reason}")]
+    CannotGetBlockHash {
+        block_number: u64,
+        reason: BackendError,
+    },
+    #[error("Cannot discover spec versions: block {block_number} was not found")]
+    BlockNotFound { block_number: u64 },
+    #[error(
+        "Cannot discover spec versions: cannot get the runtime version at block {block_number}: {reason}"
+    )]
+    CannotGetSpecVersion {
+        block_number: u64,
+        reason: BackendError,
+    },
+    #[error(
+        "Cannot discover spec versions: cannot decode the runtime version at block {block_number}: {reason}"
+    )]
+    CannotDecodeSpecVersion {
+        block_number: u64,
+        reason: codec::Error,
+    },
+}
+
+impl SpecVersionDiscoveryError {
+    fn backend_error(&self) -> Option<&BackendError> {
+        match self {
+            SpecVersionDiscoveryError::CannotGetBlockHash { reason, .. }
+            | SpecVersionDiscoveryError::CannotGetSpecVersion { reason, .. } => Some(reason),
+            SpecVersionDiscoveryError::EmptyBlockRange { .. }
+            | SpecVersionDiscoveryError::BlockNotFound { .. }
+            | SpecVersionDiscoveryError::CannotDecodeSpecVersion { .. } => None,
+        }
+    }
+}
+
+/// Something went wrong reading from or writing to a
+/// [`crate::config::MetadataDiskCache`].
+#[derive(Debug, DeriveError)]
+#[non_exhaustive]
+#[allow(missing_docs)]
+pub enum MetadataDiskCacheError {
+    #[error("Cannot create metadata cache directory {path}: {reason}")]
+    CannotCreateDirectory {
+        path: std::path::PathBuf,
+        reason: std::io::Error,
+    },
+    #[error("Cannot read metadata cache file {path}: {reason}")]
+    CannotReadFile {
+        path: std::path::PathBuf,
+        reason: std::io::Error,
+    },
+    #[error("Cannot write metadata cache file {path}: {reason}")]
+    CannotWriteFile {
+        path: std::path::PathBuf,
+        reason: std::io::Error,
+    },
+    #[error("Metadata cache file {path} is corrupted: {reason}")]
+    CorruptedFile {
+        path: std::path::PathBuf,
+        reason: String,
+    },
+    #[error(
+        "Metadata cache entry is {size} bytes, which is larger than the maximum allowed size of {max_size} bytes"
+    )]
+    EntryTooLarge { size: u64, max_size: u64 },
+}
+
+impl MetadataDiskCacheError {
+    fn backend_error(&self) -> Option<&BackendError> {
+        None
+    }
+}
+
 /// Something went wrong working with a constant.
 #[derive(Debug, DeriveError)]
 #[non_exhaustive]
@@ -3,13 +3,15 @@
 
 mod decode_as_event;
 
+use crate::ArcMetadata;
 use crate::backend::BackendExt;
 use crate::client::{OfflineClientAtBlockT, OnlineClientAtBlockT};
 use crate::config::{Config, HashFor};
-use crate::error::EventsError;
-use crate::{ArcMetadata, Metadata};
+use crate::error::{DispatchError, DispatchErrorDecodeError, EventsError};
 use codec::{Compact, Decode, Encode};
 use scale_decode::{DecodeAsFields, DecodeAsType};
+use scale_info::PortableRegistry;
+use std::collections::BTreeMap;
 use std::marker::PhantomData;
 use std::sync::Arc;
 
@@ -119,7 +121,7 @@ impl<T: Config> Events<T> {
     pub fn iter(&'_ self) -> impl Iterator<Item = Result<Event<'_, T>, EventsError>> + Send + Sync {
         // The event bytes ignoring the compact encoded length on the front:
         let event_bytes = self.event_bytes.clone();
-        let metadata = &*self.metadata;
+        let metadata = &self.metadata;
         let num_events = self.num_events;
 
         let mut pos = self.start_idx;
@@ -170,6 +172,73 @@ impl<T: Config> Events<T> {
     pub fn has<E: DecodeAsEvent>(&self) -> bool {
         self.iter().filter_map(|e| e.ok()).any(|e| e.is::<E>())
     }
+
+    /// Decode all of the events and group them by the [`Phase`] in which they were emitted, in a
+    /// single pass. This returns an error if any of the events fail to decode.
+    ///
+    /// Use [`crate::extrinsics::Extrinsics::iter_with_events()`] to pair up each extrinsic in a
+    /// block with the events that it emitted.
+    pub fn group_by_phase(&self) -> Result<GroupedEvents<'_, T>, EventsError> {
+        let mut grouped = GroupedEvents {
+            initialization: Vec::new(),
+            extrinsics: BTreeMap::new(),
+            finalization: Vec::new(),
+        };
+
+        for event in self.iter() {
+            let event = event?;
+            match event.phase() {
+                Phase::Initialization => grouped.initialization.push(event),
+                Phase::Finalization => grouped.finalization.push(event),
+                Phase::ApplyExtrinsic(idx) => {
+                    grouped.extrinsics.entry(idx).or_default().push(event)
+                }
+            }
+        }
+
+        Ok(grouped)
+    }
+}
+
+/// The events in a block, grouped by the [`Phase`] in which they were emitted. This is
+/// constructed via [`Events::group_by_phase()`].
+#[derive(Debug, Clone)]
+pub struct GroupedEvents<'events, T: Config> {
+    initialization: Vec<Event<'events, T>>,
+    // Keyed by extrinsic index. The index comes from the event bytes, so we
+    // don't size anything based on it.
+    extrinsics: BTreeMap<u32, Vec<Event<'events, T>>>,
+    finalization: Vec<Event<'events, T>>,
+}
+
+impl<'events, T: Config> GroupedEvents<'events, T> {
+    /// The events emitted during block initialization.
+    pub fn initialization(&self) -> &[Event<'events, T>] {
+        &self.initialization
+    }
+
+    /// The events emitted while applying the extrinsic at the given index. This is empty if
+    /// no events were emitted for that extrinsic.
+    pub fn extrinsic(&self, extrinsic_index: usize) -> &[Event<'events, T>] {
+        u32::try_from(extrinsic_index)
+            .ok()
+            .and_then(|idx| self.extrinsics.get(&idx))
+            .map(|events| &**events)
+            .unwrap_or(&[])
+    }
+
+    /// Iterate over the extrinsic indexes and the events emitted while applying each extrinsic,
+    /// in order of extrinsic index. Extrinsics which emitted no events are skipped.
+    pub fn extrinsics(&self) -> impl Iterator<Item = (usize, &[Event<'events, T>])> {
+        self.extrinsics
+            .iter()
+            .map(|(idx, events)| (*idx as usize, &**events))
+    }
+
+    /// The events emitted during block finalization.
+    pub fn finalization(&self) -> &[Event<'events, T>] {
+        &self.finalization
+    }
 }
 
 /// A phase of a block's execution.
@@ -188,7 +257,7 @@ pub enum Phase {
 pub struct Event<'events, T: Config> {
     pallet_name: &'events str,
     event_name: &'events str,
-    metadata: &'events Metadata,
+    metadata: &'events ArcMetadata,
     // all of the event bytes (not just this one).
     all_bytes: Arc<[u8]>,
     // event phase.
@@ -212,7 +281,7 @@ pub struct Event<'events, T: Config> {
 impl<'events, T: Config> Event<'events, T> {
     /// Attempt to dynamically decode a single event from our events input.
     fn decode_from(
-        metadata: &'events Metadata,
+        metadata: &'events ArcMetadata,
         all_bytes: Arc<[u8]>,
         start_idx: usize,
         index: u32,
@@ -411,6 +480,62 @@ impl<'events, T: Config> Event<'events, T> {
         Ok(decoded)
     }
 
+    /// Iterate over each of the fields in this event.
+    pub fn fields(&self) -> impl Iterator<Item = EventField<'_>> {
+        let event_metadata = self.event_metadata();
+        let mut input = self.field_bytes();
+
+        event_metadata.variant.fields.iter().map(move |field| {
+            let field_start = input;
+            scale_decode::visitor::decode_with_visitor(
+                &mut input,
+                field.ty.id,
+                self.metadata.types(),
+                scale_decode::visitor::IgnoreVisitor::new(),
+            )
+            .expect("event fields to decode; we did this already during decoding");
+
+            EventField {
+                bytes: &field_start[..field_start.len() - input.len()],
+                name: field.name.as_deref(),
+                type_id: field.ty.id,
+                metadata: self.metadata,
+            }
+        })
+    }
+
+    /// Find and decode any errors in fields of this event which have the type `DispatchResult`
+    /// or `DispatchError`. Fields containing a successful `DispatchResult` are ignored.
+    ///
+    /// See [`Self::nested_dispatch_error()`] to only look at well known events which report the
+    /// result of dispatching some inner call.
+    pub fn dispatch_errors(
+        &self,
+    ) -> impl Iterator<Item = Result<DispatchError, DispatchErrorDecodeError>> {
+        self.fields().filter_map(|field| field.dispatch_error())
+    }
+
+    /// If this is one of a handful of well known events which report the result of dispatching
+    /// an inner call (for instance `Utility::BatchInterrupted` or `Proxy::ProxyExecuted`), and that
+    /// result is an error, then decode and return the error. Otherwise, return `None`.
+    ///
+    /// Such errors do not lead to an `ExtrinsicFailed` event, and so are not noticed by
+    /// [`crate::transactions::TransactionInBlock::wait_for_success()`].
+    pub fn nested_dispatch_error(&self) -> Option<Result<DispatchError, DispatchErrorDecodeError>> {
+        let is_known_event =
+            NESTED_DISPATCH_RESULT_EVENTS
+                .iter()
+                .any(|(pallet_name, event_name)| {
+                    *pallet_name == self.pallet_name() && *event_name == self.event_name()
+                });
+
+        if is_known_event {
+            self.dispatch_errors().next()
+        } else {
+            None
+        }
+    }
+
     /// Fetch details from the metadata for this event. This is used for decoding but
     /// we try to avoid using it elsewhere.
     fn event_metadata(&self) -> EventMetadataDetails<'_> {
@@ -426,6 +551,121 @@ impl<'events, T: Config> Event<'events, T> {
     }
 }
 
+// Events which report the result of dispatching some inner call in one of their fields.
+const NESTED_DISPATCH_RESULT_EVENTS: &[(&str, &str)] = &[
+    ("Utility", "BatchInterrupted"),
+    ("Utility", "ItemFailed"),
+    ("Utility", "DispatchedAs"),
+    ("Proxy", "ProxyExecuted"),
+    ("Sudo", "Sudid"),
+    ("Sudo", "SudoAsDone"),
+    ("Multisig", "MultisigExecuted"),
+];
+
+/// A field in an event.
+#[derive(Debug, Clone)]
+pub struct EventField<'event> {
+    bytes: &'event [u8],
+    name: Option<&'event str>,
+    type_id: u32,
+    metadata: &'event ArcMetadata,
+}
+
+impl<'event> EventField<'event> {
+    /// The bytes for this field.
+    pub fn bytes(&self) -> &'event [u8] {
+        self.bytes
+    }
+
+    /// Name of this field, if it has one.
+    pub fn name(&self) -> Option<&'event str> {
+        self.name
+    }
+
+    /// The type ID for this field.
+    pub fn type_id(&self) -> u32 {
+        self.type_id
+    }
+
+    /// Decode this field into the given type.
+    pub fn decode_as<E: DecodeAsType>(&self) -> Result<E, scale_decode::Error> {
+        E::decode_as_type(&mut &*self.bytes, self.type_id, self.metadata.types())
+    }
+
+    /// Visit this field with the provided visitor, returning the output from it.
+    pub fn visit<V>(&self, visitor: V) -> Result<V::Value<'event, '_>, V::Error>
+    where
+        V: scale_decode::visitor::Visitor<TypeResolver = PortableRegistry>,
+    {
+        scale_decode::visitor::decode_with_visitor(
+            &mut &*self.bytes,
+            self.type_id,
+            self.metadata.types(),
+            visitor,
+        )
+    }
+
+    /// Does this field have the type `DispatchResult`, ie `Result<(), DispatchError>`?
+    pub fn is_dispatch_result(&self) -> bool {
+        self.dispatch_result_err_index().is_some()
+    }
+
+    /// Does this field have the type `DispatchError`?
+    pub fn is_dispatch_error(&self) -> bool {
+        self.metadata.dispatch_error_ty() == Some(self.type_id)
+    }
+
+    /// If this field is a `DispatchError`, or a `DispatchResult` containing an error, then
+    /// decode and return the error. Otherwise, return `None`.
+    pub fn dispatch_error(&self) -> Option<Result<DispatchError, DispatchErrorDecodeError>> {
+        if self.is_dispatch_error() {
+            return Some(DispatchError::decode_from(
+                self.bytes,
+                self.metadata.clone(),
+            ));
+        }
+
+        let err_index = self.dispatch_result_err_index()?;
+        match self.bytes.split_first() {
+            Some((variant_index, error_bytes)) if *variant_index == err_index => Some(
+                DispatchError::decode_from(error_bytes, self.metadata.clone()),
+            ),
+            _ => None,
+        }
+    }
+
+    // If the field is a `Result<(), DispatchError>`, return the index of the `Err` variant.
+    fn dispatch_result_err_index(&self) -> Option<u8> {
+        use scale_info::TypeDef;
+
+        let dispatch_error_ty = self.metadata.dispatch_error_ty()?;
+        let ty = self.metadata.types().resolve(self.type_id)?;
+        if ty.path.ident().as_deref() != Some("Result") {
+            return None;
+        }
+        let TypeDef::Variant(variant) = &ty.type_def else {
+            return None;
+        };
+
+        let ok = variant.variants.iter().find(|v| v.name == "Ok")?;
+        let err = variant.variants.iter().find(|v| v.name == "Err")?;
+
+        let [ok_field] = &ok.fields[..] else {
+            return None;
+        };
+        let [err_field] = &err.fields[..] else {
+            return None;
+        };
+
+        let ok_is_unit = matches!(
+            &self.metadata.types().resolve(ok_field.ty.id)?.type_def,
+            TypeDef::Tuple(tuple) if tuple.fields.is_empty()
+        );
+
+        (ok_is_unit && err_field.ty.id == dispatch_error_ty).then_some(err.index)
+    }
+}
+
 // The storage key needed to access events.
 fn system_events_key() -> [u8; 32] {
     let a = sp_crypto_hashing::twox_128(b"System");
@@ -443,3 +683,265 @@ struct EventMetadataDetails<'a> {
     /// Metadata for the variant which describes the pallet events.
     pub variant: &'a scale_info::Variant<scale_info::form::PortableForm>,
 }
+
+/// Event related test utilities used outside this module.
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use super::*;
+    use crate::config::SubstrateConfig;
+    use frame_metadata::{
+        RuntimeMetadataPrefixed,
+        v15::{
+            CustomMetadata, ExtrinsicMetadata, OuterEnums, PalletEventMetadata, PalletMetadata,
+            RuntimeMetadataV15,
+        },
+    };
+    use primitive_types::H256;
+    use scale_info::{TypeInfo, meta_type};
+
+    /// An "outer" events enum containing exactly one type of event.
+    #[derive(Encode, Decode, TypeInfo, Clone, Debug, PartialEq, Eq)]
+    pub enum AllEvents<Ev> {
+        Test(Ev),
+    }
+
+    /// This encodes to the same format an event is expected to encode to
+    /// in node System.Events storage.
+    #[derive(Encode)]
+    pub struct EventRecord<E: Encode> {
+        phase: Phase,
+        event: AllEvents<E>,
+        topics: Vec<H256>,
+    }
+
+    /// Build an EventRecord, which encodes to the same format an event is
+    /// expected to encode to in node System.Events storage.
+    pub fn event_record<E: Encode>(phase: Phase, event: E) -> EventRecord<E> {
+        EventRecord {
+            phase,
+            event: AllEvents::Test(event),
+            topics: vec![],
+        }
+    }
+
+    /// Build fake metadata consisting of a single pallet called "Test" which
+    /// knows about the event type provided.
+    pub fn metadata<E: TypeInfo + 'static>() -> ArcMetadata {
+        metadata_with_pallet_name::<E>("Test")
+    }
+
+    /// Build fake metadata consisting of a single pallet with the given name
+    /// which knows about the event type provided.
+    pub fn metadata_with_pallet_name<E: TypeInfo + 'static>(
+        pallet_name: &'static str,
+    ) -> ArcMetadata {
+        // Extrinsic needs to contain at least the generic type parameter "Call"
+        // for the metadata to be valid.
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        struct ExtrinsicType<Call> {
+            call: Call,
+        }
+        // Because this type is used to decode extrinsics, we expect this to be a TypeDefVariant.
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        enum RuntimeCall {
+            PalletName(Pallet),
+        }
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        enum Pallet {
+            SomeCall,
+        }
+
+        let pallets = vec![PalletMetadata {
+            name: pallet_name,
+            storage: None,
+            calls: None,
+            event: Some(PalletEventMetadata {
+                ty: meta_type::<E>(),
+            }),
+            constants: vec![],
+            error: None,
+            index: 0,
+            docs: vec![],
+        }];
+
+        let extrinsic = ExtrinsicMetadata {
+            version: 4,
+            signed_extensions: vec![],
+            address_ty: meta_type::<()>(),
+            call_ty: meta_type::<RuntimeCall>(),
+            signature_ty: meta_type::<()>(),
+            extra_ty: meta_type::<()>(),
+        };
+
+        let meta = RuntimeMetadataV15::new(
+            pallets,
+            extrinsic,
+            meta_type::<()>(),
+            vec![],
+            OuterEnums {
+                call_enum_ty: meta_type::<()>(),
+                event_enum_ty: meta_type::<AllEvents<E>>(),
+                error_enum_ty: meta_type::<()>(),
+            },
+            CustomMetadata {
+                map: Default::default(),
+            },
+        );
+        let runtime_metadata: RuntimeMetadataPrefixed = meta.into();
+        let metadata: subxt_metadata::Metadata = runtime_metadata.try_into().unwrap();
+
+        Arc::new(metadata)
+    }
+
+    /// Build an `Events` object for test purposes, based on the details provided,
+    /// and with a default block hash.
+    pub fn events<E: Encode>(
+        metadata: ArcMetadata,
+        event_records: Vec<EventRecord<E>>,
+    ) -> Events<SubstrateConfig> {
+        let num_events = event_records.len() as u32;
+        let mut event_bytes = Compact(num_events).encode();
+        for ev in event_records {
+            ev.encode_to(&mut event_bytes);
+        }
+
+        Events {
+            metadata,
+            start_idx: Compact(num_events).encoded_size(),
+            event_bytes: event_bytes.into(),
+            num_events,
+            marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::test_utils::{event_record, events, metadata, metadata_with_pallet_name};
+    use super::*;
+    use scale_info::TypeInfo;
+
+    #[derive(Clone, Debug, PartialEq, Decode, Encode, TypeInfo)]
+    enum TestEvent {
+        A(u8),
+        B(bool),
+    }
+
+    #[test]
+    fn group_by_phase_covers_every_phase() {
+        let metadata = metadata::<TestEvent>();
+        let events = events(
+            metadata,
+            vec![
+                event_record(Phase::Initialization, TestEvent::A(1)),
+                event_record(Phase::ApplyExtrinsic(0), TestEvent::B(true)),
+                event_record(Phase::ApplyExtrinsic(3), TestEvent::A(2)),
+                event_record(Phase::ApplyExtrinsic(0), TestEvent::A(3)),
+                // A sparse, huge index must not lead to a huge allocation.
+                event_record(Phase::ApplyExtrinsic(u32::MAX), TestEvent::A(4)),
+                event_record(Phase::Finalization, TestEvent::B(false)),
+            ],
+        );
+
+        let grouped = events.group_by_phase().unwrap();
+        let indexes = |evs: &[Event<'_, _>]| evs.iter().map(|e| e.index()).collect::<Vec<_>>();
+
+        assert_eq!(indexes(grouped.initialization()), vec![0]);
+        assert_eq!(indexes(grouped.finalization()), vec![5]);
+        assert_eq!(indexes(grouped.extrinsic(0)), vec![1, 3]);
+        assert_eq!(indexes(grouped.extrinsic(3)), vec![2]);
+        assert_eq!(indexes(grouped.extrinsic(u32::MAX as usize)), vec![4]);
+        assert!(grouped.extrinsic(1).is_empty());
+        assert!(grouped.extrinsic(usize::MAX).is_empty());
+
+        let extrinsics: Vec<_> = grouped
+            .extrinsics()
+            .map(|(idx, evs)| (idx, indexes(evs)))
+            .collect();
+        assert_eq!(
+            extrinsics,
+            vec![(0, vec![1, 3]), (3, vec![2]), (u32::MAX as usize, vec![4])]
+        );
+    }
+
+    #[derive(Clone, Debug, Encode, TypeInfo)]
+    enum UtilityEvent {
+        BatchInterrupted {
+            index: u32,
+            error: sp_runtime::DispatchError,
+        },
+        DispatchedAs {
+            result: Result<(), sp_runtime::DispatchError>,
+        },
+        SomethingElse {
+            result: Result<(), sp_runtime::DispatchError>,
+        },
+    }
+
+    #[test]
+    fn dispatch_errors_are_found_in_event_fields() {
+        let metadata = metadata_with_pallet_name::<UtilityEvent>("Utility");
+        let events = events(
+            metadata,
+            vec![
+                event_record(
+                    Phase::ApplyExtrinsic(0),
+                    UtilityEvent::BatchInterrupted {
+                        index: 2,
+                        error: sp_runtime::DispatchError::BadOrigin,
+                    },
+                ),
+                event_record(
+                    Phase::ApplyExtrinsic(0),
+                    UtilityEvent::DispatchedAs { result: Ok(()) },
+                ),
+                event_record(
+                    Phase::ApplyExtrinsic(0),
+                    UtilityEvent::DispatchedAs {
+                        result: Err(sp_runtime::DispatchError::CannotLookup),
+                    },
+                ),
+                event_record(
+                    Phase::ApplyExtrinsic(0),
+                    UtilityEvent::SomethingElse {
+                        result: Err(sp_runtime::DispatchError::BadOrigin),
+                    },
+                ),
+            ],
+        );
+        let evs: Vec<_> = events.iter().collect::<Result<_, _>>().unwrap();
+
+        // The fields of the event are exposed along with their names and types.
+        let fields: Vec<_> = evs[0].fields().collect();
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].name(), Some("index"));
+        assert_eq!(fields[0].decode_as::<u32>().unwrap(), 2);
+        assert!(!fields[0].is_dispatch_error());
+        assert_eq!(fields[1].name(), Some("error"));
+        assert!(fields[1].is_dispatch_error());
+        assert!(matches!(
+            evs[0].nested_dispatch_error(),
+            Some(Ok(DispatchError::BadOrigin))
+        ));
+
+        // Successful results are not errors.
+        assert!(evs[1].fields().next().unwrap().is_dispatch_result());
+        assert!(evs[1].dispatch_errors().next().is_none());
+        assert!(evs[1].nested_dispatch_error().is_none());
+
+        // Errors are found inside of a `DispatchResult`.
+        assert!(matches!(
+            evs[2].nested_dispatch_error(),
+            Some(Ok(DispatchError::CannotLookup))
+        ));
+
+        // Errors in events which aren't known to report nested results are only
+        // found via `dispatch_errors()`.
+        assert!(evs[3].nested_dispatch_error().is_none());
+        let errors: Vec<_> = evs[3].dispatch_errors().collect();
+        assert!(matches!(&errors[..], [Ok(DispatchError::BadOrigin)]));
+    }
+}
@@ -9,7 +9,7 @@ use crate::config::{Config, HashFor, Hasher};
 use crate::error::{
     EventsError, ExtrinsicDecodeErrorAt, ExtrinsicDecodeErrorAtReason, ExtrinsicError,
 };
-use crate::events::{self, DecodeAsEvent};
+use crate::events::{self, DecodeAsEvent, GroupedEvents};
 use frame_decode::extrinsics::Extrinsic as ExtrinsicInfo;
 use scale_decode::{DecodeAsFields, DecodeAsType};
 use scale_info::PortableRegistry;
@@ -169,6 +169,25 @@ impl<'atblock, T: Config, C: OfflineClientAtBlockT<T>> Extrinsics<'atblock, T, C
     pub fn has<E: DecodeAsExtrinsic>(&self) -> bool {
         self.iter().filter_map(|e| e.ok()).any(|e| e.is::<E>())
     }
+
+    /// Iterate over the extrinsics in the block body, pairing each one with the events that it
+    /// emitted. The events are obtained by calling [`events::Events::group_by_phase()`] on the
+    /// events from the same block.
+    pub fn iter_with_events<'a, 'events>(
+        &'a self,
+        events: &'a GroupedEvents<'events, T>,
+    ) -> impl Iterator<
+        Item = Result<
+            (Extrinsic<'a, T, C>, &'a [events::Event<'events, T>]),
+            ExtrinsicDecodeErrorAt,
+        >,
+    > {
+        self.iter().map(move |extrinsic| {
+            let extrinsic = extrinsic?;
+            let extrinsic_events = events.extrinsic(extrinsic.index());
+            Ok((extrinsic, extrinsic_events))
+        })
+    }
 }
 
 /// A single extrinsic in a block.
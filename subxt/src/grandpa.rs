@@ -0,0 +1,501 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+//! This module exposes [`GrandpaClient`], which has methods for fetching, decoding and verifying
+//! GRANDPA finality justifications. It's created by calling [`crate::client::ClientAtBlock::grandpa()`].
+//!
+//! Justifications can be obtained for the current block via [`GrandpaClient::justification()`],
+//! or from a [`GrandpaFinalityProof`], whose bytes can be obtained for any finalized block by calling
+//! [`crate::rpcs::methods::LegacyRpcMethods::grandpa_prove_finality()`].
+//!
+//! With the `grandpa-verification` feature enabled, [`GrandpaJustification::verify()`] can then be
+//! used to check a justification offline against some [`GrandpaAuthoritySet`], which can be
+//! fetched using [`GrandpaClient::authority_set()`] or supplied manually.
+
+use crate::client::{OfflineClientAtBlockT, OnlineClientAtBlockT};
+use crate::config::substrate::GRANDPA_ENGINE_ID;
+use crate::config::{Config, HashFor};
+use crate::error::GrandpaError;
+use codec::{Decode, DecodeAll, Encode};
+use std::marker::PhantomData;
+
+#[cfg(feature = "grandpa-verification")]
+use crate::{
+    config::{Hash, Hasher},
+    error::GrandpaVerificationError,
+};
+
+/// A [`GrandpaJustification`] for some [`Config`].
+pub type GrandpaJustificationFor<T> = GrandpaJustification<HashFor<T>, <T as Config>::Header>;
+
+/// A [`GrandpaFinalityProof`] for some [`Config`].
+pub type GrandpaFinalityProofFor<T> = GrandpaFinalityProof<HashFor<T>, <T as Config>::Header>;
+
+/// A client for working with GRANDPA justifications. See [the module docs](crate::grandpa) for more.
+pub struct GrandpaClient<'atblock, T, Client> {
+    client: &'atblock Client,
+    marker: PhantomData<T>,
+}
+
+impl<'atblock, T, Client> GrandpaClient<'atblock, T, Client> {
+    pub(crate) fn new(client: &'atblock Client) -> Self {
+        GrandpaClient {
+            client,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'atblock, T: Config, Client: OfflineClientAtBlockT<T>> GrandpaClient<'atblock, T, Client> {
+    /// Decode some SCALE encoded GRANDPA justification bytes.
+    pub fn decode_justification(
+        &self,
+        bytes: &[u8],
+    ) -> Result<GrandpaJustificationFor<T>, GrandpaError> {
+        GrandpaJustification::decode_all(&mut &*bytes)
+            .map_err(GrandpaError::CannotDecodeJustification)
+    }
+
+    /// Decode some SCALE encoded finality proof bytes, as returned from
+    /// [`crate::rpcs::methods::LegacyRpcMethods::grandpa_prove_finality()`].
+    pub fn decode_finality_proof(
+        &self,
+        bytes: &[u8],
+    ) -> Result<GrandpaFinalityProofFor<T>, GrandpaError> {
+        GrandpaFinalityProof::decode_all(&mut &*bytes)
+            .map_err(GrandpaError::CannotDecodeFinalityProof)
+    }
+}
+
+impl<'atblock, T: Config, Client: OnlineClientAtBlockT<T>> GrandpaClient<'atblock, T, Client> {
+    /// Fetch and decode the GRANDPA justification for this block. This returns `None` if
+    /// the block has no GRANDPA justification, which is the case for most blocks; nodes
+    /// typically only store justifications for blocks which enact an authority set change
+    /// (and periodically beyond that).
+    ///
+    /// **Note:** Justifications can only be obtained via the legacy `chain_getBlock` RPC method,
+    /// and so this will return an error if the backend does not support this.
+    pub async fn justification(&self) -> Result<Option<GrandpaJustificationFor<T>>, GrandpaError> {
+        let block_hash = self.client.block_ref().hash();
+        let justifications = self
+            .client
+            .backend()
+            .block_justifications(block_hash)
+            .await
+            .map_err(GrandpaError::CannotFetchJustifications)?
+            .ok_or_else(|| GrandpaError::BlockNotFound {
+                block_hash: block_hash.into(),
+            })?;
+
+        justifications
+            .into_iter()
+            .find(|(engine_id, _)| *engine_id == GRANDPA_ENGINE_ID)
+            .map(|(_, bytes)| self.decode_justification(&bytes))
+            .transpose()
+    }
+
+    /// Fetch the GRANDPA authority set at this block, using the `GrandpaApi_grandpa_authorities`
+    /// and `GrandpaApi_current_set_id` runtime APIs.
+    pub async fn authority_set(&self) -> Result<GrandpaAuthoritySet, GrandpaError> {
+        let block_hash = self.client.block_ref().hash();
+        let backend = self.client.backend();
+
+        let authorities_bytes = backend
+            .call("GrandpaApi_grandpa_authorities", None, block_hash)
+            .await
+            .map_err(GrandpaError::CannotFetchAuthoritySet)?;
+        let authorities = Vec::<([u8; 32], u64)>::decode_all(&mut &*authorities_bytes)
+            .map_err(GrandpaError::CannotDecodeAuthoritySet)?;
+
+        let set_id_bytes = backend
+            .call("GrandpaApi_current_set_id", None, block_hash)
+            .await
+            .map_err(GrandpaError::CannotFetchAuthoritySet)?;
+        let set_id =
+            u64::decode_all(&mut &*set_id_bytes).map_err(GrandpaError::CannotDecodeAuthoritySet)?;
+
+        Ok(GrandpaAuthoritySet {
+            set_id,
+            authorities: authorities
+                .into_iter()
+                .map(|(id, weight)| GrandpaAuthority { id, weight })
+                .collect(),
+        })
+    }
+}
+
+/// A set of GRANDPA authorities which can be used to verify a [`GrandpaJustification`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrandpaAuthoritySet {
+    /// The ID of the authority set. This is incremented every time the authority set changes.
+    pub set_id: u64,
+    /// The authorities in the set.
+    pub authorities: Vec<GrandpaAuthority>,
+}
+
+/// A single GRANDPA authority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GrandpaAuthority {
+    /// The ed25519 public key of the authority.
+    pub id: [u8; 32],
+    /// The voting weight of the authority.
+    pub weight: u64,
+}
+
+/// A GRANDPA justification for block finality. From `sc_consensus_grandpa::GrandpaJustification`.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct GrandpaJustification<Hash, Header> {
+    /// The round in which the commit was made.
+    pub round: u64,
+    /// The commit, containing the block being finalized and the signed precommits for it.
+    pub commit: GrandpaCommit<Hash>,
+    /// The headers needed to show that each precommit target is a descendant of the
+    /// commit target.
+    pub votes_ancestries: Vec<Header>,
+}
+
+/// A GRANDPA commit message. From `finality_grandpa::Commit`.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct GrandpaCommit<Hash> {
+    /// The hash of the block being finalized.
+    pub target_hash: Hash,
+    /// The number of the block being finalized.
+    pub target_number: u32,
+    /// The precommits justifying this commit.
+    pub precommits: Vec<GrandpaSignedPrecommit<Hash>>,
+}
+
+/// A signed precommit message. From `finality_grandpa::SignedPrecommit`.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct GrandpaSignedPrecommit<Hash> {
+    /// The precommit message that was signed.
+    pub precommit: GrandpaPrecommit<Hash>,
+    /// The ed25519 signature of the precommit.
+    pub signature: [u8; 64],
+    /// The ed25519 public key of the authority which signed the precommit.
+    pub id: [u8; 32],
+}
+
+/// A precommit for a block and its ancestors. From `finality_grandpa::Precommit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub struct GrandpaPrecommit<Hash> {
+    /// The hash of the target block.
+    pub target_hash: Hash,
+    /// The number of the target block.
+    pub target_number: u32,
+}
+
+/// A GRANDPA finality proof. From `sc_consensus_grandpa::FinalityProof`.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct GrandpaFinalityProof<Hash, Header> {
+    /// The hash of the block which is finalized by the justification.
+    pub block: Hash,
+    /// The SCALE encoded [`GrandpaJustification`] for the block.
+    pub justification: Vec<u8>,
+    /// The headers from the block that the proof was requested for, up to (but
+    /// excluding) the block that the justification is for.
+    pub unknown_headers: Vec<Header>,
+}
+
+impl<Hash: Decode, Header: Decode> GrandpaFinalityProof<Hash, Header> {
+    /// Decode the justification contained within this finality proof.
+    pub fn decode_justification(&self) -> Result<GrandpaJustification<Hash, Header>, GrandpaError> {
+        GrandpaJustification::decode_all(&mut &*self.justification)
+            .map_err(GrandpaError::CannotDecodeJustification)
+    }
+}
+
+#[cfg(feature = "grandpa-verification")]
+impl<H: Hash, Header: Encode> GrandpaJustification<H, Header> {
+    /// Verify this justification against the given authority set, returning an error if
+    /// it is not valid. This checks that:
+    ///
+    /// - Every precommit is signed by a member of the authority set, and the signature is valid
+    ///   for the round and authority set ID.
+    /// - Every precommit target is either the commit target or a descendant of it, as proven by
+    ///   the headers in [`Self::votes_ancestries`], and every one of those headers is needed to
+    ///   prove this.
+    /// - The precommits come from authorities with more than two thirds of the total voting weight.
+    ///
+    /// The `hasher` is used to hash the ancestry headers; use [`crate::client::ClientAtBlock::hasher()`].
+    ///
+    /// **Note:** This does not check that [`GrandpaCommit::target_hash`] is the block that you
+    /// expect to be finalized; you should check this yourself.
+    pub fn verify<Hs: Hasher<Hash = H>>(
+        &self,
+        authority_set: &GrandpaAuthoritySet,
+        hasher: &Hs,
+    ) -> Result<(), GrandpaVerificationError> {
+        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+        use std::collections::{HashMap, HashSet};
+
+        let total_weight = authority_set
+            .authorities
+            .iter()
+            .fold(0u64, |acc, a| acc.saturating_add(a.weight));
+        if total_weight == 0 {
+            return Err(GrandpaVerificationError::EmptyAuthoritySet);
+        }
+        // This matches `finality_grandpa::VoterSet::threshold()`.
+        let threshold = total_weight - (total_weight - 1) / 3;
+
+        // Map each ancestry header hash to its parent hash. Substrate headers begin
+        // with the parent hash, so we decode it from the front of the encoded header.
+        let mut parents = HashMap::new();
+        for header in &self.votes_ancestries {
+            let header_bytes = header.encode();
+            let parent_hash = H::decode(&mut &*header_bytes)
+                .map_err(GrandpaVerificationError::CannotDecodeAncestryHeader)?;
+            parents.insert(hasher.hash(&header_bytes), parent_hash);
+        }
+
+        let mut seen_authorities = HashSet::new();
+        let mut visited_ancestries = HashSet::new();
+        let mut signed_weight = 0u64;
+
+        for signed in &self.commit.precommits {
+            let authority = authority_set
+                .authorities
+                .iter()
+                .find(|a| a.id == signed.id)
+                .ok_or_else(|| GrandpaVerificationError::UnknownAuthority {
+                    authority_id: signed.id.into(),
+                })?;
+
+            // The signed payload is `(Message::Precommit(precommit), round, set_id)`, where
+            // the `Precommit` variant of `finality_grandpa::Message` has index 1.
+            let payload = (1u8, &signed.precommit, self.round, authority_set.set_id).encode();
+            let is_valid_signature = VerifyingKey::from_bytes(&signed.id)
+                .map(|key| {
+                    key.verify(&payload, &Signature::from_bytes(&signed.signature))
+                        .is_ok()
+                })
+                .unwrap_or(false);
+            if !is_valid_signature {
+                return Err(GrandpaVerificationError::InvalidSignature {
+                    authority_id: signed.id.into(),
+                });
+            }
+
+            // Walk back from the precommit target to the commit target.
+            let mut current = signed.precommit.target_hash;
+            while current != self.commit.target_hash {
+                visited_ancestries.insert(current);
+                current = *parents.get(&current).ok_or_else(|| {
+                    GrandpaVerificationError::InvalidAncestry {
+                        precommit_target: signed.precommit.target_hash.into(),
+                    }
+                })?;
+            }
+
+            // Equivocating authorities may sign more than one precommit; count them once.
+            if seen_authorities.insert(signed.id) {
+                signed_weight = signed_weight.saturating_add(authority.weight);
+            }
+        }
+
+        // Like `finality_grandpa`, reject justifications carrying headers which don't
+        // lie on the path from any precommit target to the commit target.
+        if let Some(unused) = parents.keys().find(|h| !visited_ancestries.contains(*h)) {
+            return Err(GrandpaVerificationError::UnusedAncestry {
+                header_hash: (*unused).into(),
+            });
+        }
+
+        if signed_weight < threshold {
+            return Err(GrandpaVerificationError::InsufficientWeight {
+                signed_weight,
+                threshold,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "grandpa-verification"))]
+mod test {
+    use super::*;
+    use crate::config::substrate::{Digest, H256, SubstrateHeader};
+    use crate::metadata::Metadata;
+    use sp_core::{Pair, ed25519};
+
+    #[derive(Debug, Clone)]
+    struct BlakeTwo256Hasher;
+
+    impl Hasher for BlakeTwo256Hasher {
+        type Hash = H256;
+        fn new(_metadata: &Metadata) -> Self {
+            BlakeTwo256Hasher
+        }
+        fn hash(&self, s: &[u8]) -> Self::Hash {
+            sp_crypto_hashing::blake2_256(s).into()
+        }
+    }
+
+    const ROUND: u64 = 7;
+    const SET_ID: u64 = 3;
+
+    fn pairs() -> Vec<ed25519::Pair> {
+        (1..=4u8)
+            .map(|n| ed25519::Pair::from_seed(&[n; 32]))
+            .collect()
+    }
+
+    fn authority_set(pairs: &[ed25519::Pair]) -> GrandpaAuthoritySet {
+        GrandpaAuthoritySet {
+            set_id: SET_ID,
+            authorities: pairs
+                .iter()
+                .map(|p| GrandpaAuthority {
+                    id: p.public().0,
+                    weight: 1,
+                })
+                .collect(),
+        }
+    }
+
+    fn sign(
+        pair: &ed25519::Pair,
+        precommit: GrandpaPrecommit<H256>,
+        set_id: u64,
+    ) -> GrandpaSignedPrecommit<H256> {
+        let payload = (1u8, &precommit, ROUND, set_id).encode();
+        GrandpaSignedPrecommit {
+            precommit,
+            signature: pair.sign(&payload).0,
+            id: pair.public().0,
+        }
+    }
+
+    fn justification(
+        precommits: Vec<GrandpaSignedPrecommit<H256>>,
+        votes_ancestries: Vec<SubstrateHeader<H256>>,
+    ) -> GrandpaJustification<H256, SubstrateHeader<H256>> {
+        GrandpaJustification {
+            round: ROUND,
+            commit: GrandpaCommit {
+                target_hash: H256::repeat_byte(1),
+                target_number: 100,
+                precommits,
+            },
+            votes_ancestries,
+        }
+    }
+
+    fn target() -> GrandpaPrecommit<H256> {
+        GrandpaPrecommit {
+            target_hash: H256::repeat_byte(1),
+            target_number: 100,
+        }
+    }
+
+    #[test]
+    fn valid_justification_verifies() {
+        let pairs = pairs();
+        let precommits = pairs[..3]
+            .iter()
+            .map(|p| sign(p, target(), SET_ID))
+            .collect();
+
+        let justification = justification(precommits, vec![]);
+        let bytes = justification.encode();
+        let decoded = GrandpaJustification::<H256, SubstrateHeader<H256>>::decode(&mut &*bytes)
+            .expect("justification should decode");
+
+        assert_eq!(decoded, justification);
+        decoded
+            .verify(&authority_set(&pairs), &BlakeTwo256Hasher)
+            .expect("justification should be valid");
+    }
+
+    #[test]
+    fn precommits_for_descendants_verify() {
+        let pairs = pairs();
+        let child = SubstrateHeader {
+            parent_hash: H256::repeat_byte(1),
+            number: 101,
+            state_root: H256::zero(),
+            extrinsics_root: H256::zero(),
+            digest: Digest::default(),
+        };
+        let child_precommit = GrandpaPrecommit {
+            target_hash: BlakeTwo256Hasher.hash(&child.encode()),
+            target_number: 101,
+        };
+
+        let precommits = vec![
+            sign(&pairs[0], target(), SET_ID),
+            sign(&pairs[1], target(), SET_ID),
+            sign(&pairs[2], child_precommit, SET_ID),
+        ];
+
+        justification(precommits.clone(), vec![child.clone()])
+            .verify(&authority_set(&pairs), &BlakeTwo256Hasher)
+            .expect("justification should be valid");
+
+        // Without the ancestry, we can't prove that the child descends from the target.
+        assert!(matches!(
+            justification(precommits.clone(), vec![])
+                .verify(&authority_set(&pairs), &BlakeTwo256Hasher),
+            Err(GrandpaVerificationError::InvalidAncestry { .. })
+        ));
+
+        // Headers which aren't needed to prove any ancestry are rejected.
+        let unrelated = SubstrateHeader {
+            parent_hash: H256::repeat_byte(2),
+            ..child.clone()
+        };
+        assert!(matches!(
+            justification(precommits, vec![child, unrelated])
+                .verify(&authority_set(&pairs), &BlakeTwo256Hasher),
+            Err(GrandpaVerificationError::UnusedAncestry { .. })
+        ));
+    }
+
+    #[test]
+    fn insufficient_weight_fails() {
+        let pairs = pairs();
+        // The same authority signing twice doesn't count twice.
+        let precommits = vec![
+            sign(&pairs[0], target(), SET_ID),
+            sign(&pairs[1], target(), SET_ID),
+            sign(&pairs[1], target(), SET_ID),
+        ];
+
+        assert!(matches!(
+            justification(precommits, vec![]).verify(&authority_set(&pairs), &BlakeTwo256Hasher),
+            Err(GrandpaVerificationError::InsufficientWeight {
+                signed_weight: 2,
+                threshold: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn bad_signatures_and_unknown_authorities_fail() {
+        let pairs = pairs();
+
+        // Signed for the wrong authority set ID:
+        let precommits = pairs[..3]
+            .iter()
+            .map(|p| sign(p, target(), SET_ID + 1))
+            .collect();
+        assert!(matches!(
+            justification(precommits, vec![]).verify(&authority_set(&pairs), &BlakeTwo256Hasher),
+            Err(GrandpaVerificationError::InvalidSignature { .. })
+        ));
+
+        // Signed by an authority not in the set:
+        let precommits = pairs[..3]
+            .iter()
+            .map(|p| sign(p, target(), SET_ID))
+            .collect();
+        assert!(matches!(
+            justification(precommits, vec![])
+                .verify(&authority_set(&pairs[1..]), &BlakeTwo256Hasher),
+            Err(GrandpaVerificationError::UnknownAuthority { .. })
+        ));
+    }
+}
@@ -48,6 +48,7 @@ pub mod dynamic;
 pub mod error;
 pub mod events;
 pub mod extrinsics;
+pub mod grandpa;
 pub mod introduction;
 pub mod runtime_apis;
 pub mod storage;
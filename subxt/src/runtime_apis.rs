@@ -4,18 +4,34 @@
 
 //! This module exposes [`RuntimeApisClient`], which has methods for calling Runtime APIs.
 //! It's created by calling [`crate::client::ClientAtBlock::runtime_apis()`].
-
+//!
+//! With the `local-executor` feature enabled, Runtime APIs can also be executed locally against
+//! the runtime code at some block (or some other runtime code) by calling `RuntimeApisClient::local()`
+//! or `RuntimeApisClient::local_with_code()`.
+
+mod call_multi;
+#[cfg(feature = "local-executor")]
+mod local_executor;
 mod payload;
 
 use crate::client::{OfflineClientAtBlockT, OnlineClientAtBlockT};
 use crate::config::Config;
+use crate::error::BackendError;
 use crate::error::RuntimeApiError;
 use derive_where::derive_where;
+use futures::StreamExt;
 use scale_decode::IntoVisitor;
 use std::marker::PhantomData;
 
+pub use call_multi::{CallMulti, CallMultiItem};
 pub use payload::{DynamicPayload, Payload, StaticPayload, dynamic};
 
+#[cfg(feature = "local-executor")]
+pub use local_executor::LocalRuntimeApis;
+
+/// The number of calls that we'll have in progress at once, if the backend has no limit.
+const DEFAULT_MAX_CONCURRENT_CALLS: usize = 16;
+
 /// A client for working with Runtime APIs. See [the module docs](crate::runtime_apis) for more.
 #[derive_where(Clone; Client)]
 pub struct RuntimeApisClient<'atblock, T: Config, Client> {
@@ -146,4 +162,84 @@ where
 
         Ok(value)
     }
+
+    /// Make several Runtime API and/or View Function calls at this block, handing back a
+    /// tuple containing the result of each call in the order that they were given.
+    ///
+    /// The calls are all sent to the node without waiting for earlier ones to complete
+    /// (up to the limit that the backend allows), which is much faster than calling
+    /// [`RuntimeApisClient::call()`] for each one in turn.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let (version, account_nonce) = at_block
+    ///     .runtime_apis()
+    ///     .call_multi((
+    ///         polkadot::apis().core().version(),
+    ///         polkadot::apis().account_nonce_api().account_nonce(alice),
+    ///     ))
+    ///     .await;
+    /// ```
+    pub async fn call_multi<Calls>(&self, calls: Calls) -> Calls::Output
+    where
+        Calls: CallMulti<'atblock, T, Client>,
+    {
+        let (prepared, raw_calls) = calls.prepare(self.client);
+        let responses = self.call_raw_many(raw_calls).await;
+        Calls::finish(prepared, self.client, responses)
+    }
+
+    /// Make several Runtime API or View Function calls of the same type at this block,
+    /// handing back the result of each call in the order that they were given. Like
+    /// [`RuntimeApisClient::call_multi()`], calls are sent without waiting for earlier
+    /// ones to complete.
+    pub async fn call_many<Call, Calls>(&self, calls: Calls) -> Vec<Call::Output>
+    where
+        Call: CallMultiItem<'atblock, T, Client>,
+        Calls: IntoIterator<Item = Call>,
+    {
+        let mut raw_calls = Vec::new();
+        let prepared: Vec<_> = calls
+            .into_iter()
+            .map(|call| {
+                call.prepare(self.client).map(|(prepared, name, args)| {
+                    raw_calls.push((name, args));
+                    prepared
+                })
+            })
+            .collect();
+
+        let mut responses = self.call_raw_many(raw_calls).await.into_iter();
+        prepared
+            .into_iter()
+            .map(|prepared| match prepared {
+                Ok(prepared) => {
+                    let response = responses.next().expect("one response per prepared call");
+                    Call::finish(prepared, self.client, response)
+                }
+                Err(output) => output,
+            })
+            .collect()
+    }
+
+    // Make each of the given raw calls, returning the responses in the same order.
+    // There's no JSON-RPC batching here; instead, calls are made concurrently.
+    async fn call_raw_many(
+        &self,
+        calls: Vec<(String, Vec<u8>)>,
+    ) -> Vec<Result<Vec<u8>, BackendError>> {
+        let backend = self.client.backend();
+        let block_hash = self.client.block_ref().hash();
+        let max_concurrent_calls = backend
+            .max_concurrent_calls()
+            .unwrap_or(DEFAULT_MAX_CONCURRENT_CALLS)
+            .max(1);
+
+        futures::stream::iter(calls)
+            .map(|(name, args)| async move { backend.call(&name, Some(&args), block_hash).await })
+            .buffered(max_concurrent_calls)
+            .collect()
+            .await
+    }
 }
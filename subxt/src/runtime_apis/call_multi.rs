@@ -0,0 +1,322 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+use super::{Payload, RuntimeApisClient, StaticPayload};
+use crate::client::OfflineClientAtBlockT;
+use crate::config::Config;
+use crate::error::{BackendError, RuntimeApiError, ViewFunctionError};
+use crate::view_functions::{self, Payload as _, ViewFunctionsClient};
+use scale_decode::{DecodeAsType, IntoVisitor};
+use scale_info_legacy::IntoEncodableValues;
+
+/// A single call that can be given as part of a tuple to [`RuntimeApisClient::call_multi()`],
+/// or in an iterator to [`RuntimeApisClient::call_many()`]. This is implemented for Runtime API
+/// and View Function payloads.
+pub trait CallMultiItem<'atblock, T, Client> {
+    /// The result handed back for this call.
+    type Output;
+    /// Details needed to decode the response.
+    #[doc(hidden)]
+    type Prepared;
+
+    /// Return the details needed to decode the response, along with the name of the
+    /// Runtime API function to call and the encoded arguments to call it with. If the
+    /// call cannot be prepared, the output is handed back immediately.
+    #[doc(hidden)]
+    fn prepare(
+        self,
+        client: &'atblock Client,
+    ) -> Result<(Self::Prepared, String, Vec<u8>), Self::Output>;
+
+    /// Turn the response from the call into the output.
+    #[doc(hidden)]
+    fn finish(
+        prepared: Self::Prepared,
+        client: &'atblock Client,
+        response: Result<Vec<u8>, BackendError>,
+    ) -> Self::Output;
+}
+
+impl<'atblock, T, Client, ArgsType, ReturnType> CallMultiItem<'atblock, T, Client>
+    for StaticPayload<ArgsType, ReturnType>
+where
+    T: Config,
+    Client: OfflineClientAtBlockT<T>,
+    ArgsType: IntoEncodableValues,
+    ReturnType: DecodeAsType,
+{
+    type Output = Result<ReturnType, RuntimeApiError>;
+    type Prepared = Self;
+
+    fn prepare(
+        self,
+        client: &'atblock Client,
+    ) -> Result<(Self::Prepared, String, Vec<u8>), Self::Output> {
+        let runtime_apis = RuntimeApisClient::<T, Client>::new(client);
+        runtime_apis.validate(&self).map_err(Err)?;
+        let call_name = runtime_apis.encode_name(&self);
+        let call_args = runtime_apis.encode_args(&self).map_err(Err)?;
+        Ok((self, call_name, call_args))
+    }
+
+    fn finish(
+        prepared: Self::Prepared,
+        client: &'atblock Client,
+        response: Result<Vec<u8>, BackendError>,
+    ) -> Self::Output {
+        let bytes = response.map_err(RuntimeApiError::CannotCallApi)?;
+        let metadata = client.metadata_ref();
+        frame_decode::runtime_apis::decode_runtime_api_response(
+            prepared.trait_name(),
+            prepared.method_name(),
+            &mut &*bytes,
+            metadata,
+            metadata.types(),
+            ReturnType::into_visitor(),
+        )
+        .map_err(RuntimeApiError::CouldNotDecodeResponse)
+    }
+}
+
+impl<'atblock, T, Client, ArgsType, ReturnType> CallMultiItem<'atblock, T, Client>
+    for view_functions::StaticPayload<ArgsType, ReturnType>
+where
+    T: Config,
+    Client: OfflineClientAtBlockT<T>,
+    ArgsType: IntoEncodableValues,
+    ReturnType: DecodeAsType,
+{
+    type Output = Result<ReturnType, ViewFunctionError>;
+    type Prepared = Self;
+
+    fn prepare(
+        self,
+        client: &'atblock Client,
+    ) -> Result<(Self::Prepared, String, Vec<u8>), Self::Output> {
+        let view_functions = ViewFunctionsClient::<T, Client>::new(client);
+        view_functions.validate(&self).map_err(Err)?;
+        let call_args = view_functions.encode_args(&self).map_err(Err)?;
+        Ok((self, view_functions::CALL_NAME.to_string(), call_args))
+    }
+
+    fn finish(
+        prepared: Self::Prepared,
+        client: &'atblock Client,
+        response: Result<Vec<u8>, BackendError>,
+    ) -> Self::Output {
+        let bytes = response.map_err(ViewFunctionError::CannotCallApi)?;
+        let metadata = client.metadata_ref();
+        frame_decode::view_functions::decode_view_function_response(
+            prepared.pallet_name(),
+            prepared.function_name(),
+            &mut &*bytes,
+            metadata,
+            metadata.types(),
+            ReturnType::into_visitor(),
+        )
+        .map_err(ViewFunctionError::CouldNotDecodeResponse)
+    }
+}
+
+/// A set of Runtime API and View Function calls which can be made together using
+/// [`RuntimeApisClient::call_multi()`]. This is implemented for tuples of [`CallMultiItem`]s.
+pub trait CallMulti<'atblock, T, Client> {
+    /// The results handed back for these calls.
+    type Output;
+    /// Details needed to decode the responses.
+    #[doc(hidden)]
+    type Prepared;
+
+    /// Return the details needed to decode the responses, along with the Runtime API
+    /// function names and encoded arguments for each call that was prepared successfully.
+    #[doc(hidden)]
+    fn prepare(self, client: &'atblock Client) -> (Self::Prepared, Vec<(String, Vec<u8>)>);
+
+    /// Turn the responses, one for each call that was prepared successfully, into the output.
+    #[doc(hidden)]
+    fn finish(
+        prepared: Self::Prepared,
+        client: &'atblock Client,
+        responses: Vec<Result<Vec<u8>, BackendError>>,
+    ) -> Self::Output;
+}
+
+macro_rules! impl_call_multi_tuple {
+    ($($ident:ident $index:tt),+) => {
+        impl<'atblock, Conf, Client, $($ident),+> CallMulti<'atblock, Conf, Client> for ($($ident,)+)
+        where
+            Conf: Config,
+            Client: OfflineClientAtBlockT<Conf>,
+            $($ident: CallMultiItem<'atblock, Conf, Client>,)+
+        {
+            type Output = ($($ident::Output,)+);
+            type Prepared = ($(Result<$ident::Prepared, $ident::Output>,)+);
+
+            fn prepare(self, client: &'atblock Client) -> (Self::Prepared, Vec<(String, Vec<u8>)>) {
+                let mut calls = Vec::new();
+                let prepared = ($(
+                    self.$index.prepare(client).map(|(prepared, name, args)| {
+                        calls.push((name, args));
+                        prepared
+                    }),
+                )+);
+                (prepared, calls)
+            }
+
+            fn finish(
+                prepared: Self::Prepared,
+                client: &'atblock Client,
+                responses: Vec<Result<Vec<u8>, BackendError>>,
+            ) -> Self::Output {
+                let mut responses = responses.into_iter();
+                ($(
+                    match prepared.$index {
+                        Ok(prepared) => {
+                            let response = responses.next().expect("one response per prepared call");
+                            $ident::finish(prepared, client, response)
+                        },
+                        Err(output) => output,
+                    },
+                )+)
+            }
+        }
+    }
+}
+
+#[rustfmt::skip]
+const _: () = {
+    impl_call_multi_tuple!(A 0);
+    impl_call_multi_tuple!(A 0, B 1);
+    impl_call_multi_tuple!(A 0, B 1, C 2);
+    impl_call_multi_tuple!(A 0, B 1, C 2, D 3);
+    impl_call_multi_tuple!(A 0, B 1, C 2, D 3, E 4);
+    impl_call_multi_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+    impl_call_multi_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+    impl_call_multi_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+    impl_call_multi_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+    impl_call_multi_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+    impl_call_multi_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+    impl_call_multi_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+    impl_call_multi_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12);
+    impl_call_multi_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12, N 13);
+    impl_call_multi_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12, N 13, O 14);
+    impl_call_multi_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12, N 13, O 14, P 15);
+};
+
+#[cfg(test)]
+mod test {
+    use crate::backend::LegacyBackend;
+    use crate::client::test_utils::config;
+    use crate::client::{OnlineClient, OnlineClientAtBlock};
+    use crate::config::SubstrateConfig;
+    use crate::config::substrate::H256;
+    use crate::error::RuntimeApiError;
+    use crate::runtime_apis::{self, DynamicPayload};
+    use codec::Encode;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use subxt_rpcs::RpcClient;
+    use subxt_rpcs::client::MockRpcClient;
+    use subxt_rpcs::client::mock_rpc_client::Json;
+
+    // An account for which the node will fail to return a nonce.
+    const FAILING_ACCOUNT: [u8; 32] = [0xff; 32];
+
+    // A client whose node returns the first byte of the account as its nonce. Calls for lower
+    // numbered accounts take longer to respond, so that responses arrive out of order.
+    async fn client() -> OnlineClientAtBlock<SubstrateConfig> {
+        let mock_client = MockRpcClient::builder()
+            .method_handler(
+                "chain_getBlockHash",
+                |_params| async move { Json(H256::zero()) },
+            )
+            .method_handler("state_call", |params| async move {
+                let (method, data, _at): (String, String, Option<H256>) =
+                    serde_json::from_str(params.unwrap().get()).unwrap();
+                assert_eq!(method, "AccountNonceApi_account_nonce");
+
+                let account = hex::decode(data.trim_start_matches("0x")).unwrap();
+                if account == FAILING_ACCOUNT {
+                    return None;
+                }
+
+                let delay = 10u64.saturating_sub(account[0].into());
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                let nonce = u32::from(account[0]).encode();
+                Some(Json(format!("0x{}", hex::encode(nonce))))
+            })
+            .build();
+        let backend = LegacyBackend::builder().build(RpcClient::new(mock_client));
+
+        OnlineClient::from_backend_with_config(config(), Arc::new(backend))
+            .await
+            .unwrap()
+            .at_block_hash_and_number(H256::zero(), 0)
+            .await
+            .unwrap()
+    }
+
+    fn account_nonce(account: [u8; 32]) -> DynamicPayload<([u8; 32],), u32> {
+        runtime_apis::dynamic("AccountNonceApi", "account_nonce", (account,))
+    }
+
+    #[tokio::test]
+    async fn call_multi_hands_back_each_result_in_order() {
+        let client = client().await;
+
+        let (a, b, c, d) = client
+            .runtime_apis()
+            .call_multi((
+                account_nonce([1; 32]),
+                runtime_apis::dynamic::<_, u32>("NotAnApi", "not_a_method", ()),
+                account_nonce(FAILING_ACCOUNT),
+                account_nonce([5; 32]),
+            ))
+            .await;
+
+        assert_eq!(a.unwrap(), 1);
+        assert!(matches!(b, Err(RuntimeApiError::CouldNotEncodeInputs(_))));
+        assert!(matches!(c, Err(RuntimeApiError::CannotCallApi(_))));
+        assert_eq!(d.unwrap(), 5);
+    }
+
+    #[tokio::test]
+    async fn call_many_hands_back_each_result_in_order() {
+        let client = client().await;
+
+        let accounts = [[1; 32], [2; 32], FAILING_ACCOUNT, [3; 32], [4; 32]];
+        let results = client
+            .runtime_apis()
+            .call_many(accounts.into_iter().map(account_nonce))
+            .await;
+
+        assert_eq!(results.len(), accounts.len());
+        assert_eq!(results[0].as_ref().unwrap(), &1);
+        assert_eq!(results[1].as_ref().unwrap(), &2);
+        assert!(matches!(results[2], Err(RuntimeApiError::CannotCallApi(_))));
+        assert_eq!(results[3].as_ref().unwrap(), &3);
+        assert_eq!(results[4].as_ref().unwrap(), &4);
+    }
+
+    #[tokio::test]
+    async fn call_many_handles_calls_that_cannot_be_prepared() {
+        let client = client().await;
+
+        let results = client
+            .runtime_apis()
+            .call_many([
+                account_nonce([1; 32]),
+                runtime_apis::dynamic("NotAnApi", "not_a_method", ([0; 32],)),
+                account_nonce([2; 32]),
+            ])
+            .await;
+
+        assert_eq!(results[0].as_ref().unwrap(), &1);
+        assert!(matches!(
+            results[1],
+            Err(RuntimeApiError::CouldNotEncodeInputs(_))
+        ));
+        assert_eq!(results[2].as_ref().unwrap(), &2);
+    }
+}
@@ -0,0 +1,557 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+use super::{Payload, RuntimeApisClient};
+use crate::backend::StorageFetchDescendantsOptions;
+use crate::client::StorageClientAtBlockT;
+use crate::config::Config;
+use crate::error::RuntimeApiError;
+use crate::metadata::{Metadata, SUPPORTED_METADATA_VERSIONS};
+use crate::storage::{RuntimeCode, StorageClient};
+use codec::{Decode, Encode};
+use core::marker::PhantomData;
+use futures::StreamExt;
+use scale_decode::IntoVisitor;
+use smoldot::executor::host::{self, HeapPages, HostVmPrototype};
+use smoldot::executor::runtime_call::{self, RuntimeCall};
+use smoldot::executor::storage_diff::TrieDiff;
+use smoldot::executor::vm::ExecHint;
+use smoldot::trie::{self, Nibble, TrieEntryVersion};
+use std::sync::Mutex;
+
+/// The number of heap pages that nodes give the runtime if none are set in `:heappages`.
+const DEFAULT_HEAP_PAGES: u32 = 2048;
+
+/// A client for executing Runtime APIs locally, rather than asking a node to execute them.
+/// This is created by calling [`RuntimeApisClient::local()`] or [`RuntimeApisClient::local_with_code()`].
+///
+/// The runtime is executed in a local WASM executor, and any storage that it reads is fetched
+/// lazily from the client that this was created from; either the node, at the block in question,
+/// or some [`crate::storage::StorageSnapshot`]. Any storage that the runtime writes is discarded
+/// at the end of each call.
+pub struct LocalRuntimeApis<'atblock, T, Client> {
+    client: &'atblock Client,
+    code: Vec<u8>,
+    heap_pages: HeapPages,
+    // The compiled runtime. This is taken for the duration of each call and handed back
+    // afterwards, so that it doesn't need recompiling each time.
+    vm: Mutex<Option<HostVmPrototype>>,
+    marker: PhantomData<T>,
+}
+
+impl<'atblock, T, Client> RuntimeApisClient<'atblock, T, Client>
+where
+    T: Config,
+    Client: StorageClientAtBlockT<T>,
+{
+    /// Execute Runtime APIs locally, using the runtime code stored at `:code` in this block.
+    pub async fn local(&self) -> Result<LocalRuntimeApis<'atblock, T, Client>, RuntimeApiError> {
+        let code = StorageClient::new(self.client)
+            .well_known()
+            .code()
+            .await
+            .map_err(RuntimeApiError::CannotFetchRuntimeCode)?;
+        self.local_with_code(code.into_bytes()).await
+    }
+
+    /// Execute Runtime APIs locally using the given runtime code, which may be compressed. This
+    /// could, for instance, be a proposed runtime upgrade, allowing it to be tested against the
+    /// state at this block before it is enacted.
+    pub async fn local_with_code(
+        &self,
+        code: Vec<u8>,
+    ) -> Result<LocalRuntimeApis<'atblock, T, Client>, RuntimeApiError> {
+        let code = RuntimeCode::new(code)
+            .wasm()
+            .map_err(RuntimeApiError::InvalidRuntimeCode)?
+            .into_owned();
+
+        let heap_pages = StorageClient::new(self.client)
+            .well_known()
+            .heap_pages()
+            .await
+            .map_err(RuntimeApiError::CannotFetchRuntimeCode)?
+            .map(|pages| u32::try_from(pages).unwrap_or(u32::MAX))
+            .unwrap_or(DEFAULT_HEAP_PAGES);
+
+        let local = LocalRuntimeApis {
+            client: self.client,
+            code,
+            heap_pages: HeapPages::new(heap_pages),
+            vm: Mutex::new(None),
+            marker: PhantomData,
+        };
+
+        // Compile the runtime up front so that invalid code is reported immediately.
+        let vm = local.take_vm()?;
+        local.return_vm(vm);
+        Ok(local)
+    }
+}
+
+impl<'atblock, T, Client> LocalRuntimeApis<'atblock, T, Client>
+where
+    T: Config,
+    Client: StorageClientAtBlockT<T>,
+{
+    /// Execute a raw runtime API call locally. This returns the raw bytes representing the
+    /// result of this call. The caller is responsible for decoding the result.
+    pub async fn call_raw(
+        &self,
+        function: &str,
+        call_parameters: Option<&[u8]>,
+    ) -> Result<Vec<u8>, RuntimeApiError> {
+        let vm = self.take_vm()?;
+        let mut call = runtime_call::run(runtime_call::Config {
+            virtual_machine: vm,
+            function_to_call: function,
+            parameter: std::iter::once(call_parameters.unwrap_or_default()),
+            storage_main_trie_changes: TrieDiff::default(),
+            storage_proof_size_behavior:
+                runtime_call::StorageProofSizeBehavior::proof_recording_disabled(),
+            max_log_level: 0,
+            calculate_trie_changes: false,
+        })
+        .map_err(|(e, vm)| {
+            self.return_vm(vm);
+            RuntimeApiError::LocalExecutionFailed(e.to_string())
+        })?;
+
+        loop {
+            call = match call {
+                RuntimeCall::Finished(Ok(success)) => {
+                    let output = success.virtual_machine.value().as_ref().to_vec();
+                    self.return_vm(success.virtual_machine.into_prototype());
+                    return Ok(output);
+                }
+                RuntimeCall::Finished(Err(error)) => {
+                    self.return_vm(error.prototype);
+                    return Err(RuntimeApiError::LocalExecutionFailed(
+                        error.detail.to_string(),
+                    ));
+                }
+                RuntimeCall::StorageGet(req) => {
+                    let key = req.key().as_ref().to_vec();
+                    let child_key = req.child_trie().map(|c| c.as_ref().to_vec());
+                    let value = match self.storage_value(child_key, key).await {
+                        Ok(value) => value,
+                        Err(e) => return Err(self.abort(RuntimeCall::StorageGet(req), e)),
+                    };
+                    req.inject_value(
+                        value
+                            .as_ref()
+                            .map(|value| (std::iter::once(value), TrieEntryVersion::V1)),
+                    )
+                }
+                RuntimeCall::ClosestDescendantMerkleValue(req) => {
+                    // Only the main trie can be asked for Merkle values. Otherwise, smoldot
+                    // works the value out itself by asking for the relevant keys.
+                    let key = nibbles_to_bytes(req.key());
+                    match (req.child_trie().is_none(), key) {
+                        (true, Some(key)) => {
+                            // Not every backend (nor a snapshot) can hand back Merkle values, so
+                            // if we can't get one, smoldot works it out as above instead.
+                            match self
+                                .client
+                                .storage_closest_descendant_merkle_value(key)
+                                .await
+                            {
+                                Ok(merkle_value) => {
+                                    req.inject_merkle_value(merkle_value.as_deref())
+                                }
+                                Err(_) => req.resume_unknown(),
+                            }
+                        }
+                        _ => req.resume_unknown(),
+                    }
+                }
+                RuntimeCall::NextKey(req) => {
+                    if req.branch_nodes() {
+                        return Err(self.abort(
+                            RuntimeCall::NextKey(req),
+                            RuntimeApiError::LocalExecutionFailed(
+                                "the runtime asked for trie branch nodes, which cannot be fetched"
+                                    .into(),
+                            ),
+                        ));
+                    }
+                    let (Some(key), Some(prefix)) =
+                        (nibbles_to_bytes(req.key()), nibbles_to_bytes(req.prefix()))
+                    else {
+                        return Err(self.abort(
+                            RuntimeCall::NextKey(req),
+                            RuntimeApiError::LocalExecutionFailed(
+                                "the runtime asked for a key which is not a whole number of bytes"
+                                    .into(),
+                            ),
+                        ));
+                    };
+                    let child_key = req.child_trie().map(|c| c.as_ref().to_vec());
+                    let next_key = match self.next_key(child_key, key, prefix, req.or_equal()).await
+                    {
+                        Ok(next_key) => next_key,
+                        Err(e) => return Err(self.abort(RuntimeCall::NextKey(req), e)),
+                    };
+                    req.inject_key(next_key.map(|key| trie::bytes_to_nibbles(key.into_iter())))
+                }
+                RuntimeCall::SignatureVerification(req) => req.verify_and_resume(),
+                RuntimeCall::OffchainStorageSet(req) => req.resume(),
+                RuntimeCall::LogEmit(req) => req.resume(),
+                RuntimeCall::Offchain(req) => {
+                    return Err(self.abort(
+                        RuntimeCall::Offchain(req),
+                        RuntimeApiError::LocalExecutionFailed(
+                            "offchain host functions are not supported".into(),
+                        ),
+                    ));
+                }
+            };
+        }
+    }
+
+    /// Execute a runtime API call locally. The arguments are encoded and the response
+    /// decoded using the metadata at the block that this client was created from.
+    pub async fn call<P: Payload>(&self, payload: P) -> Result<P::ReturnType, RuntimeApiError> {
+        let runtime_apis = RuntimeApisClient::<T, Client>::new(self.client);
+        let metadata = self.client.metadata_ref();
+
+        runtime_apis.validate(&payload)?;
+        let call_name = runtime_apis.encode_name(&payload);
+        let call_args = runtime_apis.encode_args(&payload)?;
+
+        let bytes = self.call_raw(&call_name, Some(&call_args)).await?;
+
+        let value = frame_decode::runtime_apis::decode_runtime_api_response(
+            payload.trait_name(),
+            payload.method_name(),
+            &mut &*bytes,
+            metadata,
+            metadata.types(),
+            P::ReturnType::into_visitor(),
+        )
+        .map_err(RuntimeApiError::CouldNotDecodeResponse)?;
+
+        Ok(value)
+    }
+
+    /// Ask the local runtime for its metadata. If the runtime code is not the code stored at
+    /// this block, this metadata may differ from the metadata at this block.
+    pub async fn metadata(&self) -> Result<Metadata, RuntimeApiError> {
+        let versions = self.call_raw("Metadata_metadata_versions", None).await;
+        let latest_version = versions.ok().and_then(|versions| {
+            Vec::<u32>::decode(&mut &*versions)
+                .ok()?
+                .into_iter()
+                .filter(|v| SUPPORTED_METADATA_VERSIONS.contains(v))
+                .max()
+        });
+
+        let metadata_bytes = match latest_version {
+            Some(version) => {
+                let bytes = self
+                    .call_raw("Metadata_metadata_at_version", Some(&version.encode()))
+                    .await?;
+                Option::<Vec<u8>>::decode(&mut &*bytes)
+                    .map_err(|e| RuntimeApiError::CannotDecodeLocalMetadata(e.to_string()))?
+                    .ok_or_else(|| {
+                        RuntimeApiError::CannotDecodeLocalMetadata(format!(
+                            "no metadata returned at version {version}"
+                        ))
+                    })?
+            }
+            // Older runtimes only expose `Metadata_metadata`.
+            None => {
+                let bytes = self.call_raw("Metadata_metadata", None).await?;
+                Vec::<u8>::decode(&mut &*bytes)
+                    .map_err(|e| RuntimeApiError::CannotDecodeLocalMetadata(e.to_string()))?
+            }
+        };
+
+        Metadata::decode_from(&metadata_bytes)
+            .map_err(|e| RuntimeApiError::CannotDecodeLocalMetadata(e.to_string()))
+    }
+
+    fn take_vm(&self) -> Result<HostVmPrototype, RuntimeApiError> {
+        let cached = self.vm.lock().expect("lock poisoned").take();
+        match cached {
+            Some(vm) => Ok(vm),
+            None => HostVmPrototype::new(host::Config {
+                module: &self.code,
+                heap_pages: self.heap_pages,
+                exec_hint: ExecHint::ValidateAndCompile,
+                allow_unresolved_imports: false,
+            })
+            .map_err(|e| RuntimeApiError::CannotInstantiateLocalRuntime(e.to_string())),
+        }
+    }
+
+    fn return_vm(&self, vm: HostVmPrototype) {
+        *self.vm.lock().expect("lock poisoned") = Some(vm);
+    }
+
+    // Stop a call part way through, handing the VM back so that it can be reused.
+    fn abort(&self, call: RuntimeCall, error: RuntimeApiError) -> RuntimeApiError {
+        self.return_vm(call.into_prototype());
+        error
+    }
+
+    async fn storage_value(
+        &self,
+        child_key: Option<Vec<u8>>,
+        key: Vec<u8>,
+    ) -> Result<Option<Vec<u8>>, RuntimeApiError> {
+        let values = match child_key {
+            Some(child_key) => {
+                self.client
+                    .storage_fetch_child_values(child_key, vec![key])
+                    .await
+            }
+            None => self.client.storage_fetch_values(vec![key]).await,
+        };
+        let mut values = values.map_err(RuntimeApiError::CannotFetchLocalStorage)?;
+
+        match values.next().await {
+            Some(value) => value
+                .map(|value| Some(value.value))
+                .map_err(RuntimeApiError::CannotFetchLocalStorage),
+            None => Ok(None),
+        }
+    }
+
+    // Find the first key which starts with the prefix and comes after (or is equal to) the key.
+    // Not every backend can resume iteration from a given key or hands keys back in order, so we
+    // fetch every key with the prefix and pick out the smallest one that comes after the key.
+    async fn next_key(
+        &self,
+        child_key: Option<Vec<u8>>,
+        key: Vec<u8>,
+        prefix: Vec<u8>,
+        or_equal: bool,
+    ) -> Result<Option<Vec<u8>>, RuntimeApiError> {
+        if !key.starts_with(&prefix) && key > prefix {
+            // Every key with the prefix comes before the key we've been given.
+            return Ok(None);
+        }
+
+        let options = StorageFetchDescendantsOptions::default();
+        let values = match child_key {
+            Some(child_key) => {
+                self.client
+                    .storage_fetch_child_descendant_values(child_key, prefix, options)
+                    .await
+            }
+            None => {
+                self.client
+                    .storage_fetch_descendant_values(prefix, options)
+                    .await
+            }
+        };
+        let mut values = values.map_err(RuntimeApiError::CannotFetchLocalStorage)?;
+
+        let mut next_key: Option<Vec<u8>> = None;
+        while let Some(value) = values.next().await {
+            let value = value.map_err(RuntimeApiError::CannotFetchLocalStorage)?;
+            let comes_after = if or_equal {
+                value.key >= key
+            } else {
+                value.key > key
+            };
+            if comes_after && next_key.as_ref().is_none_or(|next| value.key < *next) {
+                next_key = Some(value.key);
+            }
+        }
+        Ok(next_key)
+    }
+}
+
+// Storage keys are always a whole number of bytes, so we expect an even number of nibbles.
+fn nibbles_to_bytes(nibbles: impl Iterator<Item = Nibble>) -> Option<Vec<u8>> {
+    let nibbles: Vec<u8> = nibbles.map(u8::from).collect();
+    if nibbles.len() % 2 != 0 {
+        return None;
+    }
+    Some(
+        nibbles
+            .chunks_exact(2)
+            .map(|pair| (pair[0] << 4) | pair[1])
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::ChainHeadBackend;
+    use crate::client::{OnlineClient, test_utils};
+    use crate::config::SubstrateConfig;
+    use crate::config::substrate::H256;
+    use crate::storage::{StorageSnapshot, decode_runtime_version};
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicUsize;
+    use subxt_rpcs::RpcClient;
+    use subxt_rpcs::client::MockRpcClient;
+    use subxt_rpcs::client::mock_rpc_client::Json;
+    use subxt_rpcs::methods::chain_head::StorageQuery;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn converts_nibbles_to_bytes() {
+        let bytes = vec![0x12, 0xab, 0x00, 0xff];
+        let nibbles = trie::bytes_to_nibbles(bytes.iter().copied());
+        assert_eq!(nibbles_to_bytes(nibbles), Some(bytes));
+
+        let odd = trie::bytes_to_nibbles([0x12u8].into_iter()).take(1);
+        assert_eq!(nibbles_to_bytes(odd), None);
+    }
+
+    fn westend_snapshot() -> StorageSnapshot {
+        let code = std::fs::read("../artifacts/westend_runtime.compact.compressed.wasm")
+            .expect("westend runtime artifact should exist");
+        let mut snapshot = StorageSnapshot::new();
+        snapshot.insert(b":code".to_vec(), code);
+        snapshot
+    }
+
+    #[tokio::test]
+    async fn executes_westend_runtime_against_snapshot() {
+        let client = test_utils::snapshot_client(westend_snapshot());
+        let runtime_apis = client.runtime_apis();
+        let local = runtime_apis.local().await.unwrap();
+
+        // Call more than once to check that the VM is handed back and reused each time.
+        for _ in 0..2 {
+            let version_bytes = local.call_raw("Core_version", None).await.unwrap();
+            let version = decode_runtime_version(&version_bytes).unwrap();
+            assert!(version.spec_version > 0);
+            assert_eq!(
+                version.other.get("specName").and_then(|n| n.as_str()),
+                Some("westend")
+            );
+        }
+
+        let versions_bytes = local
+            .call_raw("Metadata_metadata_versions", None)
+            .await
+            .unwrap();
+        let versions = Vec::<u32>::decode(&mut &*versions_bytes).unwrap();
+        assert!(versions.contains(&14));
+        assert!(versions.contains(&15));
+    }
+
+    #[tokio::test]
+    async fn vm_is_handed_back_after_failed_calls() {
+        let client = test_utils::snapshot_client(westend_snapshot());
+        let runtime_apis = client.runtime_apis();
+        let local = runtime_apis.local().await.unwrap();
+
+        assert!(local.call_raw("Not_a_function", None).await.is_err());
+        assert!(local.vm.lock().unwrap().is_some());
+
+        assert!(local.call_raw("Core_version", None).await.is_ok());
+        assert!(local.vm.lock().unwrap().is_some());
+    }
+
+    // A client at block 0 of a chain served by the chainHead backend, which hands back the
+    // storage keys below some prefix out of order and can't resume iteration from a key.
+    async fn chain_head_client(
+        keys: &'static [&'static [u8]],
+    ) -> crate::client::OnlineClientAtBlock<SubstrateConfig> {
+        let (follow_tx, follow_rx) = mpsc::unbounded_channel();
+        follow_tx
+            .send(Json(serde_json::json!({
+                "event": "initialized",
+                "finalizedBlockHashes": [H256::zero()],
+            })))
+            .unwrap();
+
+        let operations = Arc::new(AtomicUsize::new(0));
+        let mock_client = MockRpcClient::builder()
+            .subscription_handler_once("chainHead_v1_follow", move |_params, _unsub| async move {
+                (follow_rx, "sub_id")
+            })
+            .method_handler("chainHead_v1_storage", move |params| {
+                let follow_tx = follow_tx.clone();
+                let operation_id = format!(
+                    "storage_{}",
+                    operations.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+                );
+                async move {
+                    let (_sub_id, _at, queries, _child_key): (
+                        String,
+                        H256,
+                        Vec<StorageQuery<String>>,
+                        Option<String>,
+                    ) = serde_json::from_str(params.unwrap().get()).unwrap();
+                    let prefix = hex::decode(queries[0].key.trim_start_matches("0x")).unwrap();
+
+                    let items: Vec<_> = keys
+                        .iter()
+                        .filter(|key| key.starts_with(&prefix))
+                        .map(|key| {
+                            serde_json::json!({
+                                "key": format!("0x{}", hex::encode(key)),
+                                "value": "0x00",
+                            })
+                        })
+                        .collect();
+                    for event in [
+                        serde_json::json!({
+                            "event": "operationStorageItems",
+                            "operationId": operation_id,
+                            "items": items,
+                        }),
+                        serde_json::json!({
+                            "event": "operationStorageDone",
+                            "operationId": operation_id,
+                        }),
+                    ] {
+                        follow_tx.send(Json(event)).unwrap();
+                    }
+
+                    Json(serde_json::json!({
+                        "result": "started",
+                        "operationId": operation_id,
+                    }))
+                }
+            })
+            .method_handler("chainHead_v1_unpin", |_params| async { Json(()) })
+            .method_handler("chainSpec_v1_genesisHash", |_params| async {
+                Json(H256::zero())
+            })
+            .build();
+
+        let (backend, mut driver) =
+            ChainHeadBackend::<SubstrateConfig>::builder().build(RpcClient::new(mock_client));
+        tokio::spawn(async move { while driver.next().await.is_some() {} });
+
+        OnlineClient::from_backend_with_config(test_utils::config(), Arc::new(backend))
+            .await
+            .unwrap()
+            .at_block_hash_and_number(H256::zero(), 0)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn next_key_finds_the_smallest_following_key_from_unordered_keys() {
+        let client = chain_head_client(&[&[1, 5], &[1, 2], &[2, 0], &[1, 9]]).await;
+        // We only need storage access here, so the runtime is never compiled.
+        let local = LocalRuntimeApis {
+            client: &client.client,
+            code: Vec::new(),
+            heap_pages: HeapPages::new(DEFAULT_HEAP_PAGES),
+            vm: Mutex::new(None),
+            marker: PhantomData,
+        };
+
+        let next_key =
+            |key: &[u8], or_equal: bool| local.next_key(None, key.to_vec(), vec![1], or_equal);
+        assert_eq!(next_key(&[1, 2], false).await.unwrap(), Some(vec![1, 5]));
+        assert_eq!(next_key(&[1, 2], true).await.unwrap(), Some(vec![1, 2]));
+        assert_eq!(next_key(&[1, 3], true).await.unwrap(), Some(vec![1, 5]));
+        assert_eq!(next_key(&[0], false).await.unwrap(), Some(vec![1, 2]));
+        assert_eq!(next_key(&[1, 9], false).await.unwrap(), None);
+        assert_eq!(next_key(&[3], false).await.unwrap(), None);
+    }
+}
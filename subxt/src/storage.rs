@@ -2,27 +2,46 @@
 //! storage entries. It's created by calling [`crate::client::ClientAtBlock::storage()`].
 
 mod address;
+mod child_trie;
+mod fetch_multi;
+mod history;
+mod iter_options;
 mod prefix_of;
+mod snapshot;
+mod stats;
 mod storage_entry;
 mod storage_key;
 mod storage_key_value;
 mod storage_value;
+mod well_known;
 
-use crate::backend::BackendExt;
-use crate::client::{OfflineClientAtBlockT, OnlineClientAtBlockT};
+use crate::backend::StorageResponse;
+use crate::client::{OfflineClientAtBlockT, StorageClientAtBlockT};
 use crate::config::Config;
 use crate::error::StorageError;
 use core::marker::PhantomData;
 use frame_decode::helpers::Entry;
 use frame_decode::storage::StorageEntryInfo;
+use futures::{Stream, StreamExt};
+use scale_encode::EncodeAsType;
 use std::borrow::Cow;
+use std::collections::HashMap;
 
 pub use address::{Address, DynamicAddress, StaticAddress, dynamic};
+pub use child_trie::{ChildTrieClient, ChildTrieEntries, ChildTrieKeyValue};
+pub use fetch_multi::{FetchMulti, FetchMultiItem};
+pub use history::{StorageHistory, StorageHistoryItem};
+pub use iter_options::{StorageCursor, StorageIterOptions};
 pub use prefix_of::PrefixOf;
+pub use snapshot::StorageSnapshot;
+pub use stats::{StorageEntryStats, StorageItemSize, StorageSizeHistogram, StorageStatsOptions};
 pub use storage_entry::{StorageEntries, StorageEntry};
 pub use storage_key::{StorageKey, StorageKeyPart};
 pub use storage_key_value::StorageKeyValue;
 pub use storage_value::StorageValue;
+pub use well_known::{DefaultChildTrie, RuntimeCode, RuntimeVersion, WellKnownStorage};
+
+pub(crate) use well_known::decode_runtime_version;
 
 /// A client for working with storage entries. See [the module docs](crate::storage) for more.
 #[derive(Clone)]
@@ -82,22 +101,79 @@ impl<'atblock, T: Config, Client: OfflineClientAtBlockT<T>> StorageClient<'atblo
         StorageEntry::new(self.client, address)
     }
 
+    /// This is essentially a shorthand for `client.entry(addr)?.encode_key_value(key_parts, value)`.
+    /// See [`StorageEntry::encode_key_value()`].
+    pub fn encode_key_value<Addr: Address, V: EncodeAsType + ?Sized>(
+        &self,
+        address: Addr,
+        key_parts: Addr::KeyParts,
+        value: &V,
+    ) -> Result<(Vec<u8>, Vec<u8>), StorageError> {
+        self.entry(address)?.encode_key_value(key_parts, value)
+    }
+
+    /// Work with the values in the default child trie with the given key (the key without the
+    /// `:child_storage:default:` prefix). For example, the storage of a smart contract lives in a
+    /// child trie.
+    pub fn child_trie(
+        &self,
+        child_key: impl Into<Vec<u8>>,
+    ) -> ChildTrieClient<'atblock, T, Client> {
+        ChildTrieClient::new(self.client, child_key.into())
+    }
+
+    /// Access the well known storage entries like `:code`, which are not listed in the metadata.
+    pub fn well_known(&self) -> WellKnownStorage<'atblock, T, Client> {
+        WellKnownStorage::new(self.client)
+    }
+
     /// Iterate over all of the storage entries listed in the metadata for the current block. This does **not** include well known
-    /// storage entries like `:code` which are not listed in the metadata.
-    pub fn entries(&self) -> impl Iterator<Item = StorageEntryRef<'atblock, T, Client>> {
-        let metadata = self.client.metadata_ref();
-        Entry::tuples_of(metadata.storage_entries()).map(|(pallet_name, entry_name)| {
+    /// storage entries like `:code` which are not listed in the metadata; see [`Self::well_known()`] for those.
+    pub fn entries(
+        &self,
+    ) -> impl Iterator<Item = StorageEntryRef<'atblock, T, Client>> + use<'atblock, T, Client> {
+        let client = self.client;
+        let metadata = client.metadata_ref();
+        Entry::tuples_of(metadata.storage_entries()).map(move |(pallet_name, entry_name)| {
             StorageEntryRef {
                 pallet_name: pallet_name.clone(),
                 entry_name,
-                client: self.client,
+                client,
                 marker: std::marker::PhantomData,
             }
         })
     }
 }
 
-impl<'atblock, T: Config, Client: OnlineClientAtBlockT<T>> StorageClient<'atblock, T, Client> {
+impl<'atblock, T: Config, Client: StorageClientAtBlockT<T>> StorageClient<'atblock, T, Client> {
+    /// Gather size and key count statistics for each of the storage entries listed in the metadata
+    /// (see [`Self::entries()`]). Statistics for each entry are handed back as soon as they have been
+    /// gathered, and values are streamed rather than all being loaded into memory at once.
+    pub fn stats(
+        &self,
+        options: StorageStatsOptions,
+    ) -> impl Stream<Item = Result<StorageEntryStats, StorageError>> + use<'atblock, T, Client>
+    {
+        self.stats_matching(options, |_| true)
+    }
+
+    /// Gather size and key count statistics for the storage entries listed in the metadata
+    /// for which the given filter returns true. See [`Self::stats()`].
+    pub fn stats_matching<F>(
+        &self,
+        options: StorageStatsOptions,
+        mut filter: F,
+    ) -> impl Stream<Item = Result<StorageEntryStats, StorageError>> + use<'atblock, T, Client, F>
+    where
+        F: FnMut(&StorageEntryRef<'atblock, T, Client>) -> bool,
+    {
+        let entries = self.entries().filter(move |entry| filter(entry));
+        futures::stream::iter(entries).then(move |entry| {
+            let options = options.clone();
+            async move { entry.stats(options).await }
+        })
+    }
+
     /// This is essentially a shorthand for `client.entry(addr)?.fetch(key_parts)`. See [`StorageEntry::fetch()`].
     pub async fn fetch<Addr: Address>(
         &self,
@@ -118,6 +194,39 @@ impl<'atblock, T: Config, Client: OnlineClientAtBlockT<T>> StorageClient<'atbloc
         entry.try_fetch(key_parts).await
     }
 
+    /// This is essentially a shorthand for `client.entry(addr)?.fetch_many(key_parts)`. See [`StorageEntry::fetch_many()`].
+    pub async fn fetch_many<Addr: Address>(
+        &self,
+        addr: Addr,
+        key_parts: impl IntoIterator<Item = Addr::KeyParts>,
+    ) -> Result<Vec<Option<StorageValue<'atblock, Addr::Value>>>, StorageError> {
+        let entry = self.entry(addr)?;
+        entry.fetch_many(key_parts).await
+    }
+
+    /// Fetch several storage values, each of which may live in a different storage entry, using as few
+    /// requests as possible. This takes a tuple of `(address, key_parts)` pairs and hands back a tuple of
+    /// the same length containing the value found for each one (or the default value for that entry if
+    /// no value was found), in the same order.
+    ///
+    /// ```rust,ignore
+    /// let (alice_account, total_issuance) = client
+    ///     .storage()
+    ///     .fetch_multi((
+    ///         (polkadot::storage().system().account(), (alice,)),
+    ///         (polkadot::storage().balances().total_issuance(), ()),
+    ///     ))
+    ///     .await?;
+    /// ```
+    pub async fn fetch_multi<Queries: FetchMulti<'atblock, T, Client>>(
+        &self,
+        queries: Queries,
+    ) -> Result<Queries::Output, StorageError> {
+        let (entries, keys) = queries.prepare(self)?;
+        let values = fetch_raw_values::<T, Client>(self.client, keys).await?;
+        Ok(Queries::finish(entries, values))
+    }
+
     /// This is essentially a shorthand for `client.entry(addr)?.iter(key_parts)`. See [`StorageEntry::iter()`].
     pub async fn iter<Addr: Address, KeyParts: PrefixOf<Addr::KeyParts>>(
         &self,
@@ -134,16 +243,37 @@ impl<'atblock, T: Config, Client: OnlineClientAtBlockT<T>> StorageClient<'atbloc
     /// otherwise an error. [`StorageError::NoValueFound`] will be returned in the event that the request was valid
     /// but no value lives at the given location).
     pub async fn fetch_raw(&self, key_bytes: Vec<u8>) -> Result<Vec<u8>, StorageError> {
-        let block_hash = self.client.block_ref().hash();
-        let value = self
-            .client
-            .backend()
-            .storage_fetch_value(key_bytes, block_hash)
-            .await
-            .map_err(StorageError::CannotFetchValue)?
-            .ok_or(StorageError::NoValueFound)?;
+        fetch_raw_value::<T, Client>(self.client, key_bytes)
+            .await?
+            .ok_or(StorageError::NoValueFound)
+    }
+
+    /// Take a [`StorageSnapshot`] of every storage value whose key begins with one of the given prefixes.
+    /// An empty prefix will take a snapshot of all storage, which may be very large.
+    ///
+    /// The resulting snapshot can be saved and later handed to
+    /// [`crate::client::OfflineClientAtBlock::with_storage_snapshot()`] to work with these storage
+    /// values without a connection to a node.
+    pub async fn snapshot<Prefix: Into<Vec<u8>>>(
+        &self,
+        prefixes: impl IntoIterator<Item = Prefix>,
+    ) -> Result<StorageSnapshot, StorageError> {
+        let mut snapshot = StorageSnapshot::new();
 
-        Ok(value)
+        for prefix in prefixes {
+            let mut values = self
+                .client
+                .storage_fetch_descendant_values(prefix.into(), Default::default())
+                .await
+                .map_err(StorageError::CannotIterateValues)?;
+
+            while let Some(value) = values.next().await {
+                let StorageResponse { key, value } = value.map_err(StorageError::StreamFailure)?;
+                snapshot.insert(key, value);
+            }
+        }
+
+        Ok(snapshot)
     }
 
     /// The storage version of a pallet.
@@ -171,6 +301,64 @@ impl<'atblock, T: Config, Client: OnlineClientAtBlockT<T>> StorageClient<'atbloc
     }
 }
 
+/// The maximum number of keys that we'll ask the backend for in a single request.
+const FETCH_MANY_CHUNK_SIZE: usize = 512;
+
+// Fetch the values at each of the given keys, in chunks, returning them in the same order as the keys.
+async fn fetch_raw_values<T: Config, Client: StorageClientAtBlockT<T>>(
+    client: &Client,
+    keys: Vec<Vec<u8>>,
+) -> Result<Vec<Option<Vec<u8>>>, StorageError> {
+    // Where each key appears in the output, so that values can be moved into place as they arrive.
+    let mut positions: HashMap<Vec<u8>, Vec<usize>> = HashMap::new();
+    for (idx, key) in keys.iter().enumerate() {
+        positions.entry(key.clone()).or_default().push(idx);
+    }
+
+    let mut output = vec![None; keys.len()];
+    for chunk in keys.chunks(FETCH_MANY_CHUNK_SIZE) {
+        let mut values = client
+            .storage_fetch_values(chunk.to_vec())
+            .await
+            .map_err(StorageError::CannotFetchValue)?;
+
+        while let Some(value) = values.next().await {
+            let StorageResponse { key, value } = value.map_err(StorageError::CannotFetchValue)?;
+            let Some(idxs) = positions.remove(&key) else {
+                continue;
+            };
+            // Only clone the value if the same key was asked for more than once.
+            let (last_idx, other_idxs) = idxs.split_last().expect("positions are never empty; qed");
+            for idx in other_idxs {
+                output[*idx] = Some(value.clone());
+            }
+            output[*last_idx] = Some(value);
+        }
+    }
+
+    Ok(output)
+}
+
+// Fetch the value at a single key.
+async fn fetch_raw_value<T: Config, Client: StorageClientAtBlockT<T>>(
+    client: &Client,
+    key: Vec<u8>,
+) -> Result<Option<Vec<u8>>, StorageError> {
+    let mut values = client
+        .storage_fetch_values(vec![key])
+        .await
+        .map_err(StorageError::CannotFetchValue)?;
+
+    let value = values
+        .next()
+        .await
+        .transpose()
+        .map_err(StorageError::CannotFetchValue)?
+        .map(|res| res.value);
+
+    Ok(value)
+}
+
 /// Working with a specific storage entry.
 pub struct StorageEntryRef<'atblock, T, Client> {
     pallet_name: Cow<'atblock, str>,
@@ -202,3 +390,122 @@ where
         StorageEntry::new(self.client, addr)
     }
 }
+
+impl<'atblock, Client, T> StorageEntryRef<'atblock, T, Client>
+where
+    T: Config,
+    Client: StorageClientAtBlockT<T>,
+{
+    /// Gather size and key count statistics for this storage entry.
+    pub async fn stats(
+        &self,
+        options: StorageStatsOptions,
+    ) -> Result<StorageEntryStats, StorageError> {
+        stats::entry_stats(self.client, &self.pallet_name, &self.entry_name, options).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client::test_utils::snapshot_client;
+    use crate::config::SubstrateConfig;
+    use codec::Encode;
+    use futures::TryStreamExt;
+    use scale_value::Value;
+
+    fn account(n: u8) -> Vec<Value> {
+        vec![Value::from_bytes([n; 32])]
+    }
+
+    fn account_key(n: u8) -> Vec<u8> {
+        let client = snapshot_client(StorageSnapshot::new());
+        let entry = client.storage().entry(("System", "Account")).unwrap();
+        entry.fetch_key(account(n)).unwrap()
+    }
+
+    fn snapshot() -> StorageSnapshot {
+        [(account_key(1), vec![1]), (account_key(2), vec![2])]
+            .into_iter()
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn fetch_raw_values_keeps_key_order() {
+        let client = snapshot_client(snapshot());
+        let storage = client.storage();
+
+        // Duplicate and missing keys are both handled.
+        let keys = vec![
+            account_key(2),
+            account_key(3),
+            account_key(1),
+            account_key(2),
+        ];
+        let values = fetch_raw_values::<SubstrateConfig, _>(storage.client, keys)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            values,
+            vec![Some(vec![2]), None, Some(vec![1]), Some(vec![2])]
+        );
+    }
+
+    #[tokio::test]
+    async fn fetch_many_falls_back_to_default_values() {
+        let client = snapshot_client(snapshot());
+        let entry = client.storage().entry(("System", "Account")).unwrap();
+
+        let values = entry
+            .fetch_many([account(2), account(3), account(1)])
+            .await
+            .unwrap();
+        let bytes: Vec<_> = values
+            .iter()
+            .map(|v| v.as_ref().map(|v| v.bytes().to_vec()))
+            .collect();
+        let default_bytes = entry.default_value().map(|v| v.bytes().to_vec());
+
+        assert!(default_bytes.is_some());
+        assert_eq!(bytes, vec![Some(vec![2]), default_bytes, Some(vec![1])]);
+    }
+
+    #[tokio::test]
+    async fn fetch_multi_fetches_from_several_entries() {
+        let mut snapshot = snapshot();
+        let number_key = frame_decode::storage::encode_storage_key_prefix("System", "Number");
+        snapshot.insert(number_key.to_vec(), 123u32.encode());
+
+        let client = snapshot_client(snapshot);
+        let (account_value, number_value) = client
+            .storage()
+            .fetch_multi((
+                (("System", "Account"), account(1)),
+                (("System", "Number"), vec![]),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(account_value.unwrap().bytes(), &[1]);
+        assert_eq!(number_value.unwrap().decode_as::<u32>().unwrap(), 123);
+    }
+
+    #[tokio::test]
+    async fn stats_matching_only_gathers_matching_entries() {
+        let client = snapshot_client(snapshot());
+        let storage = client.storage();
+
+        let stats: Vec<_> = storage
+            .stats_matching(StorageStatsOptions::new(), |entry| {
+                entry.pallet_name() == "System" && entry.entry_name() == "Account"
+            })
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].key_count(), 2);
+        assert_eq!(stats[0].total_value_bytes(), 2);
+    }
+}
@@ -0,0 +1,216 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+use super::{StorageCursor, StorageIterOptions};
+use crate::backend::{StorageResponse, StreamOfResults};
+use crate::client::StorageClientAtBlockT;
+use crate::config::Config;
+use crate::error::StorageError;
+use codec::{Decode, DecodeAll};
+use core::marker::PhantomData;
+use futures::{Stream, StreamExt};
+use std::pin::Pin;
+use std::task::Poll;
+
+/// A client for fetching and iterating over values in a default child trie, for example the
+/// storage of a smart contract. This is created by calling [`super::StorageClient::child_trie()`].
+///
+/// Child tries are not described by the metadata, and so values are fetched using raw keys and
+/// can be decoded into any type implementing [`codec::Decode`].
+#[derive(Clone)]
+pub struct ChildTrieClient<'atblock, T, Client> {
+    client: &'atblock Client,
+    child_key: Vec<u8>,
+    marker: PhantomData<T>,
+}
+
+impl<'atblock, T, Client> ChildTrieClient<'atblock, T, Client> {
+    pub(crate) fn new(client: &'atblock Client, child_key: Vec<u8>) -> Self {
+        ChildTrieClient {
+            client,
+            child_key,
+            marker: PhantomData,
+        }
+    }
+
+    /// The key of the child trie, without the `:child_storage:default:` prefix.
+    pub fn child_key(&self) -> &[u8] {
+        &self.child_key
+    }
+}
+
+impl<'atblock, T: Config, Client: StorageClientAtBlockT<T>> ChildTrieClient<'atblock, T, Client> {
+    /// Fetch the raw bytes stored at the given key in this child trie.
+    /// [`StorageError::NoValueFound`] is returned if there is no value at this key.
+    pub async fn fetch_raw(&self, key_bytes: Vec<u8>) -> Result<Vec<u8>, StorageError> {
+        self.try_fetch_raw(key_bytes)
+            .await?
+            .ok_or(StorageError::NoValueFound)
+    }
+
+    /// Fetch the raw bytes stored at the given key in this child trie, returning `None`
+    /// if there is no value at this key.
+    pub async fn try_fetch_raw(&self, key_bytes: Vec<u8>) -> Result<Option<Vec<u8>>, StorageError> {
+        let mut values = self
+            .client
+            .storage_fetch_child_values(self.child_key.clone(), vec![key_bytes])
+            .await
+            .map_err(StorageError::CannotFetchValue)?;
+
+        let value = values
+            .next()
+            .await
+            .transpose()
+            .map_err(StorageError::CannotFetchValue)?
+            .map(|res| res.value);
+
+        Ok(value)
+    }
+
+    /// Fetch and decode the value stored at the given key in this child trie.
+    /// [`StorageError::NoValueFound`] is returned if there is no value at this key.
+    pub async fn fetch<V: Decode>(&self, key_bytes: Vec<u8>) -> Result<V, StorageError> {
+        let bytes = self.fetch_raw(key_bytes).await?;
+        decode_child_trie_value(&bytes)
+    }
+
+    /// Fetch and decode the value stored at the given key in this child trie, returning
+    /// `None` if there is no value at this key.
+    pub async fn try_fetch<V: Decode>(
+        &self,
+        key_bytes: Vec<u8>,
+    ) -> Result<Option<V>, StorageError> {
+        self.try_fetch_raw(key_bytes)
+            .await?
+            .map(|bytes| decode_child_trie_value(&bytes))
+            .transpose()
+    }
+
+    /// Iterate over all of the values in this child trie whose keys begin with the given prefix.
+    /// An empty prefix iterates over the entire child trie.
+    pub async fn iter(&self, prefix: Vec<u8>) -> Result<ChildTrieEntries, StorageError> {
+        self.iter_with_options(prefix, StorageIterOptions::new())
+            .await
+    }
+
+    /// Iterate over the values in this child trie whose keys begin with the given prefix,
+    /// with the given [`StorageIterOptions`] (for example, to resume from some cursor).
+    pub async fn iter_with_options(
+        &self,
+        prefix: Vec<u8>,
+        options: StorageIterOptions,
+    ) -> Result<ChildTrieEntries, StorageError> {
+        let (last_key, backend_options) = options.into_backend_options(&prefix)?;
+
+        let stream = self
+            .client
+            .storage_fetch_child_descendant_values(self.child_key.clone(), prefix, backend_options)
+            .await
+            .map_err(StorageError::CannotIterateValues)?;
+
+        Ok(ChildTrieEntries { stream, last_key })
+    }
+}
+
+/// A stream of the key/value pairs found in some child trie.
+pub struct ChildTrieEntries {
+    stream: StreamOfResults<StorageResponse>,
+    // The last key that we handed back, so that iteration can be resumed from it:
+    last_key: Option<Vec<u8>>,
+}
+
+impl ChildTrieEntries {
+    /// Get the next entry. This is an alias for `futures::StreamExt::next(self)`.
+    pub async fn next(&mut self) -> Option<Result<ChildTrieKeyValue, StorageError>> {
+        StreamExt::next(self).await
+    }
+
+    /// A cursor pointing at the last entry handed back, which can be used to resume
+    /// iterating from this point via [`StorageIterOptions::resume_from()`].
+    pub fn cursor(&self) -> Option<StorageCursor> {
+        self.last_key.clone().map(StorageCursor::new)
+    }
+}
+
+impl std::marker::Unpin for ChildTrieEntries {}
+impl Stream for ChildTrieEntries {
+    type Item = Result<ChildTrieKeyValue, StorageError>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let val = match futures::ready!(self.stream.poll_next_unpin(cx)) {
+            Some(Ok(val)) => val,
+            Some(Err(e)) => return Poll::Ready(Some(Err(StorageError::StreamFailure(e)))),
+            None => return Poll::Ready(None),
+        };
+
+        self.last_key = Some(val.key.clone());
+
+        Poll::Ready(Some(Ok(ChildTrieKeyValue {
+            key: val.key,
+            value: val.value,
+        })))
+    }
+}
+
+/// A raw key and value found in some child trie.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChildTrieKeyValue {
+    key: Vec<u8>,
+    value: Vec<u8>,
+}
+
+impl ChildTrieKeyValue {
+    /// The raw key bytes.
+    pub fn key_bytes(&self) -> &[u8] {
+        &self.key
+    }
+
+    /// The raw value bytes.
+    pub fn value_bytes(&self) -> &[u8] {
+        &self.value
+    }
+
+    /// Decode the value into some type.
+    pub fn decode_value<V: Decode>(&self) -> Result<V, StorageError> {
+        decode_child_trie_value(&self.value)
+    }
+
+    /// Consume this, returning the raw key and value bytes.
+    pub fn into_parts(self) -> (Vec<u8>, Vec<u8>) {
+        (self.key, self.value)
+    }
+}
+
+fn decode_child_trie_value<V: Decode>(bytes: &[u8]) -> Result<V, StorageError> {
+    V::decode_all(&mut &*bytes).map_err(StorageError::CannotDecodeChildTrieValue)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use codec::Encode;
+
+    #[test]
+    fn values_must_decode_fully() {
+        let mut value = 42u32.encode();
+        let kv = ChildTrieKeyValue {
+            key: vec![1],
+            value: value.clone(),
+        };
+        assert_eq!(kv.decode_value::<u32>().unwrap(), 42);
+
+        value.push(0);
+        let kv = ChildTrieKeyValue {
+            key: vec![1],
+            value,
+        };
+        assert!(matches!(
+            kv.decode_value::<u32>(),
+            Err(StorageError::CannotDecodeChildTrieValue(_))
+        ));
+    }
+}
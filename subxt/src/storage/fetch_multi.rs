@@ -0,0 +1,128 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+use super::{Address, StorageClient, StorageEntry, StorageValue};
+use crate::client::OfflineClientAtBlockT;
+use crate::config::Config;
+use crate::error::StorageError;
+
+/// A single query that can be given as part of a tuple to [`StorageClient::fetch_multi()`].
+/// This is implemented for `(address, key_parts)` pairs.
+pub trait FetchMultiItem<'atblock, T, Client> {
+    /// The value handed back for this query.
+    type Output;
+    /// Details about the storage entry being queried.
+    #[doc(hidden)]
+    type Entry;
+
+    /// Return details about the storage entry and the key to fetch.
+    #[doc(hidden)]
+    fn prepare(
+        self,
+        client: &StorageClient<'atblock, T, Client>,
+    ) -> Result<(Self::Entry, Vec<u8>), StorageError>;
+
+    /// Turn the bytes found at the key (if any) into the output.
+    #[doc(hidden)]
+    fn finish(entry: Self::Entry, bytes: Option<Vec<u8>>) -> Self::Output;
+}
+
+impl<'atblock, T, Client, Addr> FetchMultiItem<'atblock, T, Client> for (Addr, Addr::KeyParts)
+where
+    T: Config,
+    Client: OfflineClientAtBlockT<T>,
+    Addr: Address,
+{
+    type Output = Option<StorageValue<'atblock, Addr::Value>>;
+    type Entry = StorageEntry<'atblock, T, Client, Addr>;
+
+    fn prepare(
+        self,
+        client: &StorageClient<'atblock, T, Client>,
+    ) -> Result<(Self::Entry, Vec<u8>), StorageError> {
+        let (addr, key_parts) = self;
+        let entry = client.entry(addr)?;
+        let key = entry.fetch_key(key_parts)?;
+        Ok((entry, key))
+    }
+
+    fn finish(entry: Self::Entry, bytes: Option<Vec<u8>>) -> Self::Output {
+        entry.value_or_default(bytes)
+    }
+}
+
+/// A set of storage queries, each of which may point to a different storage entry, which
+/// can be fetched together using [`StorageClient::fetch_multi()`]. This is implemented for
+/// tuples of [`FetchMultiItem`]s.
+pub trait FetchMulti<'atblock, T, Client> {
+    /// The values handed back for these queries.
+    type Output;
+    /// Details about the storage entries being queried.
+    #[doc(hidden)]
+    type Entries;
+
+    /// Return details about the storage entries and the keys to fetch, in order.
+    #[doc(hidden)]
+    fn prepare(
+        self,
+        client: &StorageClient<'atblock, T, Client>,
+    ) -> Result<(Self::Entries, Vec<Vec<u8>>), StorageError>;
+
+    /// Turn the bytes found at each key (in the same order as the keys) into the output.
+    #[doc(hidden)]
+    fn finish(entries: Self::Entries, values: Vec<Option<Vec<u8>>>) -> Self::Output;
+}
+
+// tuples of FetchMultiItems can be fetched together.
+macro_rules! impl_fetch_multi_tuple {
+    ($($ident:ident $index:tt),+) => {
+        impl<'atblock, Conf, Client, $($ident),+> FetchMulti<'atblock, Conf, Client> for ($($ident,)+)
+        where
+            Conf: Config,
+            Client: OfflineClientAtBlockT<Conf>,
+            $($ident: FetchMultiItem<'atblock, Conf, Client>,)+
+        {
+            type Output = ($($ident::Output,)+);
+            type Entries = ($($ident::Entry,)+);
+
+            fn prepare(
+                self,
+                client: &StorageClient<'atblock, Conf, Client>,
+            ) -> Result<(Self::Entries, Vec<Vec<u8>>), StorageError> {
+                let mut keys = Vec::new();
+                let entries = ($({
+                    let (entry, key) = self.$index.prepare(client)?;
+                    keys.push(key);
+                    entry
+                },)+);
+                Ok((entries, keys))
+            }
+
+            fn finish(entries: Self::Entries, values: Vec<Option<Vec<u8>>>) -> Self::Output {
+                let mut values = values.into_iter();
+                ($($ident::finish(entries.$index, values.next().flatten()),)+)
+            }
+        }
+    }
+}
+
+#[rustfmt::skip]
+const _: () = {
+    impl_fetch_multi_tuple!(A 0);
+    impl_fetch_multi_tuple!(A 0, B 1);
+    impl_fetch_multi_tuple!(A 0, B 1, C 2);
+    impl_fetch_multi_tuple!(A 0, B 1, C 2, D 3);
+    impl_fetch_multi_tuple!(A 0, B 1, C 2, D 3, E 4);
+    impl_fetch_multi_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+    impl_fetch_multi_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+    impl_fetch_multi_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+    impl_fetch_multi_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+    impl_fetch_multi_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+    impl_fetch_multi_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+    impl_fetch_multi_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+    impl_fetch_multi_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12);
+    impl_fetch_multi_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12, N 13);
+    impl_fetch_multi_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12, N 13, O 14);
+    impl_fetch_multi_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12, N 13, O 14, P 15);
+};
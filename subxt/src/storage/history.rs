@@ -0,0 +1,241 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+use crate::backend::{BlockRef, StorageValueChange};
+use crate::client::{BlockNumberOrRef, OnlineClient, OnlineClientAtBlockT};
+use crate::config::{Config, HashFor};
+use crate::error::StorageHistoryError;
+use crate::storage::Address;
+use futures::{Stream, StreamExt};
+use std::pin::Pin;
+use std::task::Poll;
+
+/// A stream of the values of some storage entry over a range of blocks. This is created
+/// by calling [`OnlineClient::storage_history()`].
+///
+/// An item is returned for the first block in the range, and then for each subsequent block
+/// in which the value changes. Each value is decoded using the metadata from the block that it
+/// was found in, so that values can be decoded correctly across runtime upgrades.
+pub struct StorageHistory<T: Config, Value> {
+    stream: Pin<
+        Box<dyn Stream<Item = Result<StorageHistoryItem<T, Value>, StorageHistoryError>> + Send>,
+    >,
+}
+
+impl<T: Config, Value: Send + 'static> StorageHistory<T, Value> {
+    pub(crate) async fn new<Addr>(
+        client: OnlineClient<T>,
+        address: Addr,
+        key_parts: Addr::KeyParts,
+        from: BlockNumberOrRef<T>,
+        to: BlockNumberOrRef<T>,
+    ) -> Result<Self, StorageHistoryError>
+    where
+        Addr: Address<Value = Value> + Clone + Send + Sync + 'static,
+    {
+        // The key is encoded using the metadata at the start of the range.
+        let from_block = client
+            .at_block(from)
+            .await
+            .map_err(StorageHistoryError::CannotInstantiateClientAtBlock)?;
+        let key = from_block
+            .storage()
+            .entry(address.clone())
+            .and_then(|entry| entry.fetch_key(key_parts))
+            .map_err(StorageHistoryError::CannotEncodeKey)?;
+
+        let to_block = client
+            .at_block(to)
+            .await
+            .map_err(StorageHistoryError::CannotInstantiateClientAtBlock)?;
+        if to_block.block_number() < from_block.block_number() {
+            return Err(StorageHistoryError::InvalidBlockRange {
+                from: from_block.block_number(),
+                to: to_block.block_number(),
+            });
+        }
+
+        let changes = from_block
+            .client
+            .backend()
+            .storage_value_changes(key, from_block.block_hash(), to_block.block_hash())
+            .await
+            .map_err(StorageHistoryError::CannotFetchChanges)?;
+
+        let stream = changes.then(move |change| {
+            let client = client.clone();
+            let address = address.clone();
+            async move {
+                let change = change.map_err(StorageHistoryError::StreamFailure)?;
+                decode_change(&client, address, change).await
+            }
+        });
+
+        Ok(StorageHistory {
+            stream: Box::pin(stream),
+        })
+    }
+}
+
+// Decode some changed value using the metadata at the block in which it changed.
+async fn decode_change<T: Config, Addr: Address>(
+    client: &OnlineClient<T>,
+    address: Addr,
+    change: StorageValueChange<HashFor<T>>,
+) -> Result<StorageHistoryItem<T, Addr::Value>, StorageHistoryError> {
+    let at_block = client
+        .at_block(BlockRef::from_hash(change.block_hash))
+        .await
+        .map_err(StorageHistoryError::CannotInstantiateClientAtBlock)?;
+    let block_number = at_block.block_number();
+
+    let storage = at_block.storage();
+    let entry =
+        storage
+            .entry(address)
+            .map_err(|error| StorageHistoryError::IncompatibleAddress {
+                block_number,
+                error,
+            })?;
+    let value = entry
+        .value_or_default(change.value)
+        .map(|value| value.decode())
+        .transpose()
+        .map_err(|error| StorageHistoryError::CannotDecodeValue {
+            block_number,
+            error,
+        })?;
+
+    Ok(StorageHistoryItem {
+        block_number,
+        block_hash: change.block_hash,
+        spec_version: at_block.spec_version(),
+        value,
+    })
+}
+
+impl<T: Config, Value> StorageHistory<T, Value> {
+    /// Get the next value. This is an alias for `futures::StreamExt::next(self)`.
+    pub async fn next(
+        &mut self,
+    ) -> Option<Result<StorageHistoryItem<T, Value>, StorageHistoryError>> {
+        StreamExt::next(self).await
+    }
+}
+
+impl<T: Config, Value> std::marker::Unpin for StorageHistory<T, Value> {}
+impl<T: Config, Value> Stream for StorageHistory<T, Value> {
+    type Item = Result<StorageHistoryItem<T, Value>, StorageHistoryError>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.stream.poll_next_unpin(cx)
+    }
+}
+
+/// The value of some storage entry at a block in which it changed.
+#[derive(Debug, Clone)]
+pub struct StorageHistoryItem<T: Config, Value> {
+    block_number: u64,
+    block_hash: HashFor<T>,
+    spec_version: u32,
+    value: Option<Value>,
+}
+
+impl<T: Config, Value> StorageHistoryItem<T, Value> {
+    /// The number of the block in which the value changed.
+    pub fn block_number(&self) -> u64 {
+        self.block_number
+    }
+
+    /// The hash of the block in which the value changed.
+    pub fn block_hash(&self) -> HashFor<T> {
+        self.block_hash
+    }
+
+    /// The spec version of the runtime whose metadata was used to decode the value.
+    pub fn spec_version(&self) -> u32 {
+        self.spec_version
+    }
+
+    /// The decoded value. This is `None` if there is no value (and no default value) in this block.
+    pub fn value(&self) -> Option<&Value> {
+        self.value.as_ref()
+    }
+
+    /// Consume this, returning the decoded value.
+    pub fn into_value(self) -> Option<Value> {
+        self.value
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::backend::LegacyBackend;
+    use crate::backend::legacy_test_utils::{block_hash, chain_with_value_history};
+    use crate::client::OnlineClient;
+    use crate::client::test_utils::config;
+    use crate::config::SubstrateConfig;
+    use crate::error::StorageHistoryError;
+    use codec::Encode;
+    use futures::TryStreamExt;
+    use std::sync::Arc;
+    use subxt_rpcs::RpcClient;
+
+    // A client for a chain in which `System::Number` has the given values in blocks 0, 1, 2...
+    async fn client(numbers: &[u32]) -> OnlineClient<SubstrateConfig> {
+        let key = frame_decode::storage::encode_storage_key_prefix("System", "Number").to_vec();
+        let values = numbers.iter().map(|n| Some(n.encode())).collect();
+        let mock_client = chain_with_value_history(key, values, Default::default());
+        let backend = LegacyBackend::builder().build(RpcClient::new(mock_client));
+
+        OnlineClient::from_backend_with_config(config(), Arc::new(backend))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn values_are_returned_for_blocks_in_which_they_change() {
+        let client = client(&[1, 1, 2, 2, 3]).await;
+
+        let items: Vec<_> = client
+            .storage_history(("System", "Number"), vec![], 0u64, 4u64)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+
+        let items: Vec<_> = items
+            .into_iter()
+            .map(|item| {
+                let value = item.value().and_then(|v| v.as_u128());
+                (item.block_number(), item.block_hash(), value)
+            })
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                (0, block_hash(0), Some(1)),
+                (2, block_hash(2), Some(2)),
+                (4, block_hash(4), Some(3)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn block_range_must_not_be_reversed() {
+        let client = client(&[1, 1, 2, 2]).await;
+
+        let res = client
+            .storage_history(("System", "Number"), vec![], 3u64, 1u64)
+            .await;
+        assert!(matches!(
+            res,
+            Err(StorageHistoryError::InvalidBlockRange { from: 3, to: 1 })
+        ));
+    }
+}
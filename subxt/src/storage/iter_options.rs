@@ -0,0 +1,87 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+use crate::backend::StorageFetchDescendantsOptions;
+use crate::error::StorageError;
+use serde::{Deserialize, Serialize};
+
+/// A position within some storage iteration, obtained from [`super::StorageEntries::cursor()`].
+/// This can be serialized and stored, and later handed to [`StorageIterOptions::resume_from()`]
+/// to continue iterating from where we left off.
+///
+/// A cursor is just the last storage key that was seen. To see a consistent set of values,
+/// resume iterating at the same block that the cursor was obtained from.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageCursor {
+    #[serde(with = "impl_serde::serialize")]
+    last_key: Vec<u8>,
+}
+
+impl StorageCursor {
+    /// Create a cursor from the last storage key that was seen.
+    pub fn new(last_key: Vec<u8>) -> Self {
+        StorageCursor { last_key }
+    }
+
+    /// The last storage key that was seen. Iterating from this cursor will return
+    /// values with keys after this one.
+    pub fn last_key(&self) -> &[u8] {
+        &self.last_key
+    }
+
+    /// Consume the cursor, returning the last storage key that was seen.
+    pub fn into_last_key(self) -> Vec<u8> {
+        self.last_key
+    }
+}
+
+/// Options to configure storage iteration via [`super::StorageEntry::iter_with_options()`].
+#[derive(Clone, Debug, Default)]
+pub struct StorageIterOptions {
+    pub(crate) cursor: Option<StorageCursor>,
+    pub(crate) page_size: Option<u32>,
+}
+
+impl StorageIterOptions {
+    /// Create a new set of options with nothing configured.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resume iterating from the given cursor, returning only values which come after it.
+    pub fn resume_from(mut self, cursor: StorageCursor) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+
+    /// The number of entries to ask for in each request. Backends which don't fetch
+    /// storage entries in pages ignore this. If not set, the backend default is used.
+    pub fn page_size(mut self, page_size: u32) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+
+    // Check that any cursor we're resuming from points at a key under the `prefix` being
+    // iterated over, and then split these options into that key and the options to hand to
+    // the backend.
+    pub(crate) fn into_backend_options(
+        self,
+        prefix: &[u8],
+    ) -> Result<(Option<Vec<u8>>, StorageFetchDescendantsOptions), StorageError> {
+        let cursor_in_iteration = self
+            .cursor
+            .as_ref()
+            .is_none_or(|cursor| cursor.last_key().starts_with(prefix));
+        if !cursor_in_iteration {
+            return Err(StorageError::CursorNotInIteration);
+        }
+
+        let last_key = self.cursor.as_ref().map(|c| c.last_key().to_vec());
+        let backend_options = StorageFetchDescendantsOptions {
+            start_after: self.cursor.map(StorageCursor::into_last_key),
+            page_size: self.page_size,
+        };
+        Ok((last_key, backend_options))
+    }
+}
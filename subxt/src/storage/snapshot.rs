@@ -0,0 +1,267 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+use crate::backend::StorageResponse;
+use crate::error::StorageSnapshotError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::path::Path;
+
+/// A snapshot of raw storage key/value pairs, held in memory.
+///
+/// A snapshot can be obtained from a live node via [`super::StorageClient::snapshot()`],
+/// loaded from the `genesis.raw.top` section of a raw chain spec via
+/// [`StorageSnapshot::from_raw_chain_spec()`], or built up by hand. It can then be handed to
+/// [`crate::client::OfflineClientAtBlock::with_storage_snapshot()`] to fetch and iterate over
+/// storage entries without a connection to a node.
+///
+/// Snapshots serialize to (and deserialize from) a JSON object of hex encoded keys to hex
+/// encoded values, which is the same format used by `genesis.raw.top` in raw chain specs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StorageSnapshot {
+    entries: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl StorageSnapshot {
+    /// Create a new, empty snapshot.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a snapshot from the `genesis.raw.top` section of a raw chain spec, given the chain spec JSON.
+    pub fn from_raw_chain_spec(chain_spec_json: &str) -> Result<Self, StorageSnapshotError> {
+        #[derive(Deserialize)]
+        struct ChainSpec {
+            genesis: Genesis,
+        }
+        #[derive(Deserialize)]
+        struct Genesis {
+            raw: Option<RawGenesis>,
+        }
+        #[derive(Deserialize)]
+        struct RawGenesis {
+            top: StorageSnapshot,
+        }
+
+        let chain_spec: ChainSpec = serde_json::from_str(chain_spec_json)
+            .map_err(StorageSnapshotError::CannotDeserialize)?;
+        let raw = chain_spec
+            .genesis
+            .raw
+            .ok_or(StorageSnapshotError::NoRawGenesis)?;
+
+        Ok(raw.top)
+    }
+
+    /// Load a snapshot from a JSON object of hex encoded keys to hex encoded values.
+    pub fn from_json(json: &str) -> Result<Self, StorageSnapshotError> {
+        serde_json::from_str(json).map_err(StorageSnapshotError::CannotDeserialize)
+    }
+
+    /// Serialize this snapshot to a JSON object of hex encoded keys to hex encoded values.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("serializing a map of strings cannot fail; qed")
+    }
+
+    /// Load a snapshot from a file previously written by [`StorageSnapshot::to_file()`].
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, StorageSnapshotError> {
+        let json = std::fs::read_to_string(path).map_err(StorageSnapshotError::Io)?;
+        Self::from_json(&json)
+    }
+
+    /// Write this snapshot to a file, so that it can later be loaded with
+    /// [`StorageSnapshot::from_file()`].
+    pub fn to_file(&self, path: impl AsRef<Path>) -> Result<(), StorageSnapshotError> {
+        std::fs::write(path, self.to_json()).map_err(StorageSnapshotError::Io)
+    }
+
+    /// Insert a value at the given key, returning the previous value if there was one.
+    pub fn insert(&mut self, key: Vec<u8>, value: Vec<u8>) -> Option<Vec<u8>> {
+        self.entries.insert(key, value)
+    }
+
+    /// Remove the value at the given key, returning it if it existed.
+    pub fn remove(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+        self.entries.remove(key)
+    }
+
+    /// Get the value at the given key.
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        self.entries.get(key).map(|v| &**v)
+    }
+
+    /// The number of key/value pairs in this snapshot.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Is the snapshot empty?
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over all of the key/value pairs in this snapshot, ordered by key.
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], &[u8])> {
+        self.entries.iter().map(|(k, v)| (&**k, &**v))
+    }
+
+    /// Iterate over the key/value pairs whose keys begin with the given prefix, ordered by key.
+    pub fn iter_prefix<'a>(
+        &'a self,
+        prefix: &'a [u8],
+    ) -> impl Iterator<Item = (&'a [u8], &'a [u8])> {
+        self.iter_prefix_after(prefix, None)
+    }
+
+    // Iterate over the key/value pairs whose keys begin with the given prefix,
+    // and which come after `start_after` if it's given.
+    pub(crate) fn iter_prefix_after<'a>(
+        &'a self,
+        prefix: &'a [u8],
+        start_after: Option<&[u8]>,
+    ) -> impl Iterator<Item = (&'a [u8], &'a [u8])> + use<'a> {
+        let lower = match start_after {
+            Some(start) if start >= prefix => Bound::Excluded(start.to_vec()),
+            _ => Bound::Included(prefix.to_vec()),
+        };
+
+        self.entries
+            .range((lower, Bound::Unbounded))
+            .take_while(move |(k, _)| k.starts_with(prefix))
+            .map(|(k, v)| (&**k, &**v))
+    }
+
+    // Hand back the storage responses for the given keys, skipping any which don't exist.
+    pub(crate) fn values_at(&self, keys: &[Vec<u8>]) -> Vec<StorageResponse> {
+        keys.iter()
+            .filter_map(|key| {
+                let value = self.entries.get(key)?;
+                Some(StorageResponse {
+                    key: key.clone(),
+                    value: value.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl FromIterator<(Vec<u8>, Vec<u8>)> for StorageSnapshot {
+    fn from_iter<I: IntoIterator<Item = (Vec<u8>, Vec<u8>)>>(iter: I) -> Self {
+        StorageSnapshot {
+            entries: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<(Vec<u8>, Vec<u8>)> for StorageSnapshot {
+    fn extend<I: IntoIterator<Item = (Vec<u8>, Vec<u8>)>>(&mut self, iter: I) {
+        self.entries.extend(iter)
+    }
+}
+
+impl Serialize for StorageSnapshot {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.entries.iter().map(|(k, v)| {
+            (
+                format!("0x{}", hex::encode(k)),
+                format!("0x{}", hex::encode(v)),
+            )
+        }))
+    }
+}
+
+impl<'de> Deserialize<'de> for StorageSnapshot {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        fn from_hex<E: serde::de::Error>(s: &str) -> Result<Vec<u8>, E> {
+            hex::decode(s.trim_start_matches("0x"))
+                .map_err(|e| E::custom(format!("invalid hex string '{s}': {e}")))
+        }
+
+        let entries = BTreeMap::<String, String>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(k, v)| Ok((from_hex(&k)?, from_hex(&v)?)))
+            .collect::<Result<_, D::Error>>()?;
+
+        Ok(StorageSnapshot { entries })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn loads_raw_chain_spec() {
+        let chain_spec = r#"{
+            "name": "Test",
+            "genesis": {
+                "raw": {
+                    "top": {
+                        "0x0102": "0xaa",
+                        "0x0103": "0xbb",
+                        "0x02": "0x"
+                    },
+                    "childrenDefault": {}
+                }
+            }
+        }"#;
+
+        let snapshot = StorageSnapshot::from_raw_chain_spec(chain_spec).unwrap();
+        assert_eq!(snapshot.len(), 3);
+        assert_eq!(snapshot.get(&[1, 2]), Some(&[0xaa][..]));
+        assert_eq!(snapshot.get(&[2]), Some(&[][..]));
+    }
+
+    #[test]
+    fn non_raw_chain_spec_is_an_error() {
+        let chain_spec = r#"{ "genesis": { "runtimeGenesis": {} } }"#;
+        assert!(matches!(
+            StorageSnapshot::from_raw_chain_spec(chain_spec),
+            Err(StorageSnapshotError::NoRawGenesis)
+        ));
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        let snapshot: StorageSnapshot = [(vec![1, 2, 3], vec![4]), (vec![5], vec![])]
+            .into_iter()
+            .collect();
+
+        let json = snapshot.to_json();
+        assert_eq!(json, r#"{"0x010203":"0x04","0x05":"0x"}"#);
+        assert_eq!(StorageSnapshot::from_json(&json).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn iterates_by_prefix() {
+        let snapshot: StorageSnapshot = [
+            (vec![1], vec![]),
+            (vec![1, 1], vec![]),
+            (vec![1, 2], vec![]),
+            (vec![1, 3], vec![]),
+            (vec![2, 1], vec![]),
+        ]
+        .into_iter()
+        .collect();
+
+        let keys = |start_after: Option<&[u8]>| {
+            snapshot
+                .iter_prefix_after(&[1], start_after)
+                .map(|(k, _)| k.to_vec())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            keys(None),
+            vec![vec![1], vec![1, 1], vec![1, 2], vec![1, 3]]
+        );
+        assert_eq!(keys(Some(&[1, 1])), vec![vec![1, 2], vec![1, 3]]);
+        assert_eq!(
+            keys(Some(&[0])),
+            vec![vec![1], vec![1, 1], vec![1, 2], vec![1, 3]]
+        );
+        assert_eq!(keys(Some(&[1, 3])), Vec::<Vec<u8>>::new());
+    }
+}
@@ -0,0 +1,281 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+use crate::backend::StorageFetchDescendantsOptions;
+use crate::client::StorageClientAtBlockT;
+use crate::config::Config;
+use crate::error::StorageError;
+use futures::StreamExt;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Options to configure how storage statistics are gathered via
+/// [`super::StorageEntryRef::stats()`] or [`super::StorageClient::stats()`].
+#[derive(Clone, Debug)]
+pub struct StorageStatsOptions {
+    pub(crate) page_size: Option<u32>,
+    pub(crate) largest_items: usize,
+}
+
+impl Default for StorageStatsOptions {
+    fn default() -> Self {
+        StorageStatsOptions {
+            page_size: None,
+            largest_items: 10,
+        }
+    }
+}
+
+impl StorageStatsOptions {
+    /// Create a new set of options. By default, the 10 largest items in each entry are kept.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of entries to ask for in each request. Backends which don't fetch
+    /// storage entries in pages ignore this. If not set, the backend default is used.
+    pub fn page_size(mut self, page_size: u32) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+
+    /// The number of largest items (by value size) to keep track of for each storage entry.
+    pub fn largest_items(mut self, largest_items: usize) -> Self {
+        self.largest_items = largest_items;
+        self
+    }
+}
+
+/// Size and key count statistics for a single storage entry at some block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StorageEntryStats {
+    pallet_name: String,
+    entry_name: String,
+    key_count: u64,
+    total_key_bytes: u64,
+    total_value_bytes: u64,
+    histogram: StorageSizeHistogram,
+    largest_items: Vec<StorageItemSize>,
+}
+
+impl StorageEntryStats {
+    /// The name of the pallet that this storage entry is in.
+    pub fn pallet_name(&self) -> &str {
+        &self.pallet_name
+    }
+
+    /// The name of the storage entry.
+    pub fn entry_name(&self) -> &str {
+        &self.entry_name
+    }
+
+    /// The number of keys which have a value in this storage entry.
+    pub fn key_count(&self) -> u64 {
+        self.key_count
+    }
+
+    /// The total size in bytes of all of the keys in this storage entry.
+    pub fn total_key_bytes(&self) -> u64 {
+        self.total_key_bytes
+    }
+
+    /// The total size in bytes of all of the values in this storage entry.
+    pub fn total_value_bytes(&self) -> u64 {
+        self.total_value_bytes
+    }
+
+    /// A histogram of the sizes of the values in this storage entry.
+    pub fn histogram(&self) -> &StorageSizeHistogram {
+        &self.histogram
+    }
+
+    /// The largest items in this storage entry, largest first.
+    pub fn largest_items(&self) -> &[StorageItemSize] {
+        &self.largest_items
+    }
+}
+
+/// A histogram of storage value sizes. Bucket `0` counts values of 0 or 1 bytes, and each bucket
+/// `n` after that counts values whose size is at least `2^n` and less than `2^(n+1)` bytes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StorageSizeHistogram {
+    buckets: Vec<u64>,
+}
+
+impl StorageSizeHistogram {
+    /// The number of values counted in each bucket. Trailing empty buckets are not included.
+    pub fn buckets(&self) -> &[u64] {
+        &self.buckets
+    }
+
+    /// The range of value sizes (in bytes) that are counted by the given bucket.
+    pub fn bucket_range(bucket: usize) -> std::ops::Range<u64> {
+        let start = if bucket == 0 { 0 } else { 1u64 << bucket };
+        start..(1u64 << (bucket + 1))
+    }
+
+    fn add(&mut self, size: usize) {
+        let bucket = (usize::BITS - size.max(1).leading_zeros() - 1) as usize;
+        if self.buckets.len() <= bucket {
+            self.buckets.resize(bucket + 1, 0);
+        }
+        self.buckets[bucket] += 1;
+    }
+}
+
+/// The key and value size of a single storage item.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct StorageItemSize {
+    // Dev note: field order matters for the derived Ord impl;
+    // we want to order by value size first.
+    value_size: usize,
+    key: Vec<u8>,
+}
+
+impl StorageItemSize {
+    /// The full storage key of this item.
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+
+    /// The size in bytes of the value stored at this key.
+    pub fn value_size(&self) -> usize {
+        self.value_size
+    }
+}
+
+// Gather statistics about the entry, streaming values so that only the
+// largest items need to be held in memory at any one time.
+pub(crate) async fn entry_stats<T: Config, Client: StorageClientAtBlockT<T>>(
+    client: &Client,
+    pallet_name: &str,
+    entry_name: &str,
+    options: StorageStatsOptions,
+) -> Result<StorageEntryStats, StorageError> {
+    let key_prefix = frame_decode::storage::encode_storage_key_prefix(pallet_name, entry_name);
+    let backend_options = StorageFetchDescendantsOptions {
+        start_after: None,
+        page_size: options.page_size,
+    };
+
+    let mut values = client
+        .storage_fetch_descendant_values(key_prefix.to_vec(), backend_options)
+        .await
+        .map_err(StorageError::CannotIterateValues)?;
+
+    let mut stats = StatsBuilder::new(options.largest_items);
+    while let Some(value) = values.next().await {
+        let value = value.map_err(StorageError::StreamFailure)?;
+        stats.add(value.key, value.value.len());
+    }
+
+    Ok(stats.build(pallet_name.to_owned(), entry_name.to_owned()))
+}
+
+struct StatsBuilder {
+    key_count: u64,
+    total_key_bytes: u64,
+    total_value_bytes: u64,
+    histogram: StorageSizeHistogram,
+    max_largest_items: usize,
+    // A min-heap, so that the smallest of the largest items can be evicted.
+    largest_items: BinaryHeap<Reverse<StorageItemSize>>,
+}
+
+impl StatsBuilder {
+    fn new(max_largest_items: usize) -> Self {
+        StatsBuilder {
+            key_count: 0,
+            total_key_bytes: 0,
+            total_value_bytes: 0,
+            histogram: StorageSizeHistogram::default(),
+            max_largest_items,
+            largest_items: BinaryHeap::new(),
+        }
+    }
+
+    fn add(&mut self, key: Vec<u8>, value_size: usize) {
+        self.key_count += 1;
+        self.total_key_bytes += key.len() as u64;
+        self.total_value_bytes += value_size as u64;
+        self.histogram.add(value_size);
+
+        if self.max_largest_items == 0 {
+            return;
+        }
+        if self.largest_items.len() < self.max_largest_items {
+            self.largest_items
+                .push(Reverse(StorageItemSize { value_size, key }));
+        } else if self
+            .largest_items
+            .peek()
+            .is_some_and(|Reverse(smallest)| smallest.value_size < value_size)
+        {
+            self.largest_items.pop();
+            self.largest_items
+                .push(Reverse(StorageItemSize { value_size, key }));
+        }
+    }
+
+    fn build(self, pallet_name: String, entry_name: String) -> StorageEntryStats {
+        // Sorting the reversed items in ascending order puts the largest first.
+        let largest_items = self
+            .largest_items
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(item)| item)
+            .collect();
+
+        StorageEntryStats {
+            pallet_name,
+            entry_name,
+            key_count: self.key_count,
+            total_key_bytes: self.total_key_bytes,
+            total_value_bytes: self.total_value_bytes,
+            histogram: self.histogram,
+            largest_items,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn histogram_buckets_by_powers_of_two() {
+        let mut histogram = StorageSizeHistogram::default();
+        for size in [0, 1, 2, 3, 4, 7, 8, 1024] {
+            histogram.add(size);
+        }
+
+        assert_eq!(&histogram.buckets()[..4], &[2, 2, 2, 1]);
+        assert_eq!(histogram.buckets().len(), 11);
+        assert_eq!(histogram.buckets()[10], 1);
+
+        assert_eq!(StorageSizeHistogram::bucket_range(0), 0..2);
+        assert_eq!(StorageSizeHistogram::bucket_range(3), 8..16);
+        assert!(StorageSizeHistogram::bucket_range(10).contains(&1024));
+    }
+
+    #[test]
+    fn keeps_only_the_largest_items() {
+        let mut builder = StatsBuilder::new(2);
+        for (key, size) in [(1u8, 10), (2, 50), (3, 5), (4, 30), (5, 40)] {
+            builder.add(vec![key; 3], size);
+        }
+
+        let stats = builder.build("Pallet".into(), "Entry".into());
+        assert_eq!(stats.key_count(), 5);
+        assert_eq!(stats.total_key_bytes(), 15);
+        assert_eq!(stats.total_value_bytes(), 135);
+
+        let largest: Vec<_> = stats
+            .largest_items()
+            .iter()
+            .map(|item| (item.key()[0], item.value_size()))
+            .collect();
+        assert_eq!(largest, vec![(2, 50), (5, 40)]);
+    }
+}
@@ -1,13 +1,14 @@
-use crate::backend::{BackendExt, StorageResponse, StreamOf};
-use crate::client::{OfflineClientAtBlockT, OnlineClientAtBlockT};
-use crate::config::Config;
+use crate::backend::{StorageResponse, StreamOf};
+use crate::client::{OfflineClientAtBlockT, StorageClientAtBlockT};
+use crate::config::{Config, HashFor};
 use crate::error::{BackendError, StorageError};
 use crate::storage::address::Address;
-use crate::storage::{PrefixOf, StorageKeyValue, StorageValue};
+use crate::storage::{PrefixOf, StorageCursor, StorageIterOptions, StorageKeyValue, StorageValue};
 use crate::utils::YesMaybe;
 use core::marker::PhantomData;
 use frame_decode::storage::{IntoEncodableValues, StorageInfo, StorageTypeInfo};
 use futures::{Stream, StreamExt};
+use scale_encode::EncodeAsType;
 use scale_info::PortableRegistry;
 use std::pin::Pin;
 use std::sync::Arc;
@@ -95,6 +96,22 @@ where
         })
     }
 
+    // Wrap the bytes found at some key in this entry, falling back to the default value if none were found.
+    pub(crate) fn value_or_default(
+        &self,
+        bytes: Option<Vec<u8>>,
+    ) -> Option<StorageValue<'atblock, Addr::Value>> {
+        bytes
+            .map(|bytes| {
+                StorageValue::new(
+                    self.inner.info.clone(),
+                    self.inner.client.metadata_ref().types(),
+                    bytes,
+                )
+            })
+            .or_else(|| self.default_value())
+    }
+
     /// The keys for plain storage values are always 32 byte hashes.
     pub fn key_prefix(&self) -> [u8; 32] {
         frame_decode::storage::encode_storage_key_prefix(self.pallet_name(), self.entry_name())
@@ -134,6 +151,37 @@ where
         }
     }
 
+    /// Encode a value so that it can be written to storage at the key given by `key_parts`, returning
+    /// the full storage key and the SCALE encoded value. The value can be any type implementing
+    /// [`EncodeAsType`] (for example a [`scale_value::Value`]), and is encoded according to the type
+    /// of this storage entry in the metadata, so an error is returned if its shape is not compatible.
+    ///
+    /// This is useful for building custom genesis or test states, for example by inserting the
+    /// result into a [`crate::storage::StorageSnapshot`].
+    pub fn encode_key_value<V: EncodeAsType + ?Sized>(
+        &self,
+        key_parts: Addr::KeyParts,
+        value: &V,
+    ) -> Result<(Vec<u8>, Vec<u8>), StorageError> {
+        let key = self.fetch_key(key_parts)?;
+        let value = self.encode_value(value)?;
+        Ok((key, value))
+    }
+
+    /// SCALE encode a value according to the type of this storage entry in the metadata. See
+    /// [`Self::encode_key_value()`].
+    pub fn encode_value<V: EncodeAsType + ?Sized>(
+        &self,
+        value: &V,
+    ) -> Result<Vec<u8>, StorageError> {
+        value
+            .encode_as_type(
+                self.inner.info.value_id,
+                self.inner.client.metadata_ref().types(),
+            )
+            .map_err(StorageError::CannotEncodeValue)
+    }
+
     // This has a more lax type signature than `.key` and so can be used in a couple of places internally.
     fn key_from_any_parts(
         &self,
@@ -154,7 +202,7 @@ impl<'atblock, T, Client, Addr> StorageEntry<'atblock, T, Client, Addr>
 where
     T: Config,
     Addr: Address,
-    Client: OnlineClientAtBlockT<T>,
+    Client: StorageClientAtBlockT<T>,
 {
     /// Fetch a storage value within this storage entry.
     ///
@@ -193,25 +241,78 @@ where
         key_parts: Addr::KeyParts,
     ) -> Result<Option<StorageValue<'atblock, Addr::Value>>, StorageError> {
         let key = self.fetch_key(key_parts)?;
-        let block_hash = self.inner.client.block_ref().hash();
+        let bytes = super::fetch_raw_value::<T, Client>(self.inner.client, key).await?;
 
-        let value = self
+        Ok(self.value_or_default(bytes))
+    }
+
+    /// Fetch many storage values within this storage entry, batching the keys into as few requests
+    /// as possible. This is much faster than calling [`StorageEntry::try_fetch()`] for each key.
+    ///
+    /// The values are returned in the same order as the key parts provided. As with
+    /// [`StorageEntry::try_fetch()`], the default value will be returned for any key without a
+    /// value if one exists, and `None` otherwise.
+    pub async fn fetch_many(
+        &self,
+        key_parts: impl IntoIterator<Item = Addr::KeyParts>,
+    ) -> Result<Vec<Option<StorageValue<'atblock, Addr::Value>>>, StorageError> {
+        let keys = key_parts
+            .into_iter()
+            .map(|key_parts| self.fetch_key(key_parts))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let values = super::fetch_raw_values::<T, Client>(self.inner.client, keys).await?;
+
+        Ok(values
+            .into_iter()
+            .map(|bytes| self.value_or_default(bytes))
+            .collect())
+    }
+
+    /// Fetch the hash of a storage value within this storage entry, without downloading the value
+    /// itself. The key parts are provided as in [`StorageEntry::try_fetch()`].
+    ///
+    /// If no value is found, `None` will be returned. Unlike [`StorageEntry::fetch()`], we do not
+    /// fall back to any default value.
+    pub async fn fetch_hash(
+        &self,
+        key_parts: Addr::KeyParts,
+    ) -> Result<Option<HashFor<T>>, StorageError> {
+        let key = self.fetch_key(key_parts)?;
+        let mut hashes = self
             .inner
             .client
-            .backend()
-            .storage_fetch_value(key, block_hash)
+            .storage_fetch_hashes(vec![key])
+            .await
+            .map_err(StorageError::CannotFetchValue)?;
+
+        let hash = hashes
+            .next()
             .await
+            .transpose()
             .map_err(StorageError::CannotFetchValue)?
-            .map(|bytes| {
-                StorageValue::new(
-                    self.inner.info.clone(),
-                    self.inner.client.metadata_ref().types(),
-                    bytes,
-                )
-            })
-            .or_else(|| self.default_value());
+            .map(|res| res.hash);
 
-        Ok(value)
+        Ok(hash)
+    }
+
+    /// Fetch the Merkle value of the closest descendant of the key formed by the given key parts
+    /// (which are provided as in [`StorageEntry::iter()`]). This value will change if any of the
+    /// storage values under this key change, and so comparing it across blocks is a cheap way to
+    /// detect whether anything under some map (or part of a map) has changed.
+    ///
+    /// `None` is returned if there are no values under the key. Only backends which support the
+    /// new JSON-RPC APIs can return this.
+    pub async fn merkle_value<KeyParts: PrefixOf<Addr::KeyParts>>(
+        &self,
+        key_parts: KeyParts,
+    ) -> Result<Option<Vec<u8>>, StorageError> {
+        let key = self.iter_key(key_parts)?;
+        self.inner
+            .client
+            .storage_closest_descendant_merkle_value(key)
+            .await
+            .map_err(StorageError::CannotFetchValue)
     }
 
     /// Iterate over storage values within this storage entry.
@@ -228,6 +329,21 @@ where
     pub async fn iter<KeyParts: PrefixOf<Addr::KeyParts>>(
         &self,
         key_parts: KeyParts,
+    ) -> Result<StorageEntries<'atblock, Addr>, StorageError> {
+        self.iter_with_options(key_parts, StorageIterOptions::new())
+            .await
+    }
+
+    /// Iterate over storage values within this storage entry, as with [`StorageEntry::iter()`], but
+    /// with some additional [`StorageIterOptions`] to configure the page size or resume iterating from
+    /// a [`StorageCursor`] obtained from [`StorageEntries::cursor()`].
+    ///
+    /// Not every backend supports resuming from a cursor; the chainHead backend in particular will
+    /// return an error if asked to.
+    pub async fn iter_with_options<KeyParts: PrefixOf<Addr::KeyParts>>(
+        &self,
+        key_parts: KeyParts,
+        options: StorageIterOptions,
     ) -> Result<StorageEntries<'atblock, Addr>, StorageError> {
         let num_keys = self.inner.info.keys.len();
         if key_parts.num_encodable_values() >= num_keys {
@@ -240,13 +356,13 @@ where
         let info = self.inner.info.clone();
         let types = self.inner.client.metadata_ref().types();
         let key_bytes = self.key_from_any_parts(key_parts)?;
-        let block_hash = self.inner.client.block_ref().hash();
+
+        let (last_key, backend_options) = options.into_backend_options(&key_bytes)?;
 
         let stream = self
             .inner
             .client
-            .backend()
-            .storage_fetch_descendant_values(key_bytes, block_hash)
+            .storage_fetch_descendant_values(key_bytes, backend_options)
             .await
             .map_err(StorageError::CannotIterateValues)?;
 
@@ -254,6 +370,7 @@ where
             info,
             stream,
             types,
+            last_key,
             marker: PhantomData,
         })
     }
@@ -266,6 +383,8 @@ pub struct StorageEntries<'atblock, Addr> {
     // things we need to convert this into what we want:
     info: Arc<StorageInfo<'atblock, u32>>,
     types: &'atblock PortableRegistry,
+    // The last key that we handed back, so that iteration can be resumed from it:
+    last_key: Option<Vec<u8>>,
     marker: PhantomData<Addr>,
 }
 
@@ -274,6 +393,13 @@ impl<'atblock, Addr: Address> StorageEntries<'atblock, Addr> {
     pub async fn next(&mut self) -> Option<Result<StorageKeyValue<'atblock, Addr>, StorageError>> {
         StreamExt::next(self).await
     }
+
+    /// A cursor pointing at the last entry handed back, which can be used to resume
+    /// iterating from this point via [`StorageIterOptions::resume_from()`]. This is `None`
+    /// if no entries have been handed back yet and we did not resume from a cursor.
+    pub fn cursor(&self) -> Option<StorageCursor> {
+        self.last_key.clone().map(StorageCursor::new)
+    }
 }
 
 impl<'atblock, Addr> std::marker::Unpin for StorageEntries<'atblock, Addr> {}
@@ -290,6 +416,8 @@ impl<'atblock, Addr: Address> Stream for StorageEntries<'atblock, Addr> {
             None => return Poll::Ready(None),
         };
 
+        self.last_key = Some(val.key.clone());
+
         Poll::Ready(Some(Ok(StorageKeyValue::new(
             self.info.clone(),
             self.types,
@@ -298,3 +426,127 @@ impl<'atblock, Addr: Address> Stream for StorageEntries<'atblock, Addr> {
         ))))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::client::test_utils::snapshot_client;
+    use crate::config::Hasher;
+    use crate::error::StorageError;
+    use crate::storage::{StorageCursor, StorageIterOptions, StorageSnapshot};
+    use codec::Encode;
+    use scale_value::Value;
+
+    const ACCOUNT: (&str, &str) = ("System", "Account");
+
+    fn account(n: u8) -> Vec<Value> {
+        vec![Value::from_bytes([n; 32])]
+    }
+
+    fn accounts_snapshot(ns: impl IntoIterator<Item = u8>) -> StorageSnapshot {
+        let client = snapshot_client(StorageSnapshot::new());
+        let entry = client.storage().entry(ACCOUNT).unwrap();
+        ns.into_iter()
+            .map(|n| (entry.fetch_key(account(n)).unwrap(), vec![n]))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn iteration_can_be_resumed_from_a_cursor() {
+        let client = snapshot_client(accounts_snapshot(1..=4));
+        let entry = client.storage().entry(ACCOUNT).unwrap();
+
+        // Iterate over the first two entries and take the cursor.
+        let mut entries = entry.iter(Vec::<Value>::new()).await.unwrap();
+        assert!(entries.cursor().is_none());
+        let mut seen = Vec::new();
+        for _ in 0..2 {
+            let kv = entries.next().await.unwrap().unwrap();
+            seen.push(kv.key_bytes().to_vec());
+        }
+        let cursor = entries.cursor().unwrap();
+        assert_eq!(cursor.last_key(), &*seen[1]);
+
+        // The cursor survives being serialized and deserialized.
+        let cursor: StorageCursor =
+            serde_json::from_str(&serde_json::to_string(&cursor).unwrap()).unwrap();
+
+        // Resuming hands back exactly the entries that we didn't see yet.
+        let options = StorageIterOptions::new().resume_from(cursor).page_size(1);
+        let mut entries = entry
+            .iter_with_options(Vec::<Value>::new(), options)
+            .await
+            .unwrap();
+        while let Some(kv) = entries.next().await {
+            seen.push(kv.unwrap().key_bytes().to_vec());
+        }
+
+        let mut all_keys: Vec<_> = accounts_snapshot(1..=4)
+            .iter()
+            .map(|(k, _)| k.to_vec())
+            .collect();
+        all_keys.sort();
+        assert_eq!(seen, all_keys);
+    }
+
+    #[tokio::test]
+    async fn cursor_must_be_within_the_iteration() {
+        let client = snapshot_client(accounts_snapshot(1..=2));
+        let entry = client.storage().entry(ACCOUNT).unwrap();
+
+        let options = StorageIterOptions::new().resume_from(StorageCursor::new(vec![1, 2, 3]));
+        let res = entry.iter_with_options(Vec::<Value>::new(), options).await;
+        assert!(matches!(res, Err(StorageError::CursorNotInIteration)));
+    }
+
+    #[tokio::test]
+    async fn fetch_hash_hashes_the_stored_value() {
+        let client = snapshot_client(accounts_snapshot([1]));
+        let entry = client.storage().entry(ACCOUNT).unwrap();
+
+        let hash = entry.fetch_hash(account(1)).await.unwrap();
+        assert_eq!(hash, Some(client.hasher().hash(&[1])));
+
+        // Unlike `fetch`, no default value is used when nothing is stored.
+        let hash = entry.fetch_hash(account(2)).await.unwrap();
+        assert_eq!(hash, None);
+    }
+
+    #[tokio::test]
+    async fn merkle_value_is_not_available_from_snapshots() {
+        let client = snapshot_client(accounts_snapshot([1]));
+        let entry = client.storage().entry(ACCOUNT).unwrap();
+
+        let res = entry.merkle_value(Vec::<Value>::new()).await;
+        assert!(matches!(res, Err(StorageError::CannotFetchValue(_))));
+    }
+
+    #[tokio::test]
+    async fn encoded_key_values_round_trip() {
+        let client = snapshot_client(StorageSnapshot::new());
+        let entry = client.storage().entry(("System", "Number")).unwrap();
+
+        // Dynamic and static values encode to the same bytes, at the same key that is fetched from.
+        let (key, value) = entry.encode_key_value(vec![], &Value::u128(123)).unwrap();
+        assert_eq!(key, entry.fetch_key(vec![]).unwrap());
+        assert_eq!(value, 123u32.encode());
+        assert_eq!(entry.encode_value(&123u32).unwrap(), value);
+
+        // The encoded value can be fetched back from a snapshot.
+        let client = snapshot_client(StorageSnapshot::from_iter([(key, value)]));
+        let number = client
+            .storage()
+            .fetch(("System", "Number"), vec![])
+            .await
+            .unwrap();
+        assert_eq!(number.decode_as::<u32>().unwrap(), 123);
+    }
+
+    #[test]
+    fn wrongly_shaped_values_cannot_be_encoded() {
+        let client = snapshot_client(StorageSnapshot::new());
+        let entry = client.storage().entry(("System", "Number")).unwrap();
+
+        let res = entry.encode_key_value(vec![], &Value::string("not a number"));
+        assert!(matches!(res, Err(StorageError::CannotEncodeValue(_))));
+    }
+}
@@ -0,0 +1,476 @@
+// Copyright 2019-2026 Parity Technologies (UK) Ltd.
+// This file is dual-licensed as Apache-2.0 or GPL-3.0.
+// see LICENSE for license details.
+
+use super::fetch_raw_value;
+use crate::backend::StorageFetchDescendantsOptions;
+use crate::client::StorageClientAtBlockT;
+use crate::config::Config;
+use crate::error::{RuntimeCodeError, StorageError};
+use crate::metadata::WellKnownStorageKey;
+use codec::{Decode, DecodeAll};
+use core::marker::PhantomData;
+use futures::{Stream, StreamExt};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::Read;
+
+pub use subxt_rpcs::methods::legacy::RuntimeVersion;
+
+// Note: these should match the constants in `sp_maybe_compressed_blob`.
+const ZSTD_PREFIX: [u8; 8] = [82, 188, 83, 118, 70, 219, 142, 5];
+const CODE_BLOB_BOMB_LIMIT: u64 = 50 * 1024 * 1024;
+
+/// A client for accessing the well known storage keys, which are not described by the metadata.
+/// This is created by calling [`super::StorageClient::well_known()`].
+#[derive(Clone)]
+pub struct WellKnownStorage<'atblock, T, Client> {
+    client: &'atblock Client,
+    marker: PhantomData<T>,
+}
+
+impl<'atblock, T, Client> WellKnownStorage<'atblock, T, Client> {
+    pub(crate) fn new(client: &'atblock Client) -> Self {
+        WellKnownStorage {
+            client,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'atblock, T: Config, Client: StorageClientAtBlockT<T>> WellKnownStorage<'atblock, T, Client> {
+    /// Fetch the runtime code stored at `:code`.
+    pub async fn code(&self) -> Result<RuntimeCode, StorageError> {
+        let bytes = fetch_raw_value(self.client, WellKnownStorageKey::Code.to_key())
+            .await?
+            .ok_or(StorageError::NoValueFound)?;
+        Ok(RuntimeCode::new(bytes))
+    }
+
+    /// Fetch the number of heap pages available to the runtime, stored at `:heappages`. This
+    /// is `None` if no value is stored, in which case the node will use some default.
+    pub async fn heap_pages(&self) -> Result<Option<u64>, StorageError> {
+        self.fetch_and_decode(WellKnownStorageKey::HeapPages).await
+    }
+
+    /// Fetch the index of the extrinsic currently being executed, stored at `:extrinsic_index`.
+    /// This is only set while a block is being built, and so is normally `None`.
+    pub async fn extrinsic_index(&self) -> Result<Option<u32>, StorageError> {
+        self.fetch_and_decode(WellKnownStorageKey::ExtrinsicIndex)
+            .await
+    }
+
+    /// Fetch the entropy stored at `:intrablock_entropy`. This is only set while a block is
+    /// being built, and so is normally `None`.
+    pub async fn intrablock_entropy(&self) -> Result<Option<[u8; 32]>, StorageError> {
+        self.fetch_and_decode(WellKnownStorageKey::IntraBlockEntropy)
+            .await
+    }
+
+    /// Iterate over the default child tries at this block, handing back the key and root hash of
+    /// each one. Use [`super::StorageClient::child_trie()`] with one of the returned keys to access
+    /// the values in that child trie.
+    pub async fn child_tries(
+        &self,
+    ) -> Result<
+        impl Stream<Item = Result<DefaultChildTrie, StorageError>> + use<T, Client>,
+        StorageError,
+    > {
+        let prefix = WellKnownStorageKey::DefaultChildStorage { child_key: &[] }.to_key();
+        let values = self
+            .client
+            .storage_fetch_descendant_values(prefix, StorageFetchDescendantsOptions::default())
+            .await
+            .map_err(StorageError::CannotIterateValues)?;
+
+        Ok(values.map(|value| {
+            let value = value.map_err(StorageError::StreamFailure)?;
+            let child_key = match WellKnownStorageKey::from_key(&value.key) {
+                Some(WellKnownStorageKey::DefaultChildStorage { child_key }) => child_key.to_vec(),
+                _ => value.key,
+            };
+            Ok(DefaultChildTrie {
+                child_key,
+                root: value.value,
+            })
+        }))
+    }
+
+    async fn fetch_and_decode<V: Decode>(
+        &self,
+        key: WellKnownStorageKey<'static>,
+    ) -> Result<Option<V>, StorageError> {
+        let key = key.to_key();
+        let Some(bytes) = fetch_raw_value(self.client, key.clone()).await? else {
+            return Ok(None);
+        };
+
+        let value = V::decode_all(&mut &*bytes).map_err(|reason| {
+            StorageError::CannotDecodeWellKnownValue {
+                key: String::from_utf8_lossy(&key).into_owned(),
+                reason,
+            }
+        })?;
+        Ok(Some(value))
+    }
+}
+
+/// A default child trie, returned from [`WellKnownStorage::child_tries()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DefaultChildTrie {
+    child_key: Vec<u8>,
+    root: Vec<u8>,
+}
+
+impl DefaultChildTrie {
+    /// The key of the child trie, without the `:child_storage:default:` prefix.
+    pub fn child_key(&self) -> &[u8] {
+        &self.child_key
+    }
+
+    /// The root hash of the child trie.
+    pub fn root(&self) -> &[u8] {
+        &self.root
+    }
+}
+
+/// Some runtime code, for example as stored at `:code`. The code may be compressed.
+///
+/// This can also be constructed from code found elsewhere (for example, code that has been
+/// stored but not yet enacted) in order to learn about an upcoming runtime upgrade.
+#[derive(Clone, PartialEq, Eq)]
+pub struct RuntimeCode {
+    bytes: Vec<u8>,
+}
+
+impl std::fmt::Debug for RuntimeCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RuntimeCode")
+            .field("len", &self.bytes.len())
+            .field("is_compressed", &self.is_compressed())
+            .finish()
+    }
+}
+
+impl RuntimeCode {
+    /// Wrap some runtime code, which may or may not be compressed.
+    pub fn new(bytes: Vec<u8>) -> Self {
+        RuntimeCode { bytes }
+    }
+
+    /// The raw bytes of the code, which may be compressed.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Consume this, returning the raw bytes of the code, which may be compressed.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Is the code compressed?
+    pub fn is_compressed(&self) -> bool {
+        self.bytes.starts_with(&ZSTD_PREFIX)
+    }
+
+    /// Return the uncompressed WASM code, decompressing it if necessary.
+    pub fn wasm(&self) -> Result<Cow<'_, [u8]>, RuntimeCodeError> {
+        let Some(compressed) = self.bytes.strip_prefix(&ZSTD_PREFIX) else {
+            return Ok(Cow::Borrowed(&self.bytes));
+        };
+
+        let mut source = compressed;
+        let decoder = ruzstd::decoding::StreamingDecoder::new(&mut source)
+            .map_err(|e| RuntimeCodeError::CannotDecompress(e.to_string()))?;
+
+        // Read one byte more than the limit so that we can tell if it was exceeded.
+        let mut wasm = Vec::new();
+        decoder
+            .take(CODE_BLOB_BOMB_LIMIT + 1)
+            .read_to_end(&mut wasm)
+            .map_err(|e| RuntimeCodeError::CannotDecompress(e.to_string()))?;
+        if wasm.len() as u64 > CODE_BLOB_BOMB_LIMIT {
+            return Err(RuntimeCodeError::TooLarge {
+                limit: CODE_BLOB_BOMB_LIMIT,
+            });
+        }
+
+        Ok(Cow::Owned(wasm))
+    }
+
+    /// Decode the runtime version embedded in the code. This is read from the `runtime_version`
+    /// and `runtime_apis` custom sections of the WASM, and so does not require executing it.
+    /// `None` is returned if the code does not contain a `runtime_version` section.
+    pub fn runtime_version(&self) -> Result<Option<RuntimeVersion>, RuntimeCodeError> {
+        let wasm = self.wasm()?;
+
+        let Some(mut version_bytes) = wasm_custom_section(&wasm, "runtime_version")? else {
+            return Ok(None);
+        };
+        let mut version = ScaleRuntimeVersion::decode(&mut version_bytes)
+            .map_err(RuntimeCodeError::CannotDecodeRuntimeVersion)?;
+
+        // Each runtime API is an 8 byte ID followed by a little endian u32 version.
+        if let Some(apis_bytes) = wasm_custom_section(&wasm, "runtime_apis")? {
+            version.apis = apis_bytes
+                .chunks_exact(12)
+                .map(|api| {
+                    let mut id = [0u8; 8];
+                    id.copy_from_slice(&api[..8]);
+                    let version = u32::from_le_bytes([api[8], api[9], api[10], api[11]]);
+                    (id, version)
+                })
+                .collect();
+        }
+
+        Ok(Some(version.into_runtime_version()))
+    }
+}
+
+/// Decode the SCALE encoded runtime version returned from the `Core_version` runtime API into
+/// the same [`RuntimeVersion`] that the `state_getRuntimeVersion` RPC method returns.
+pub(crate) fn decode_runtime_version(bytes: &[u8]) -> Result<RuntimeVersion, codec::Error> {
+    ScaleRuntimeVersion::decode(&mut &*bytes).map(ScaleRuntimeVersion::into_runtime_version)
+}
+
+// The SCALE encoded form of a runtime version, as embedded in some [`RuntimeCode`] or returned
+// from the `Core_version` runtime API.
+struct ScaleRuntimeVersion {
+    spec_name: String,
+    impl_name: String,
+    authoring_version: u32,
+    spec_version: u32,
+    impl_version: u32,
+    apis: Vec<([u8; 8], u32)>,
+    // Old runtimes do not provide the following, and they default to 0.
+    transaction_version: u32,
+    system_version: u8,
+}
+
+impl ScaleRuntimeVersion {
+    // Convert this into the form handed back from `state_getRuntimeVersion`, which names
+    // the fields in camel case and hex encodes the runtime API IDs.
+    fn into_runtime_version(self) -> RuntimeVersion {
+        let apis: Vec<_> = self
+            .apis
+            .iter()
+            .map(|(id, version)| (format!("0x{}", hex::encode(id)), *version))
+            .collect();
+        let other = HashMap::from_iter([
+            ("specName".to_owned(), self.spec_name.into()),
+            ("implName".to_owned(), self.impl_name.into()),
+            ("authoringVersion".to_owned(), self.authoring_version.into()),
+            ("implVersion".to_owned(), self.impl_version.into()),
+            ("apis".to_owned(), serde_json::json!(apis)),
+            ("systemVersion".to_owned(), self.system_version.into()),
+        ]);
+
+        RuntimeVersion {
+            spec_version: self.spec_version,
+            transaction_version: self.transaction_version,
+            other,
+        }
+    }
+}
+
+impl Decode for ScaleRuntimeVersion {
+    fn decode<I: codec::Input>(input: &mut I) -> Result<Self, codec::Error> {
+        let spec_name = String::decode(input)?;
+        let impl_name = String::decode(input)?;
+        let authoring_version = u32::decode(input)?;
+        let spec_version = u32::decode(input)?;
+        let impl_version = u32::decode(input)?;
+        let apis = Vec::<([u8; 8], u32)>::decode(input)?;
+
+        // Older runtimes have fewer fields, so decode what's left if there's anything:
+        let transaction_version = match input.remaining_len()? {
+            Some(0) => 0,
+            _ => u32::decode(input)?,
+        };
+        let system_version = match input.remaining_len()? {
+            Some(0) => 0,
+            _ => u8::decode(input)?,
+        };
+
+        Ok(ScaleRuntimeVersion {
+            spec_name,
+            impl_name,
+            authoring_version,
+            spec_version,
+            impl_version,
+            apis,
+            transaction_version,
+            system_version,
+        })
+    }
+}
+
+// Find the contents of the custom section with the given name in some WASM.
+fn wasm_custom_section<'a>(
+    wasm: &'a [u8],
+    name: &str,
+) -> Result<Option<&'a [u8]>, RuntimeCodeError> {
+    let Some(mut cursor) = wasm.strip_prefix(b"\0asm") else {
+        return Err(RuntimeCodeError::InvalidWasm("missing WASM magic bytes"));
+    };
+    // Skip over the 4 byte WASM version.
+    cursor = cursor
+        .get(4..)
+        .ok_or(RuntimeCodeError::InvalidWasm("missing WASM version"))?;
+
+    while let Some((&section_id, rest)) = cursor.split_first() {
+        let (section_len, rest) = read_leb128_u32(rest)?;
+        let section = rest
+            .get(..section_len as usize)
+            .ok_or(RuntimeCodeError::InvalidWasm(
+                "section length out of bounds",
+            ))?;
+        cursor = &rest[section_len as usize..];
+
+        // Custom sections have an ID of 0 and begin with their name.
+        if section_id != 0 {
+            continue;
+        }
+        let (name_len, section) = read_leb128_u32(section)?;
+        let section_name = section
+            .get(..name_len as usize)
+            .ok_or(RuntimeCodeError::InvalidWasm("section name out of bounds"))?;
+        if section_name == name.as_bytes() {
+            return Ok(Some(&section[name_len as usize..]));
+        }
+    }
+
+    Ok(None)
+}
+
+// Read an unsigned LEB128 encoded u32, returning it and the remaining bytes.
+fn read_leb128_u32(bytes: &[u8]) -> Result<(u32, &[u8]), RuntimeCodeError> {
+    let mut value: u32 = 0;
+    for (idx, &byte) in bytes.iter().enumerate().take(5) {
+        value |= ((byte & 0x7f) as u32) << (idx * 7);
+        if byte & 0x80 == 0 {
+            return Ok((value, &bytes[idx + 1..]));
+        }
+    }
+    Err(RuntimeCodeError::InvalidWasm("invalid LEB128 number"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client::test_utils::snapshot_client;
+    use crate::storage::StorageSnapshot;
+    use codec::Encode;
+
+    fn custom_section(name: &str, contents: &[u8]) -> Vec<u8> {
+        let mut section = vec![name.len() as u8];
+        section.extend_from_slice(name.as_bytes());
+        section.extend_from_slice(contents);
+
+        let mut bytes = vec![0u8, section.len() as u8];
+        bytes.extend(section);
+        bytes
+    }
+
+    fn wasm_with_sections(sections: &[Vec<u8>]) -> Vec<u8> {
+        let mut wasm = b"\0asm".to_vec();
+        wasm.extend_from_slice(&[1, 0, 0, 0]);
+        // A non custom (type) section, which should be skipped over:
+        wasm.extend_from_slice(&[1, 1, 0]);
+        for section in sections {
+            wasm.extend_from_slice(section);
+        }
+        wasm
+    }
+
+    #[test]
+    fn decodes_runtime_version_from_custom_sections() {
+        let version = (
+            "polkadot".to_string(),
+            "parity-polkadot".to_string(),
+            0u32,
+            1_005_001u32,
+            0u32,
+            Vec::<([u8; 8], u32)>::new(),
+            26u32,
+            1u8,
+        )
+            .encode();
+
+        let mut apis = Vec::new();
+        apis.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        apis.extend_from_slice(&5u32.to_le_bytes());
+
+        let wasm = wasm_with_sections(&[
+            custom_section("runtime_version", &version),
+            custom_section("runtime_apis", &apis),
+        ]);
+
+        let code = RuntimeCode::new(wasm);
+        assert!(!code.is_compressed());
+
+        let version = code.runtime_version().unwrap().unwrap();
+        assert_eq!(version.other["specName"], "polkadot");
+        assert_eq!(version.spec_version, 1_005_001);
+        assert_eq!(version.transaction_version, 26);
+        assert_eq!(version.other["systemVersion"], 1);
+        assert_eq!(
+            version.other["apis"],
+            serde_json::json!([["0x0102030405060708", 5]])
+        );
+    }
+
+    #[test]
+    fn old_runtime_versions_decode() {
+        let version = (
+            "kusama".to_string(),
+            "parity-kusama".to_string(),
+            2u32,
+            1020u32,
+            0u32,
+            Vec::<([u8; 8], u32)>::new(),
+        )
+            .encode();
+
+        let wasm = wasm_with_sections(&[custom_section("runtime_version", &version)]);
+        let version = RuntimeCode::new(wasm).runtime_version().unwrap().unwrap();
+
+        assert_eq!(version.spec_version, 1020);
+        assert_eq!(version.transaction_version, 0);
+        assert_eq!(version.other["systemVersion"], 0);
+    }
+
+    #[test]
+    fn missing_runtime_version_section() {
+        let wasm = wasm_with_sections(&[custom_section("other", &[1, 2, 3])]);
+        assert_eq!(RuntimeCode::new(wasm).runtime_version().unwrap(), None);
+    }
+
+    #[test]
+    fn invalid_wasm_is_an_error() {
+        let code = RuntimeCode::new(vec![1, 2, 3, 4]);
+        assert!(matches!(
+            code.runtime_version(),
+            Err(RuntimeCodeError::InvalidWasm(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn well_known_values_must_decode_fully() {
+        let mut snapshot = StorageSnapshot::new();
+        snapshot.insert(WellKnownStorageKey::HeapPages.to_key(), 8u64.encode());
+        snapshot.insert(
+            WellKnownStorageKey::ExtrinsicIndex.to_key(),
+            vec![1, 0, 0, 0, 0],
+        );
+
+        let client = snapshot_client(snapshot);
+        let well_known = client.storage().well_known();
+
+        assert_eq!(well_known.heap_pages().await.unwrap(), Some(8));
+        assert!(matches!(
+            well_known.extrinsic_index().await,
+            Err(StorageError::CannotDecodeWellKnownValue { .. })
+        ));
+    }
+}
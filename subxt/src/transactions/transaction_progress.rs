@@ -5,9 +5,10 @@ use crate::client::{
 };
 use crate::config::{Config, HashFor};
 use crate::error::{
-    DispatchError, OnlineClientAtBlockError, TransactionEventsError,
+    DispatchError, EventsError, OnlineClientAtBlockError, TransactionEventsError,
     TransactionFinalizedSuccessError, TransactionProgressError, TransactionStatusError,
 };
+use crate::events::Event;
 use crate::extrinsics::ExtrinsicEvents;
 use futures::{Stream, StreamExt};
 use std::pin::Pin;
@@ -297,6 +298,23 @@ impl<T: Config, C: OnlineClientAtBlockT<T>> TransactionInBlock<T, C> {
         Ok(events)
     }
 
+    /// This is like [`TransactionInBlock::wait_for_success`], but additionally treats the
+    /// transaction as having failed if it emitted one of a handful of well known events which
+    /// report an error dispatching some inner call. These include `Utility::BatchInterrupted`,
+    /// `Utility::ItemFailed`, `Proxy::ProxyExecuted`, `Sudo::Sudid` and `Multisig::MultisigExecuted`.
+    /// See [`crate::events::Event::nested_dispatch_error`] for more.
+    ///
+    /// **Note:** This has to download block details from the node and decode events
+    /// from them.
+    pub async fn wait_for_success_including_nested(
+        &self,
+    ) -> Result<ExtrinsicEvents<T>, TransactionEventsError> {
+        let events = self.wait_for_success().await?;
+        check_nested_dispatch_errors(events.iter(), self.block_hash())?;
+
+        Ok(events)
+    }
+
     /// Fetch all of the events associated with this transaction. This succeeds whether
     /// the transaction was a success or not; it's up to you to handle the error and
     /// success events however you prefer.
@@ -353,3 +371,87 @@ impl<T: Config, C: OnlineClientAtBlockT<T>> TransactionInBlock<T, C> {
         Ok(events)
     }
 }
+
+// Return an error if any of the given events report that some inner call failed to dispatch.
+fn check_nested_dispatch_errors<'events, T: Config>(
+    events: impl Iterator<Item = Result<Event<'events, T>, EventsError>>,
+    block_hash: HashFor<T>,
+) -> Result<(), TransactionEventsError> {
+    for (ev_idx, ev) in events.enumerate() {
+        let ev = ev.map_err(|e| TransactionEventsError::CannotDecodeEventInBlock {
+            event_index: ev_idx,
+            block_hash: block_hash.into(),
+            error: e,
+        })?;
+
+        if let Some(dispatch_error) = ev.nested_dispatch_error() {
+            let dispatch_error =
+                dispatch_error.map_err(|e| TransactionEventsError::CannotDecodeDispatchError {
+                    error: e,
+                    bytes: ev.field_bytes().to_vec(),
+                })?;
+            return Err(TransactionEventsError::NestedDispatchFailed {
+                pallet_name: ev.pallet_name().to_string(),
+                event_name: ev.event_name().to_string(),
+                error: dispatch_error,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::events::Phase;
+    use crate::events::test_utils::{event_record, events, metadata_with_pallet_name};
+    use codec::Encode;
+    use scale_info::TypeInfo;
+
+    #[derive(Encode, TypeInfo)]
+    enum UtilityEvent {
+        ItemCompleted,
+        BatchInterrupted {
+            index: u32,
+            error: sp_runtime::DispatchError,
+        },
+    }
+
+    #[test]
+    fn nested_dispatch_errors_are_reported() {
+        let metadata = metadata_with_pallet_name::<UtilityEvent>("Utility");
+
+        let ok = events(
+            metadata.clone(),
+            vec![event_record(
+                Phase::ApplyExtrinsic(0),
+                UtilityEvent::ItemCompleted,
+            )],
+        );
+        assert!(check_nested_dispatch_errors(ok.iter(), Default::default()).is_ok());
+
+        let interrupted = events(
+            metadata,
+            vec![
+                event_record(Phase::ApplyExtrinsic(0), UtilityEvent::ItemCompleted),
+                event_record(
+                    Phase::ApplyExtrinsic(0),
+                    UtilityEvent::BatchInterrupted {
+                        index: 1,
+                        error: sp_runtime::DispatchError::BadOrigin,
+                    },
+                ),
+            ],
+        );
+        let err = check_nested_dispatch_errors(interrupted.iter(), Default::default()).unwrap_err();
+        assert!(matches!(
+            err,
+            TransactionEventsError::NestedDispatchFailed {
+                pallet_name,
+                event_name,
+                error: DispatchError::BadOrigin,
+            } if pallet_name == "Utility" && event_name == "BatchInterrupted"
+        ));
+    }
+}
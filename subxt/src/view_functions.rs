@@ -17,7 +17,7 @@ use std::marker::PhantomData;
 pub use payload::{DynamicPayload, Payload, StaticPayload, dynamic};
 
 /// The name of the Runtime API call which can execute
-const CALL_NAME: &str = "RuntimeViewFunction_execute_view_function";
+pub(crate) const CALL_NAME: &str = "RuntimeViewFunction_execute_view_function";
 
 /// A client for working with View Functions. See [the module docs](crate::view_functions) for more.
 #[derive_where(Clone; Client)]
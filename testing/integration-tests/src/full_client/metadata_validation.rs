@@ -137,13 +137,32 @@ async fn full_metadata_check() {
     // Runtime metadata is identical to the metadata we just downloaded
     let metadata_before = v15_to_metadata(v15_metadata.clone());
     assert!(node_runtime::is_codegen_valid_for(&metadata_before));
+    let report = node_runtime::codegen_compatibility_report(&metadata_before);
+    assert!(report.is_compatible());
+    assert_eq!(report.checked_items(), node_runtime::CODEGEN_ITEMS.len());
 
     // Modify the metadata.
+    let old_pallet_name = v15_metadata.pallets[0].name.clone();
     v15_metadata.pallets[0].name = "NewPallet".to_string();
 
     // It should now be invalid:
     let metadata_after = v15_to_metadata(v15_metadata);
     assert!(!node_runtime::is_codegen_valid_for(&metadata_after));
+
+    // And the report should point at the items from the renamed pallet:
+    let report = node_runtime::codegen_compatibility_report(&metadata_after);
+    assert!(!report.is_compatible());
+    assert!(
+        report
+            .issues()
+            .iter()
+            .all(|issue| issue.parent() == old_pallet_name)
+    );
+    assert!(
+        report
+            .issues_by_parent()
+            .contains_key(old_pallet_name.as_str())
+    );
 }
 
 #[subxt_test]
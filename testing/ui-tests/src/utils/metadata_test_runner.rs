@@ -97,6 +97,8 @@ impl MetadataTestRunnerCaseBuilder {
     /// - checks that the `runtime::is_codegen_valid_for` function returns
     ///   true or false when compared to the `validation_metadata`, according
     ///   to whether `expects_invalid()` is set or not.
+    /// - checks that `runtime::codegen_compatibility_report` is compatible when
+    ///   the code is valid, and that it checked every item in `runtime::CODEGEN_ITEMS`.
     ///
     /// The generated code will be tidied up when the `MetadataTestRunner` that
     /// this was handed out from is dropped.
@@ -161,6 +163,15 @@ impl MetadataTestRunnerCaseBuilder {
                 // validate it:
                 let is_valid = polkadot::is_codegen_valid_for(&metadata);
                 assert_eq!(is_valid, {should_be_valid_str}, "expected validity to line up");
+
+                // the compatibility report only checks individual items, so it can't notice every
+                // change that makes the code invalid, but valid code must be compatible:
+                let report = polkadot::codegen_compatibility_report(&metadata);
+                if is_valid {{
+                    assert!(report.is_compatible(), "expected the compatibility report to line up");
+                }}
+                assert_eq!(report.checked_items(), polkadot::CODEGEN_ITEMS.len());
+                assert_eq!(report.issues().is_empty(), report.is_compatible());
             }}
         "#
         );